@@ -21,6 +21,11 @@ fn main() {
 	cli::main();
 }
 
+#[cfg(feature = "cli")]
+mod eth {
+	include!("src/eth.rs");
+}
+
 #[cfg(feature = "cli")]
 mod cli {
 	include!("src/cli.rs");
@@ -486,7 +486,10 @@ pub fn local_testnet_config() -> ChainSpec {
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use crate::service::{new_full_base, NewFullBase};
+	use crate::{
+		eth::EthConfiguration,
+		service::{new_full_base, NewFullBase},
+	};
 	use sc_service_test;
 	use sp_runtime::BuildStorage;
 
@@ -539,7 +542,7 @@ pub(crate) mod tests {
 
 		sc_service_test::connectivity(integration_test_config_with_two_authorities(), |config| {
 			let NewFullBase { task_manager, client, network, transaction_pool, .. } =
-				new_full_base(config, false, |_, _| ())?;
+				new_full_base(config, false, EthConfiguration::default(), |_, _| ())?;
 			Ok(sc_service_test::TestNetComponents::new(
 				task_manager,
 				client,
@@ -86,8 +86,9 @@ pub fn run() -> Result<()> {
 	match &cli.subcommand {
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let eth_config = cli.eth.clone();
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config, cli.no_hardware_benchmarks)
+				service::new_full(config, cli.no_hardware_benchmarks, eth_config)
 					.map_err(sc_cli::Error::Service)
 			})
 		},
@@ -0,0 +1,115 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2018-2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+/// Settings of the Ethereum RPC APIs.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "cli", derive(clap::Parser))]
+pub struct EthConfiguration {
+	/// Maximum number of logs returned by a single `eth_getLogs` query.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 10000))]
+	pub max_past_logs: u32,
+
+	/// Maximum number of blocks kept in the fee history cache, and thus the maximum
+	/// block count of an `eth_feeHistory` query.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 1000))]
+	pub fee_history_limit: u64,
+
+	/// Size in bytes of the LRU cache of block data.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 50))]
+	pub eth_log_block_cache: usize,
+
+	/// Size in bytes of the LRU cache of transaction statuses data.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 50))]
+	pub eth_statuses_cache: usize,
+
+	/// Multiplier of the block gas limit giving the maximum gas limit of
+	/// `eth_call` and `eth_estimateGas`.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 1000))]
+	pub execute_gas_limit_multiplier: u64,
+
+	/// Tolerance of the `eth_estimateGas` binary search in per mille of the estimate.
+	/// The search stops once the gap between its bounds falls below it.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 15))]
+	pub estimate_gas_tolerance: u32,
+
+	/// Number of confirmations after which a block is reported as the `safe` block.
+	/// The `safe` block is the last finalized block when unset.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub eth_safe_depth: Option<u32>,
+
+	/// Enable the development signer, holding well known test accounts.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub enable_dev_signer: bool,
+
+	/// Enable the keystore signer, holding the Ethereum accounts inserted in the node
+	/// keystore under the `eths` key type.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub enable_keystore_signer: bool,
+
+	/// Enable the `personal` namespace, managing Ethereum accounts stored encrypted in
+	/// the `ethereum` directory of the keystore. The accounts only sign through RPC
+	/// servers allowing unsafe methods.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub enable_personal_accounts: bool,
+
+	/// Enable the `debug_*` and `trace_*` RPC namespaces, which are unsafe. Needs a runtime
+	/// built with the `evm-tracing` feature.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub enable_evm_tracing: bool,
+
+	/// Maximum number of blocks a `trace_filter` request may range over.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 1000))]
+	pub trace_filter_max_block_range: u32,
+
+	/// Maintain an SQLite index of the Ethereum logs to answer `eth_getLogs` queries.
+	/// Ranges holding blocks synced before the index was enabled are still answered
+	/// by scanning the block blooms.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub eth_log_index: bool,
+
+	/// Index the Ethereum mappings of the finalized blocks in parallel batches on startup,
+	/// resuming from the last batch written, before following the imported blocks.
+	#[cfg_attr(feature = "cli", arg(long))]
+	pub mapping_catch_up: bool,
+
+	/// Number of blocks indexed per batch by the mapping sync catch-up.
+	#[cfg_attr(feature = "cli", arg(long, default_value_t = 1000))]
+	pub mapping_catch_up_batch_size: u32,
+}
+
+impl Default for EthConfiguration {
+	fn default() -> Self {
+		Self {
+			max_past_logs: 10000,
+			fee_history_limit: 1000,
+			eth_log_block_cache: 50,
+			eth_statuses_cache: 50,
+			execute_gas_limit_multiplier: 1000,
+			estimate_gas_tolerance: 15,
+			eth_safe_depth: None,
+			enable_dev_signer: false,
+			enable_keystore_signer: false,
+			enable_personal_accounts: false,
+			enable_evm_tracing: false,
+			trace_filter_max_block_range: 1000,
+			eth_log_index: false,
+			mapping_catch_up: false,
+			mapping_catch_up_batch_size: 1000,
+		}
+	}
+}
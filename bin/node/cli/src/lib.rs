@@ -31,6 +31,8 @@
 #![warn(missing_docs)]
 
 pub mod chain_spec;
+/// Settings of the Ethereum RPC APIs.
+pub mod eth;
 
 #[macro_use]
 pub mod service;
@@ -60,13 +60,13 @@ use sc_client_api::{
 };
 
 //=============================================
-use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::{traits::BlakeTwo256, Permill};
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_network::NetworkService;
 use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 // Frontier
 use fc_rpc::{
-	EthBlockDataCacheTask, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override,
+	EthAccountStore, EthBlockDataCacheTask, EthMetrics, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override,
 	SchemaV2Override, SchemaV3Override, StorageOverride,
 };
 use fp_storage::EthereumStorageSchema;
@@ -117,12 +117,22 @@ pub struct FullDeps<C, P, SC, B,A:ChainApi> {
 	pub is_authority: bool,
 	/// Whether to enable dev signer
 	pub enable_dev_signer: bool,
+	/// Whether to enable keystore signer
+	pub enable_keystore_signer: bool,
+	/// Encrypted accounts of the personal namespace
+	pub eth_accounts: Option<EthAccountStore>,
+	/// Whether to enable the debug and trace namespaces
+	pub enable_evm_tracing: bool,
+	/// Maximum number of blocks a `trace_filter` request may range over.
+	pub trace_filter_max_block_range: u32,
 	/// Network service
 	pub network: Arc<NetworkService<Block, Hash>>,
 	/// EthFilterApi pool.
 	pub filter_pool: Option<FilterPool>,
 	/// Backend.
 	pub backend: Arc<fc_db::Backend<Block>>,
+	/// Log index used by `eth_getLogs`, if enabled.
+	pub log_index: Option<Arc<fc_db::sql::LogIndexDb<Block>>>,
 	/// Maximum number of logs in a query.
 	pub max_past_logs: u32,
 	/// Fee history cache.
@@ -134,6 +144,12 @@ pub struct FullDeps<C, P, SC, B,A:ChainApi> {
 	/// Cache for Ethereum block data.
 	pub block_data_cache: Arc<EthBlockDataCacheTask<Block>>,
 	pub execute_gas_limit_multiplier: u64,
+	/// Tolerance of the `eth_estimateGas` binary search.
+	pub estimate_gas_tolerance: Permill,
+	/// Number of confirmations of the `safe` block, the finalized block when `None`.
+	pub safe_depth: Option<u32>,
+	/// Prometheus metrics of the Ethereum RPC layer.
+	pub eth_metrics: Option<Arc<EthMetrics>>,
 }
 
 
@@ -189,6 +205,7 @@ BE::State: StateBackend<BlakeTwo256>,
 		+ Sync
 		+ Send
 		+ StorageProvider<Block, BE>
+		+ sc_client_api::ProofProvider<Block>
 		+ 'static,
 	C: BlockchainEvents<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
@@ -205,6 +222,8 @@ BE::State: StateBackend<BlakeTwo256>,
 	SC: SelectChain<Block> + 'static,
 	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
 	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+	C::Api: fp_rpc::DebugRuntimeApi<Block>,
+	C::Api: fp_rpc::EthereumProofRuntimeApi<Block>,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
 	A: ChainApi<Block = Block> + 'static,
@@ -220,23 +239,31 @@ BE::State: StateBackend<BlakeTwo256>,
 	pub use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 	use fc_rpc::{
-		Eth,  EthDevSigner, EthFilter, EthFilterApiServer, EthPubSub,EthPubSubApiServer,
-		 EthSigner, Net, NetApiServer, Web3, Web3ApiServer,EthApiServer
+		Debug, DebugApiServer, Eth,  EthDevSigner, EthFilter, EthFilterApiServer, EthPubSub,EthPubSubApiServer,
+		 EthKeystoreSigner, EthSigner, Net, NetApiServer, Personal, PersonalApiServer, Trace, TraceApiServer, TxPool, TxPoolApiServer, Web3, Web3ApiServer,EthApiServer
 	};
 
 	let mut io = RpcModule::new(());
 	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa,graph,
 		is_authority,
 		enable_dev_signer,
+		enable_keystore_signer,
+		eth_accounts,
+		enable_evm_tracing,
+		trace_filter_max_block_range,
 		network,
 		filter_pool,
 		backend,
+		log_index,
 		max_past_logs,
 		fee_history_cache,
 		fee_history_cache_limit,
 		overrides,
 		block_data_cache,
-		execute_gas_limit_multiplier } = deps;
+		execute_gas_limit_multiplier,
+		estimate_gas_tolerance,
+		safe_depth,
+		eth_metrics } = deps;
 
 	let BabeDeps { keystore, babe_config, shared_epoch_changes } = babe;
 	let GrandpaDeps {
@@ -256,10 +283,21 @@ BE::State: StateBackend<BlakeTwo256>,
 	// io.merge(Contracts::new(client.clone()).into_rpc())?;
 	// io.merge(Mmr::new(client.clone()).into_rpc())?;
 	io.merge(TransactionPayment::new(client.clone()).into_rpc())?;
-	let mut signers = Vec::new();
-	if enable_dev_signer {
-		signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
-	}
+	let signer_keystore = keystore.clone();
+	let signers = || {
+		let mut signers = Vec::new();
+		if enable_dev_signer {
+			signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
+		}
+		if enable_keystore_signer {
+			signers.push(Box::new(EthKeystoreSigner::new(signer_keystore.clone())) as Box<dyn EthSigner>);
+		}
+		// Accounts of the personal namespace only sign through servers allowing unsafe calls.
+		if let (Some(eth_accounts), Ok(())) = (&eth_accounts, deny_unsafe.check_if_safe()) {
+			signers.push(Box::new(eth_accounts.clone()) as Box<dyn EthSigner>);
+		}
+		signers
+	};
 	io.merge(
 		Babe::new(
 			client.clone(),
@@ -288,25 +326,32 @@ BE::State: StateBackend<BlakeTwo256>,
 	)?;
 	// io.merge(StateMigration::new(client.clone(), backend, deny_unsafe).into_rpc())?;
 
-    io.merge(
+	let eth = || {
 		Eth::new(
 			client.clone(),
-			pp,
-			graph,
+			pp.clone(),
+			graph.clone(),
 			Some(node_5ire_runtime::TransactionConverter),
 			network.clone(),
-			signers,
+			signers(),
 			overrides.clone(),
 			backend.clone(),
 			// Is authority.
 			is_authority,
 			block_data_cache.clone(),
-			fee_history_cache,
+			fee_history_cache.clone(),
 			fee_history_cache_limit,
 			execute_gas_limit_multiplier
 		)
-		.into_rpc(),
-	)?;
+		.with_estimate_gas_tolerance(estimate_gas_tolerance)
+		.with_safe_depth(safe_depth)
+		.with_deny_unsafe(deny_unsafe)
+		.with_metrics(eth_metrics.clone())
+	};
+    io.merge(eth().into_rpc())?;
+	if let Some(eth_accounts) = eth_accounts.clone() {
+		io.merge(Personal::new(eth(), eth_accounts, deny_unsafe).into_rpc())?;
+	}
 
 
     if let Some(filter_pool) = filter_pool {
@@ -314,12 +359,14 @@ BE::State: StateBackend<BlakeTwo256>,
 		io.merge(
 			EthFilter::new(
 				client.clone(),
-				backend,
+				backend.clone(),
+				log_index,
 				filter_pool,
 				500_usize, // max stored filters
 				max_past_logs,
-				block_data_cache,
+				block_data_cache.clone(),
 			)
+			.with_metrics(eth_metrics.clone())
 			.into_rpc(),
 		)?;
 	}
@@ -331,6 +378,7 @@ BE::State: StateBackend<BlakeTwo256>,
 			subscription_task_executor,
 			overrides,
 		)
+		.with_metrics(eth_metrics)
 		.into_rpc(),
 	)?;
 
@@ -345,6 +393,28 @@ BE::State: StateBackend<BlakeTwo256>,
 		.into_rpc(),
 	)?;
 
+	if enable_evm_tracing {
+		io.merge(
+			Trace::new(
+				client.clone(),
+				backend.clone(),
+				block_data_cache,
+				trace_filter_max_block_range,
+				deny_unsafe,
+			)
+			.with_safe_depth(safe_depth)
+			.into_rpc(),
+		)?;
+
+		io.merge(
+			Debug::new(client.clone(), backend, deny_unsafe)
+				.with_safe_depth(safe_depth)
+				.into_rpc(),
+		)?;
+	}
+
+	io.merge(TxPool::new(client.clone(), graph).into_rpc())?;
+
 	io.merge(Web3::new(client).into_rpc())?;
 
 	// io.merge(Dev::new(client, deny_unsafe).into_rpc())?;
@@ -20,7 +20,10 @@
 
 //! Service implementation. Specialized wrapper over substrate service.
 
-use crate::rpc::{create_full, BabeDeps, FullDeps, GrandpaDeps};
+use crate::{
+	eth::EthConfiguration,
+	rpc::{create_full, BabeDeps, FullDeps, GrandpaDeps},
+};
 use fc_db::Backend as FrontierBackend;
 use node_5ire_runtime::{self, opaque::Block, RuntimeApi};
 
@@ -40,7 +43,7 @@ use sc_service::{config::Configuration, error::Error as ServiceError,BasePath, R
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_api::ProvideRuntimeApi;
 use sp_core::crypto::Pair;
-use sp_runtime::{generic, traits::Block as BlockT, SaturatedConversion};
+use sp_runtime::{generic, traits::Block as BlockT, Permill, SaturatedConversion};
 use futures::{future, StreamExt};
 
 // use std::sync::Arc;
@@ -52,8 +55,8 @@ use std::{
 };
 
 // Frontier
-use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
-use fc_rpc::{EthTask, OverrideHandle};
+use fc_mapping_sync::{CatchUpConfig, MappingSyncWorker, SyncStrategy};
+use fc_rpc::{EthAccountStore, EthMetrics, EthTask, OverrideHandle};
 use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -190,6 +193,7 @@ pub struct NewFullBase {
 pub fn new_full_base(
 	mut config: Configuration,
 	disable_hardware_benchmarks: bool,
+	eth_config: EthConfiguration,
 	with_startup_data: impl FnOnce(
 		&sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
 		&sc_consensus_babe::BabeLink<Block>,
@@ -267,10 +271,16 @@ pub fn new_full_base(
 	let block_data_cache = Arc::new(fc_rpc::EthBlockDataCacheTask::new(
 		task_manager.spawn_handle(),
 		overrides.clone(),
-		50,
-		50,
+		eth_config.eth_log_block_cache,
+		eth_config.eth_statuses_cache,
 		prometheus_registry.clone(),
 	));
+	let eth_metrics = prometheus_registry.as_ref().and_then(|registry| {
+		EthMetrics::register(registry)
+			.map_err(|e| log::warn!("Failed to register Ethereum RPC metrics: {}", e))
+			.ok()
+			.map(Arc::new)
+	});
 
 
 
@@ -299,10 +309,27 @@ pub fn new_full_base(
 		let _chain_spec = config.chain_spec.cloned_box();
 
 		let filter_pool: Option<FilterPool> = Some(Arc::new(Mutex::new(BTreeMap::new())));
+		let log_index = if eth_config.eth_log_index {
+			Some(Arc::new(
+				fc_db::sql::LogIndexDb::<Block>::open(
+					&fc_db::frontier_database_dir(&db_config_dir(&config), "sql").join("logs.db"),
+				)
+				.map_err(ServiceError::Other)?,
+			))
+		} else {
+			None
+		};
+		// Accounts of the `personal` namespace, stored encrypted next to the node keys.
+		let eth_accounts = match config.keystore.path() {
+			Some(path) if eth_config.enable_personal_accounts => {
+				Some(EthAccountStore::open(path.join("ethereum")).map_err(ServiceError::Other)?)
+			}
+			_ => None,
+		};
 
 	    let fee_history_cache: FeeHistoryCache = Arc::new(Mutex::new(BTreeMap::new()));
-	    let fee_history_cache_limit: FeeHistoryCacheLimit = 1000;
-		let execute_gas_limit_multiplier  = 1000;
+	    let fee_history_cache_limit: FeeHistoryCacheLimit = eth_config.fee_history_limit;
+		let execute_gas_limit_multiplier = eth_config.execute_gas_limit_multiplier;
 		let rpc_backend = backend.clone();
 		// let prometheus_registry = config.prometheus_registry().cloned();
 		// let overrides = crate::rpc::overrides_handle(client.clone());
@@ -316,8 +343,11 @@ pub fn new_full_base(
 		
 		let rpc_extensions_builder =  {
 			let is_authority = false;
-			let enable_dev_signer = false;
-			let max_past_logs = 10000;
+			let enable_dev_signer = eth_config.enable_dev_signer;
+			let enable_keystore_signer = eth_config.enable_keystore_signer;
+			let enable_evm_tracing = eth_config.enable_evm_tracing;
+			let trace_filter_max_block_range = eth_config.trace_filter_max_block_range;
+			let max_past_logs = eth_config.max_past_logs;
 			let chain_spec = config.chain_spec.cloned_box();
 			let client = client.clone();
 	
@@ -331,6 +361,12 @@ pub fn new_full_base(
 			let execute_gas_limit_multiplier =  execute_gas_limit_multiplier.clone();
 			let overrides = overrides.clone();
 			let filter_pool=filter_pool.clone();
+			let log_index = log_index.clone();
+			let estimate_gas_tolerance =
+				Permill::from_perthousand(eth_config.estimate_gas_tolerance);
+			let safe_depth = eth_config.eth_safe_depth;
+			let eth_accounts = eth_accounts.clone();
+			let eth_metrics = eth_metrics.clone();
 
 			Box::new(move |deny_unsafe, subscription_executor|
 				{
@@ -356,15 +392,23 @@ pub fn new_full_base(
 				},
 				is_authority,
 				enable_dev_signer,
+				enable_keystore_signer,
+				eth_accounts: eth_accounts.clone(),
+				enable_evm_tracing,
+				trace_filter_max_block_range,
 				overrides: overrides.clone(),
 				block_data_cache: block_data_cache.clone(),
 				network: network.clone(),
 				filter_pool: filter_pool.clone(),
 				backend: frontier_backend.clone(),
+				log_index: log_index.clone(),
 				max_past_logs,
 				fee_history_cache:fee_history_cache.clone(),
 				fee_history_cache_limit:fee_history_cache_limit.clone(),
 				execute_gas_limit_multiplier: execute_gas_limit_multiplier.clone(),
+				estimate_gas_tolerance,
+				safe_depth,
+				eth_metrics: eth_metrics.clone(),
 
 			   };
 			create_full(deps,subscription_task_executor.clone(),rpc_backend.clone()).map_err(Into::into)
@@ -390,18 +434,22 @@ pub fn new_full_base(
 		telemetry: telemetry.as_mut(),
 	})?;
 
-	let fee_history_cache: FeeHistoryCache = Arc::new(Mutex::new(BTreeMap::new()));
-	let fee_history_cache_limit: FeeHistoryCacheLimit = 1000;
-	let filter_pool: Option<FilterPool> = Some(Arc::new(Mutex::new(BTreeMap::new())));
+	let mapping_catch_up = eth_config.mapping_catch_up.then(|| CatchUpConfig {
+		batch_size: eth_config.mapping_catch_up_batch_size,
+		..Default::default()
+	});
 	spawn_frontier_tasks(
 		&task_manager,
 		client.clone(),
 		backends,
 		frontier_backend,
+		log_index,
+		mapping_catch_up,
 		filter_pool,
 		overrides.clone(),
 		fee_history_cache,
 		fee_history_cache_limit,
+		eth_metrics,
 	);
 
 
@@ -696,27 +744,48 @@ fn spawn_frontier_tasks(
 	client: Arc<FullClient>,
 	backend: Arc<FullBackend>,
 	frontier_backend: Arc<FrontierBackend<Block>>,
+	log_index: Option<Arc<fc_db::sql::LogIndexDb<Block>>>,
+	mapping_catch_up: Option<CatchUpConfig>,
 	filter_pool: Option<FilterPool>,
 	overrides: Arc<OverrideHandle<Block>>,
 	fee_history_cache: FeeHistoryCache,
 	fee_history_cache_limit: FeeHistoryCacheLimit,
+	eth_metrics: Option<Arc<EthMetrics>>,
 ) {
+	let mut mapping_sync_worker = MappingSyncWorker::new(
+		client.import_notification_stream(),
+		Duration::new(6, 0),
+		client.clone(),
+		backend,
+		frontier_backend.clone(),
+		log_index,
+		3,
+		0,
+		SyncStrategy::Normal,
+	);
+	if let Some(config) = mapping_catch_up {
+		mapping_sync_worker = mapping_sync_worker.with_catch_up(config);
+	}
 	task_manager.spawn_essential_handle().spawn(
 		"frontier-mapping-sync-worker",
 		None,
-		MappingSyncWorker::new(
-			client.import_notification_stream(),
-			Duration::new(6, 0),
-			client.clone(),
-			backend,
-			frontier_backend,
-			3,
-			0,
-			SyncStrategy::Normal,
-		)
-		.for_each(|()| future::ready(())),
+		mapping_sync_worker.for_each(|()| future::ready(())),
 	);
 
+	// Spawn Frontier Prometheus metrics task.
+	if let Some(eth_metrics) = eth_metrics {
+		task_manager.spawn_handle().spawn(
+			"frontier-metrics",
+			None,
+			EthTask::metrics_task(
+				client.clone(),
+				frontier_backend,
+				filter_pool.clone(),
+				eth_metrics,
+			),
+		);
+	}
+
 	// Spawn Frontier EthFilterApi maintenance task.
 	if let Some(filter_pool) = filter_pool {
 		// Each filter is allowed to stay in the pool for 100 blocks.
@@ -739,14 +808,18 @@ fn spawn_frontier_tasks(
 pub fn new_full(
 	config: Configuration,
 	disable_hardware_benchmarks: bool,
+	eth_config: EthConfiguration,
 ) -> Result<TaskManager, ServiceError> {
-	new_full_base(config, disable_hardware_benchmarks, |_, _| ())
+	new_full_base(config, disable_hardware_benchmarks, eth_config, |_, _| ())
 		.map(|NewFullBase { task_manager, .. }| task_manager)
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::service::{new_full_base, NewFullBase};
+	use crate::{
+		eth::EthConfiguration,
+		service::{new_full_base, NewFullBase},
+	};
 	use codec::Encode;
 	use node_primitives::{Block, DigestItem, Signature};
 	use node_5ire_runtime::{
@@ -809,6 +882,7 @@ mod tests {
 					new_full_base(
 						config,
 						false,
+						EthConfiguration::default(),
 						|block_import: &sc_consensus_babe::BabeBlockImport<Block, _, _>,
 						 babe_link: &sc_consensus_babe::BabeLink<Block>| {
 							setup_handles = Some((block_import.clone(), babe_link.clone()));
@@ -990,7 +1064,7 @@ mod tests {
 			crate::chain_spec::tests::integration_test_config_with_two_authorities(),
 			|config| {
 				let NewFullBase { task_manager, client, network, transaction_pool, .. } =
-					new_full_base(config, false, |_, _| ())?;
+					new_full_base(config, false, EthConfiguration::default(), |_, _| ())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					task_manager,
 					client,
@@ -329,6 +329,11 @@ impl pallet_evm::Config for Runtime {
 	type OnChargeTransaction = pallet_evm::EVMCurrencyAdapter<Balances, DealWithFees>;
 	// type FindAuthor = (); //FindAuthorTruncated<Aura>;
 	type FindAuthor = FindAuthorTruncated<Babe>;
+
+	/// Keep London rules until the chain schedules the Shanghai upgrade.
+	fn fork() -> pallet_evm::EvmFork {
+		pallet_evm::EvmFork::London
+	}
 }
 impl pallet_ethereum::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
@@ -372,6 +377,13 @@ impl pallet_hotfix_sufficients::Config for Runtime {
 }
 
 impl pallet_evm_chain_id::Config for Runtime {}
+
+impl pallet_evm_precompile_balances_erc20::Config for Runtime {}
+
+impl pallet_evm_precompile_assets_erc20::Config for Runtime {}
+
+impl pallet_evm_precompile_call_permit::Config for Runtime {}
+
 parameter_types! {
 	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
 	pub const DepositBase: Balance = deposit(1, 88);
@@ -1940,6 +1952,9 @@ construct_runtime!(
 		BaseFee: pallet_base_fee,
 		HotfixSufficients: pallet_hotfix_sufficients,
 		EVMChainId: pallet_evm_chain_id,
+		BalancesErc20: pallet_evm_precompile_balances_erc20,
+		AssetsErc20: pallet_evm_precompile_assets_erc20,
+		CallPermit: pallet_evm_precompile_call_permit,
 
 	}
 );
@@ -2078,6 +2093,39 @@ impl fp_self_contained::SelfContainedCall for RuntimeCall {
 	}
 }
 
+/// Root call of a transaction which failed before the EVM ran it, carrying the dispatch
+/// error as its exit reason.
+#[cfg(feature = "evm-tracing")]
+fn failed_call_trace(
+	from: H160,
+	transaction: &EthereumTransaction,
+	error: sp_runtime::DispatchError,
+) -> fp_evm::tracing::CallTrace {
+	let (action, value, gas_limit, input) = match transaction {
+		EthereumTransaction::Legacy(t) => (t.action, t.value, t.gas_limit, t.input.clone()),
+		EthereumTransaction::EIP2930(t) => (t.action, t.value, t.gas_limit, t.input.clone()),
+		EthereumTransaction::EIP1559(t) => (t.action, t.value, t.gas_limit, t.input.clone()),
+	};
+	let (call_type, to) = match action {
+		pallet_ethereum::TransactionAction::Call(to) => (fp_evm::tracing::CallType::Call, to),
+		pallet_ethereum::TransactionAction::Create => (fp_evm::tracing::CallType::Create, H160::zero()),
+	};
+	fp_evm::tracing::CallTrace {
+		call_type,
+		from,
+		to,
+		value,
+		gas: gas_limit,
+		gas_used: U256::zero(),
+		input,
+		output: Vec::new(),
+		exit_reason: Some(fp_evm::ExitReason::Error(fp_evm::ExitError::Other(
+			<&'static str>::from(error).into(),
+		))),
+		calls: Vec::new(),
+	}
+}
+
 /// MMR helper types.
 mod mmr {
 	use super::Runtime;
@@ -2328,6 +2376,7 @@ impl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			state_overrides: Option<Vec<(H160, pallet_evm::StateOverride)>>,
 		) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
 			let config = if estimate {
 				let mut config = <Runtime as pallet_evm::Config>::config().clone();
@@ -2339,19 +2388,24 @@ impl_runtime_apis! {
 			let is_transactional = false;
 			let validate = true;
 			let evm_config = config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
-			<Runtime as pallet_evm::Config>::Runner::call(
-				from,
-				to,
-				data,
-				value,
-				gas_limit.low_u64(),// gas_limit.unique_saturated_into(),
-				max_fee_per_gas,
-				max_priority_fee_per_gas,
-				nonce,
-				access_list.unwrap_or_default(),
-				is_transactional,
-				validate,
-				evm_config,
+			<Runtime as pallet_evm::Config>::Runner::with_state_overrides(
+				state_overrides.unwrap_or_default(),
+				|| {
+					<Runtime as pallet_evm::Config>::Runner::call(
+						from,
+						to,
+						data,
+						value,
+						gas_limit.low_u64(),// gas_limit.unique_saturated_into(),
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						access_list.unwrap_or_default(),
+						is_transactional,
+						validate,
+						evm_config,
+					)
+				},
 			).map_err(|err| err.error.into())
 		}
 		fn create(
@@ -2364,6 +2418,7 @@ impl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			state_overrides: Option<Vec<(H160, pallet_evm::StateOverride)>>,
 		) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
 			let config = if estimate {
 				let mut config = <Runtime as pallet_evm::Config>::config().clone();
@@ -2375,18 +2430,23 @@ impl_runtime_apis! {
 			let is_transactional = false;
 			let validate = true;
 			let evm_config = config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
-			<Runtime as pallet_evm::Config>::Runner::create(
-				from,
-				data,
-				value,
-				gas_limit.low_u64(),// gas_limit.unique_saturated_into(),
-				max_fee_per_gas,
-				max_priority_fee_per_gas,
-				nonce,
-				access_list.unwrap_or_default(),
-				is_transactional,
-				validate,
-				evm_config,
+			<Runtime as pallet_evm::Config>::Runner::with_state_overrides(
+				state_overrides.unwrap_or_default(),
+				|| {
+					<Runtime as pallet_evm::Config>::Runner::create(
+						from,
+						data,
+						value,
+						gas_limit.low_u64(),// gas_limit.unique_saturated_into(),
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						access_list.unwrap_or_default(),
+						is_transactional,
+						validate,
+						evm_config,
+					)
+				},
 			).map_err(|err| err.error.into())
 		}
 		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>> {
@@ -2421,6 +2481,30 @@ impl_runtime_apis! {
 			Some(BaseFee::elasticity())
 		}
 		fn gas_limit_multiplier_support() {}
+		fn create_access_list(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<(pallet_evm::CallInfo, Vec<(H160, Vec<H256>)>), sp_runtime::DispatchError> {
+			<Runtime as pallet_evm::Config>::Runner::create_access_list(
+				from,
+				to,
+				data,
+				value,
+				gas_limit.low_u64(),
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+				<Runtime as pallet_evm::Config>::config(),
+			).map_err(|err| err.error.into())
+		}
 	}
 	impl fp_rpc::ConvertTransactionRuntimeApi<Block> for Runtime {
 		fn convert_transaction(transaction: EthereumTransaction) -> <Block as BlockT>::Extrinsic {
@@ -2429,6 +2513,166 @@ impl_runtime_apis! {
 			)
 		}
 	}
+	impl fp_rpc::DebugRuntimeApi<Block> for Runtime {
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_hash: H256,
+			trace_type: fp_evm::tracing::TraceType,
+		) -> Result<fp_evm::tracing::TransactionTrace, sp_runtime::DispatchError> {
+			#[cfg(feature = "evm-tracing")]
+			{
+				use fp_self_contained::SelfContainedCall;
+
+				for ext in extrinsics.into_iter() {
+					match &ext.0.function {
+						RuntimeCall::Ethereum(transact { transaction })
+							if transaction.hash() == transaction_hash =>
+						{
+							let from = match ext.0.function.check_self_contained() {
+								Some(Ok(from)) => from,
+								_ => return Err(sp_runtime::DispatchError::Other("Invalid transaction signature")),
+							};
+							let tracer = pallet_evm::tracing::Tracer::new(trace_type);
+							let (tracer, result) = tracer.trace(|| Ethereum::execute(from, transaction, None));
+							let used_gas = match result.map_err(|err| err.error)? {
+								(_, _, pallet_evm::CallOrCreateInfo::Call(info)) => info.used_gas,
+								(_, _, pallet_evm::CallOrCreateInfo::Create(info)) => info.used_gas,
+							};
+							return Ok(tracer.finish(used_gas));
+						}
+						_ => {
+							let _ = Executive::apply_extrinsic(ext);
+						}
+					}
+				}
+				Err(sp_runtime::DispatchError::Other("Transaction not found in block"))
+			}
+			#[cfg(not(feature = "evm-tracing"))]
+			{
+				let _ = (extrinsics, transaction_hash, trace_type);
+				Err(sp_runtime::DispatchError::Other("EVM tracing is not enabled in the runtime"))
+			}
+		}
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Result<Vec<(H256, fp_evm::tracing::CallTrace)>, sp_runtime::DispatchError> {
+			#[cfg(feature = "evm-tracing")]
+			{
+				use fp_self_contained::SelfContainedCall;
+
+				let mut traces = Vec::new();
+				for ext in extrinsics.into_iter() {
+					match &ext.0.function {
+						RuntimeCall::Ethereum(transact { transaction }) => {
+							let from = match ext.0.function.check_self_contained() {
+								Some(Ok(from)) => from,
+								_ => return Err(sp_runtime::DispatchError::Other("Invalid transaction signature")),
+							};
+							let tracer = pallet_evm::tracing::Tracer::new(pallet_evm::tracing::TraceType::CallTree);
+							let (tracer, result) = tracer.trace(|| Ethereum::execute(from, transaction, None));
+							// A failing transaction is traced with its error, and the block goes on.
+							let used_gas = match result {
+								Ok((_, _, pallet_evm::CallOrCreateInfo::Call(info))) => info.used_gas,
+								Ok((_, _, pallet_evm::CallOrCreateInfo::Create(info))) => info.used_gas,
+								Err(err) => {
+									traces.push((transaction.hash(), failed_call_trace(from, transaction, err.error)));
+									continue;
+								}
+							};
+							if let pallet_evm::tracing::TransactionTrace::CallTree(root) = tracer.finish(used_gas) {
+								traces.push((transaction.hash(), root));
+							}
+						}
+						_ => {
+							let _ = Executive::apply_extrinsic(ext);
+						}
+					}
+				}
+				Ok(traces)
+			}
+			#[cfg(not(feature = "evm-tracing"))]
+			{
+				let _ = extrinsics;
+				Err(sp_runtime::DispatchError::Other("EVM tracing is not enabled in the runtime"))
+			}
+		}
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			trace_type: fp_evm::tracing::TraceType,
+		) -> Result<fp_evm::tracing::TransactionTrace, sp_runtime::DispatchError> {
+			#[cfg(feature = "evm-tracing")]
+			{
+				let is_transactional = false;
+				let validate = true;
+				let evm_config = <Runtime as pallet_evm::Config>::config();
+				let tracer = pallet_evm::tracing::Tracer::new(trace_type);
+				let (tracer, used_gas) = tracer.trace(|| match to {
+					Some(to) => <Runtime as pallet_evm::Config>::Runner::call(
+						from,
+						to,
+						data,
+						value,
+						gas_limit.low_u64(),
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						access_list.unwrap_or_default(),
+						is_transactional,
+						validate,
+						evm_config,
+					).map(|info| info.used_gas),
+					None => <Runtime as pallet_evm::Config>::Runner::create(
+						from,
+						data,
+						value,
+						gas_limit.low_u64(),
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						access_list.unwrap_or_default(),
+						is_transactional,
+						validate,
+						evm_config,
+					).map(|info| info.used_gas),
+				});
+				let used_gas = used_gas.map_err(|err| err.error.into())?;
+				Ok(tracer.finish(used_gas))
+			}
+			#[cfg(not(feature = "evm-tracing"))]
+			{
+				let _ = (
+					from, to, data, value, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+					nonce, access_list, trace_type,
+				);
+				Err(sp_runtime::DispatchError::Other("EVM tracing is not enabled in the runtime"))
+			}
+		}
+	}
+	impl fp_rpc::EthereumProofRuntimeApi<Block> for Runtime {
+		fn account_proof_keys(address: H160) -> Vec<Vec<u8>> {
+			use frame_support::storage::StorageMap;
+			use pallet_evm::AddressMapping;
+
+			let account_id = <Runtime as pallet_evm::Config>::AddressMapping::into_account_id(address);
+			vec![
+				frame_system::Account::<Runtime>::hashed_key_for(account_id),
+				pallet_evm::AccountCodes::<Runtime>::hashed_key_for(address),
+			]
+		}
+		fn storage_proof_key(address: H160, index: H256) -> Vec<u8> {
+			use frame_support::storage::StorageDoubleMap;
+
+			pallet_evm::AccountStorages::<Runtime>::hashed_key_for(address, index)
+		}
+	}
 
 
 
@@ -2,9 +2,16 @@ use pallet_evm::{Precompile, PrecompileHandle, PrecompileResult, PrecompileSet};
 use sp_core::H160;
 use sp_std::marker::PhantomData;
 
+use pallet_evm_precompile_assets_erc20::{AddressToAssetId, Erc20AssetsPrecompileSet};
+use pallet_evm_precompile_balances_erc20::{Erc20BalancesPrecompile, Erc20Metadata};
+use pallet_evm_precompile_batch::BatchPrecompile;
+use pallet_evm_precompile_call_permit::CallPermitPrecompile;
+use pallet_evm_precompile_conviction_voting::ConvictionVotingPrecompile;
+use pallet_evm_precompile_democracy::DemocracyPrecompile;
 use pallet_evm_precompile_modexp::Modexp;
 use pallet_evm_precompile_sha3fips::Sha3FIPS256;
 use pallet_evm_precompile_simple::{ECRecover, ECRecoverPublicKey, Identity, Ripemd160, Sha256};
+use pallet_evm_precompile_staking::StakingPrecompile;
 
 pub struct FrontierPrecompiles<R>(PhantomData<R>);
 
@@ -15,7 +22,7 @@ where
 	pub fn new() -> Self {
 		Self(Default::default())
 	}
-	pub fn used_addresses() -> [H160; 7] {
+	pub fn used_addresses() -> [H160; 13] {
 		[
 			hash(1),
 			hash(2),
@@ -24,12 +31,24 @@ where
 			hash(5),
 			hash(1024),
 			hash(1025),
+			hash(2050),
+			hash(2051),
+			hash(2052),
+			hash(2053),
+			hash(2054),
+			hash(2055),
 		]
 	}
 }
 impl<R> PrecompileSet for FrontierPrecompiles<R>
 where
 	R: pallet_evm::Config,
+	Erc20AssetsPrecompileSet<R>: PrecompileSet,
+	Erc20BalancesPrecompile<R, NativeErc20Metadata>: Precompile,
+	StakingPrecompile<R>: Precompile,
+	ConvictionVotingPrecompile<R>: Precompile,
+	DemocracyPrecompile<R>: Precompile,
+	CallPermitPrecompile<R>: Precompile,
 {
 	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
 		match handle.code_address() {
@@ -42,12 +61,62 @@ where
 			// Non-Frontier specific nor Ethereum precompiles :
 			a if a == hash(1024) => Some(Sha3FIPS256::execute(handle)),
 			a if a == hash(1025) => Some(ECRecoverPublicKey::execute(handle)),
-			_ => None,
+			// ERC-20 view of the native currency :
+			a if a == hash(2050) => {
+				Some(Erc20BalancesPrecompile::<R, NativeErc20Metadata>::execute(handle))
+			}
+			// Staking and governance :
+			a if a == hash(2051) => Some(StakingPrecompile::<R>::execute(handle)),
+			a if a == hash(2052) => Some(ConvictionVotingPrecompile::<R>::execute(handle)),
+			a if a == hash(2055) => Some(DemocracyPrecompile::<R>::execute(handle)),
+			// Batched and signed calls :
+			a if a == hash(2053) => Some(BatchPrecompile::execute(handle)),
+			a if a == hash(2054) => Some(CallPermitPrecompile::<R>::execute(handle)),
+			// ERC-20 view of pallet_assets :
+			_ => Erc20AssetsPrecompileSet::<R>::new().execute(handle),
 		}
 	}
 
 	fn is_precompile(&self, address: H160) -> bool {
 		Self::used_addresses().contains(&address)
+			|| Erc20AssetsPrecompileSet::<R>::new().is_precompile(address)
+	}
+}
+
+/// Metadata of the native currency, as seen through its ERC-20 precompile.
+pub struct NativeErc20Metadata;
+
+impl Erc20Metadata for NativeErc20Metadata {
+	fn name() -> &'static str {
+		"Firechain"
+	}
+
+	fn symbol() -> &'static str {
+		"HV"
+	}
+
+	fn decimals() -> u8 {
+		6
+	}
+}
+
+/// Prefix of the addresses of the asset precompiles, followed by the asset id.
+pub const ASSET_PRECOMPILE_ADDRESS_PREFIX: [u8; 4] = [0xff; 4];
+
+impl AddressToAssetId<u32> for crate::Runtime {
+	fn address_to_asset_id(address: H160) -> Option<u32> {
+		let (prefix, id) = address.as_bytes().split_at(4);
+		if prefix != ASSET_PRECOMPILE_ADDRESS_PREFIX {
+			return None;
+		}
+		u128::from_be_bytes(id.try_into().ok()?).try_into().ok()
+	}
+
+	fn asset_id_to_address(asset_id: u32) -> H160 {
+		let mut address = [0u8; 20];
+		address[..4].copy_from_slice(&ASSET_PRECOMPILE_ADDRESS_PREFIX);
+		address[4..].copy_from_slice(&u128::from(asset_id).to_be_bytes());
+		H160(address)
 	}
 }
 
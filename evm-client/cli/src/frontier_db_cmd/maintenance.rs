@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+// Substrate
+use sp_api::ApiExt;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto},
+};
+// Frontier
+use fc_db::{BlockMapping, MappingCommitment};
+use fp_rpc::EthereumRuntimeRPCApi;
+
+/// Number of blocks between two progress reports.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Bulk operations on the mappings of the canonical chain.
+pub struct Maintenance<C, B: BlockT> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+}
+
+impl<C, B: BlockT> Maintenance<C, B>
+where
+	C: sp_api::ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: sp_blockchain::HeaderBackend<B>,
+{
+	pub fn new(client: Arc<C>, backend: Arc<fc_db::Backend<B>>) -> Self {
+		Self { client, backend }
+	}
+
+	/// Rebuild the mappings of the canonical blocks `from..=to` from the runtime state.
+	pub fn reindex(&self, from: u64, to: u64) -> sc_cli::Result<()> {
+		if from > to {
+			return Err(format!("Invalid range #{}..#{}", from, to).into());
+		}
+
+		let total = to - from + 1;
+		for (done, number) in (from..=to).enumerate() {
+			let block_hash = self.canonical_hash(number)?;
+			self.backend
+				.mapping()
+				.write(self.runtime_mapping(block_hash)?)?;
+			report_progress("Reindexed", done as u64 + 1, total);
+		}
+		Ok(())
+	}
+
+	/// Check the mappings of every canonical block, failing if any entry is missing or
+	/// points to a block that can no longer become canonical.
+	///
+	/// Blocks dropped by `prune` are skipped, apart from the genesis block which it keeps.
+	pub fn verify(&self) -> sc_cli::Result<()> {
+		let best_number: u64 = self.client.info().best_number.unique_saturated_into();
+		let pruned_before = self.backend.meta().pruned_before()?.unwrap_or(0).max(1);
+
+		let total = best_number.saturating_sub(pruned_before - 1) + 1;
+		let (mut missing, mut orphaned) = (0, 0);
+		for (done, number) in std::iter::once(0)
+			.chain(pruned_before..=best_number)
+			.enumerate()
+		{
+			let block_hash = self.canonical_hash(number)?;
+			if !self.backend.mapping().is_synced(&block_hash)? {
+				println!("Block #{} ({:?}) is not synced", number, block_hash);
+				missing += 1;
+			} else if number > 0 {
+				if let BlockMapping::Hashes(commitment) =
+					fc_mapping_sync::block_mapping::<B>(&self.header(block_hash)?)?
+				{
+					let (block_missing, block_orphaned) = self.verify_commitment(commitment)?;
+					missing += block_missing;
+					orphaned += block_orphaned;
+				}
+			}
+			report_progress("Verified", done as u64 + 1, total);
+		}
+
+		if missing > 0 || orphaned > 0 {
+			return Err(format!(
+				"Found {} missing and {} orphaned mapping entries",
+				missing, orphaned
+			)
+			.into());
+		}
+		Ok(())
+	}
+
+	/// Drop the mappings of the canonical blocks below `before`, read from their digest.
+	///
+	/// The genesis block mapping, which is not part of its digest, is kept.
+	pub fn prune(&self, before: u64) -> sc_cli::Result<()> {
+		let finalized_number: u64 = self.client.info().finalized_number.unique_saturated_into();
+		if before > finalized_number + 1 {
+			return Err(format!(
+				"Cannot prune past the finalized block #{}",
+				finalized_number
+			)
+			.into());
+		}
+
+		let total = before.saturating_sub(1);
+		for number in 1..before {
+			let block_hash = self.canonical_hash(number)?;
+			if self.backend.mapping().is_synced(&block_hash)? {
+				self.backend
+					.mapping()
+					.remove(fc_mapping_sync::block_mapping::<B>(
+						&self.header(block_hash)?,
+					)?)?;
+			}
+			report_progress("Pruned", number, total);
+		}
+
+		// Later verifications start from the first block kept.
+		let pruned_before = self.backend.meta().pruned_before()?.unwrap_or(0);
+		if before > pruned_before {
+			self.backend.meta().write_pruned_before(before)?;
+		}
+		Ok(())
+	}
+
+	/// Count the missing and orphaned entries of a block mapping.
+	fn verify_commitment(&self, commitment: MappingCommitment<B>) -> sc_cli::Result<(u64, u64)> {
+		let (mut missing, mut orphaned) = (0, 0);
+
+		let substrate_hashes = self
+			.backend
+			.mapping()
+			.block_hash(&commitment.ethereum_block_hash)?
+			.unwrap_or_default();
+		if !substrate_hashes.contains(&commitment.block_hash) {
+			println!(
+				"Ethereum block {:?} is not mapped to block {:?}",
+				commitment.ethereum_block_hash, commitment.block_hash
+			);
+			missing += 1;
+		}
+		for block_hash in substrate_hashes {
+			if self.is_orphaned(block_hash)? {
+				println!(
+					"Ethereum block {:?} is mapped to orphaned block {:?}",
+					commitment.ethereum_block_hash, block_hash
+				);
+				orphaned += 1;
+			}
+		}
+
+		for (index, transaction_hash) in commitment.ethereum_transaction_hashes.iter().enumerate() {
+			let metadata = self
+				.backend
+				.mapping()
+				.transaction_metadata(transaction_hash)?;
+			if !metadata.iter().any(|metadata| {
+				metadata.block_hash == commitment.block_hash
+					&& metadata.ethereum_index == index as u32
+			}) {
+				println!(
+					"Transaction {:?} is not mapped to block {:?}",
+					transaction_hash, commitment.block_hash
+				);
+				missing += 1;
+			}
+			for metadata in metadata {
+				if self.is_orphaned(metadata.block_hash)? {
+					println!(
+						"Transaction {:?} is mapped to orphaned block {:?}",
+						transaction_hash, metadata.block_hash
+					);
+					orphaned += 1;
+				}
+			}
+		}
+
+		Ok((missing, orphaned))
+	}
+
+	/// Read the mapping of a block from its runtime state.
+	fn runtime_mapping(&self, block_hash: B::Hash) -> sc_cli::Result<BlockMapping<B>> {
+		let id = BlockId::Hash(block_hash);
+		let api_version = match self
+			.client
+			.runtime_api()
+			.api_version::<dyn EthereumRuntimeRPCApi<B>>(&id)
+			.map_err(|e| format!("{:?}", e))?
+		{
+			Some(api_version) => api_version,
+			None => return Ok(BlockMapping::None(block_hash)),
+		};
+
+		let block = if api_version > 1 {
+			self.client
+				.runtime_api()
+				.current_block(&id)
+				.map_err(|e| format!("{:?}", e))?
+		} else {
+			#[allow(deprecated)]
+			let legacy_block = self
+				.client
+				.runtime_api()
+				.current_block_before_version_2(&id)
+				.map_err(|e| format!("{:?}", e))?;
+			legacy_block.map(|block| block.into())
+		};
+
+		Ok(match block {
+			Some(block) => BlockMapping::Hashes(MappingCommitment {
+				block_hash,
+				ethereum_block_hash: block.header.hash(),
+				ethereum_transaction_hashes: block
+					.transactions
+					.iter()
+					.map(|transaction| transaction.hash())
+					.collect(),
+			}),
+			None => BlockMapping::None(block_hash),
+		})
+	}
+
+	fn canonical_hash(&self, number: u64) -> sc_cli::Result<B::Hash> {
+		Ok(self
+			.client
+			.hash(number.unique_saturated_into())?
+			.ok_or_else(|| format!("Block #{} not found", number))?)
+	}
+
+	fn header(&self, block_hash: B::Hash) -> sc_cli::Result<B::Header> {
+		Ok(self
+			.client
+			.header(BlockId::Hash(block_hash))?
+			.ok_or_else(|| format!("Header {:?} not found", block_hash))?)
+	}
+
+	/// Whether a block is unknown, or a finalized fork that will never be canonical.
+	fn is_orphaned(&self, block_hash: B::Hash) -> sc_cli::Result<bool> {
+		let header = match self.client.header(BlockId::Hash(block_hash))? {
+			Some(header) => header,
+			None => return Ok(true),
+		};
+		let finalized_number = self.client.info().finalized_number;
+		Ok(*header.number() <= finalized_number
+			&& self.client.hash(*header.number())? != Some(block_hash))
+	}
+}
+
+fn report_progress(operation: &str, done: u64, total: u64) {
+	if done % PROGRESS_INTERVAL == 0 || done == total {
+		println!("{} {}/{} blocks", operation, done, total);
+	}
+}
@@ -167,6 +167,9 @@ where
 					.to_string()
 					.into())
 			}
+			Operation::Reindex | Operation::Verify | Operation::Prune => {
+				return Err(self.bulk_operation_error(&self.cmd.operation))
+			}
 		}
 		Ok(())
 	}
@@ -146,6 +146,9 @@ impl<'a, B: BlockT> MetaDb<'a, B> {
 					self.backend.meta().write_ethereum_schema(vec![])?;
 				}
 			},
+			Operation::Reindex | Operation::Verify | Operation::Prune => {
+				return Err(self.bulk_operation_error(&self.cmd.operation))
+			}
 		}
 		Ok(())
 	}
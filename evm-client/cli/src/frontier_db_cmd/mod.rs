@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod maintenance;
 mod mapping_db;
 mod meta_db;
 #[cfg(test)]
@@ -32,6 +33,7 @@ use sc_cli::{PruningParams, SharedParams};
 use sp_runtime::traits::Block as BlockT;
 
 use self::{
+	maintenance::Maintenance,
 	mapping_db::{MappingDb, MappingKey, MappingValue},
 	meta_db::{MetaDb, MetaKey, MetaValue},
 };
@@ -41,19 +43,50 @@ use self::{
 pub struct FrontierDbCmd {
 	/// Specify the operation to perform.
 	///
-	/// Can be one of `create | read | update | delete`.
+	/// Can be one of `create | read | update | delete` on a single key, or one of
+	/// the bulk operations `reindex | verify | prune`.
 	#[arg(value_enum, ignore_case = true, required = true)]
 	pub operation: Operation,
 
 	/// Specify the column to query.
 	///
-	/// Can be one of `meta | block | transaction`.
-	#[arg(value_enum, ignore_case = true, required = true)]
-	pub column: Column,
-
-	/// Specify the key to either read or write.
-	#[arg(short('k'), long, required = true)]
-	pub key: String,
+	/// Can be one of `meta | block | transaction`. Required by the single key operations.
+	#[arg(
+		value_enum,
+		ignore_case = true,
+		required_if_eq_any([
+			("operation", "create"),
+			("operation", "read"),
+			("operation", "update"),
+			("operation", "delete"),
+		])
+	)]
+	pub column: Option<Column>,
+
+	/// Specify the key to either read or write. Required by the single key operations.
+	#[arg(
+		short('k'),
+		long,
+		required_if_eq_any([
+			("operation", "create"),
+			("operation", "read"),
+			("operation", "update"),
+			("operation", "delete"),
+		])
+	)]
+	pub key: Option<String>,
+
+	/// First block of the range to reindex.
+	#[arg(long, required_if_eq("operation", "reindex"))]
+	pub from: Option<u64>,
+
+	/// Last block of the range to reindex.
+	#[arg(long, required_if_eq("operation", "reindex"))]
+	pub to: Option<u64>,
+
+	/// Drop the mappings of the canonical blocks below this block number.
+	#[arg(long, required_if_eq("operation", "prune"))]
+	pub before: Option<u64>,
 
 	/// Specify the value to write.
 	///
@@ -79,6 +112,12 @@ pub enum Operation {
 	Read,
 	Update,
 	Delete,
+	/// Rebuild the mappings of a range of canonical blocks from the runtime.
+	Reindex,
+	/// Check the mappings of every canonical block.
+	Verify,
+	/// Drop the mappings of old canonical blocks.
+	Prune,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -106,12 +145,29 @@ impl FrontierDbCmd {
 		C::Api: fp_rpc::EthereumRuntimeRPCApi<B>,
 		C: sp_blockchain::HeaderBackend<B>,
 	{
-		match self.column {
+		let (column, key) = match (&self.operation, &self.column, &self.key) {
+			(Operation::Reindex, _, _) => {
+				let (from, to) = self
+					.from
+					.zip(self.to)
+					.ok_or("`--from` and `--to` are required to reindex")?;
+				return Maintenance::new(client, backend).reindex(from, to);
+			}
+			(Operation::Verify, _, _) => return Maintenance::new(client, backend).verify(),
+			(Operation::Prune, _, _) => {
+				let before = self.before.ok_or("`--before` is required to prune")?;
+				return Maintenance::new(client, backend).prune(before);
+			}
+			(_, Some(column), Some(key)) => (column, key),
+			_ => return Err("A column and a key are required by this operation".into()),
+		};
+
+		match column {
 			Column::Meta => {
 				// New meta db handler
 				let meta_db = MetaDb::new(self, backend);
 				// Maybe get a MetaKey
-				let key = MetaKey::from_str(&self.key)?;
+				let key = MetaKey::from_str(key)?;
 				// Maybe get a MetaValue
 				let value = match utils::maybe_deserialize_value::<B>(
 					&self.operation,
@@ -129,7 +185,7 @@ impl FrontierDbCmd {
 				let mapping_db = MappingDb::new(self, client, backend);
 				// Maybe get a MappingKey
 				let key = MappingKey::EthBlockOrTransactionHash(
-					H256::from_str(&self.key).expect("H256 provided key"),
+					H256::from_str(key).expect("H256 provided key"),
 				);
 				// Maybe get a MappingValue
 				let value = match utils::maybe_deserialize_value::<B>(
@@ -141,7 +197,7 @@ impl FrontierDbCmd {
 					_ => return Err(format!("Unexpected `{:?}` value", self.value).into()),
 				};
 				// Run the query
-				mapping_db.query(&self.column, &key, &value)?
+				mapping_db.query(column, &key, &value)?
 			}
 		}
 		Ok(())
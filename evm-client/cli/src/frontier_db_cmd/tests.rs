@@ -26,6 +26,7 @@ use tempfile::tempdir;
 // Substrate
 use sc_block_builder::BlockBuilderProvider;
 use sc_cli::DatabasePruningMode;
+use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
 use sp_io::hashing::twox_128;
 use sp_runtime::{
@@ -78,8 +79,11 @@ enum TestValue {
 fn cmd(key: String, value: Option<PathBuf>, operation: Operation, column: Column) -> FrontierDbCmd {
 	FrontierDbCmd {
 		operation,
-		column,
-		key,
+		column: Some(column),
+		key: Some(key),
+		from: None,
+		to: None,
+		before: None,
 		value,
 		shared_params: sc_cli::SharedParams {
 			chain: None,
@@ -99,6 +103,14 @@ fn cmd(key: String, value: Option<PathBuf>, operation: Operation, column: Column
 	}
 }
 
+fn bulk_cmd(operation: Operation) -> FrontierDbCmd {
+	FrontierDbCmd {
+		column: None,
+		key: None,
+		..cmd(String::new(), None, operation, Column::Meta)
+	}
+}
+
 fn schema_test_value() -> TestValue {
 	let mut inner = HashMap::new();
 	inner.insert(H256::default(), EthereumStorageSchema::V1);
@@ -801,3 +813,156 @@ fn mapping_read_works() {
 	.run(Arc::clone(&client), backend.clone())
 	.is_ok());
 }
+
+#[test]
+fn bulk_operations_check_their_arguments() {
+	let tmp = tempdir().expect("create a temporary directory");
+
+	// Test client.
+	let (client, _) = TestClientBuilder::new().build_with_native_executor::<RuntimeApi, _>(None);
+	let client = Arc::new(client);
+	// Create a temporary frontier secondary DB.
+	let backend =
+		open_frontier_backend(client.clone(), tmp.into_path()).expect("a temporary db was created");
+
+	// The genesis block is not synced yet.
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_err());
+	backend
+		.mapping()
+		.write_none(client.info().genesis_hash)
+		.expect("genesis mapping is written");
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_ok());
+
+	// Empty ranges and ranges past the finalized block are refused.
+	assert!(FrontierDbCmd {
+		from: Some(1),
+		to: Some(0),
+		..bulk_cmd(Operation::Reindex)
+	}
+	.run(Arc::clone(&client), backend.clone())
+	.is_err());
+	assert!(FrontierDbCmd {
+		before: Some(2),
+		..bulk_cmd(Operation::Prune)
+	}
+	.run(Arc::clone(&client), backend.clone())
+	.is_err());
+}
+
+#[test]
+fn prune_drops_canonical_mappings() {
+	let tmp = tempdir().expect("create a temporary directory");
+
+	// Test client.
+	let (c, _) = TestClientBuilder::new().build_with_native_executor::<RuntimeApi, _>(None);
+	let mut client = Arc::new(c);
+	// Create a temporary frontier secondary DB.
+	let backend =
+		open_frontier_backend(client.clone(), tmp.into_path()).expect("a temporary db was created");
+
+	// Finalize three blocks on top of genesis, none of them holding an Ethereum block.
+	let mut block_hashes = vec![client.info().genesis_hash];
+	for _ in 0..3 {
+		let block = client
+			.new_block(Default::default())
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		block_hashes.push(block.header.hash());
+		executor::block_on(client.import_as_final(BlockOrigin::Own, block)).unwrap();
+	}
+	for block_hash in &block_hashes {
+		backend.mapping().write_none(*block_hash).unwrap();
+	}
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_ok());
+
+	assert!(FrontierDbCmd {
+		before: Some(3),
+		..bulk_cmd(Operation::Prune)
+	}
+	.run(Arc::clone(&client), backend.clone())
+	.is_ok());
+
+	// The genesis block and the blocks from `before` are kept.
+	let synced = block_hashes
+		.iter()
+		.map(|block_hash| backend.mapping().is_synced(block_hash).unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(synced, vec![true, false, false, true]);
+
+	// Verification starts from the first block kept.
+	assert_eq!(backend.meta().pruned_before(), Ok(Some(3)));
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_ok());
+
+	// A block missing past the pruned ones is still reported.
+	backend
+		.mapping()
+		.remove(fc_db::BlockMapping::None(block_hashes[3]))
+		.unwrap();
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_err());
+}
+
+#[test]
+fn reindex_rebuilds_missing_mappings() {
+	let tmp = tempdir().expect("create a temporary directory");
+
+	// Test client.
+	let (c, _) = TestClientBuilder::new().build_with_native_executor::<RuntimeApi, _>(None);
+	let mut client = Arc::new(c);
+	// Create a temporary frontier secondary DB.
+	let backend =
+		open_frontier_backend(client.clone(), tmp.into_path()).expect("a temporary db was created");
+
+	// Three blocks on top of genesis, none of them holding an Ethereum block.
+	let mut block_hashes = vec![client.info().genesis_hash];
+	for _ in 0..3 {
+		let block = client
+			.new_block(Default::default())
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		block_hashes.push(block.header.hash());
+		executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+	}
+	for block_hash in &block_hashes {
+		backend.mapping().write_none(*block_hash).unwrap();
+	}
+
+	// Drop the mappings of two blocks.
+	for block_hash in &block_hashes[1..3] {
+		backend
+			.mapping()
+			.remove(fc_db::BlockMapping::None(*block_hash))
+			.unwrap();
+	}
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_err());
+
+	assert!(FrontierDbCmd {
+		from: Some(1),
+		to: Some(2),
+		..bulk_cmd(Operation::Reindex)
+	}
+	.run(Arc::clone(&client), backend.clone())
+	.is_ok());
+
+	for block_hash in &block_hashes {
+		assert_eq!(backend.mapping().is_synced(block_hash), Ok(true));
+	}
+	assert!(bulk_cmd(Operation::Verify)
+		.run(Arc::clone(&client), backend.clone())
+		.is_ok());
+}
@@ -18,10 +18,13 @@
 
 #[cfg(feature = "parity-db")]
 mod parity_db_adapter;
+#[cfg(feature = "sql")]
+pub mod sql;
 mod upgrade;
 mod utils;
 
 use std::{
+	collections::{hash_map::Entry, HashMap},
 	marker::PhantomData,
 	path::{Path, PathBuf},
 	sync::Arc,
@@ -58,6 +61,9 @@ pub(crate) mod columns {
 
 pub mod static_keys {
 	pub const CURRENT_SYNCING_TIPS: &[u8] = b"CURRENT_SYNCING_TIPS";
+	pub const CATCH_UP_CHECKPOINT: &[u8] = b"CATCH_UP_CHECKPOINT";
+	pub const LOWEST_UNSYNCED: &[u8] = b"LOWEST_UNSYNCED";
+	pub const PRUNED_BEFORE: &[u8] = b"PRUNED_BEFORE";
 }
 
 pub struct Backend<Block: BlockT> {
@@ -164,6 +170,51 @@ impl<Block: BlockT> MetaDb<Block> {
 		Ok(())
 	}
 
+	/// Number of the last block indexed by the mapping sync catch-up mode.
+	pub fn catch_up_checkpoint(&self) -> Result<Option<u64>, String> {
+		self.number(crate::static_keys::CATCH_UP_CHECKPOINT)
+	}
+
+	/// Number of the lowest canonical block missing from the mapping database, as last
+	/// found by the mapping sync lag metric.
+	pub fn lowest_unsynced(&self) -> Result<Option<u64>, String> {
+		self.number(crate::static_keys::LOWEST_UNSYNCED)
+	}
+
+	pub fn write_lowest_unsynced(&self, number: u64) -> Result<(), String> {
+		self.write_number(crate::static_keys::LOWEST_UNSYNCED, number)
+	}
+
+	/// Number of the first block whose mapping was kept by the last prune.
+	pub fn pruned_before(&self) -> Result<Option<u64>, String> {
+		self.number(crate::static_keys::PRUNED_BEFORE)
+	}
+
+	pub fn write_pruned_before(&self, number: u64) -> Result<(), String> {
+		self.write_number(crate::static_keys::PRUNED_BEFORE, number)
+	}
+
+	fn number(&self, key: &[u8]) -> Result<Option<u64>, String> {
+		match self.db.get(crate::columns::META, key) {
+			Some(raw) => Ok(Some(
+				u64::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?,
+			)),
+			None => Ok(None),
+		}
+	}
+
+	fn write_number(&self, key: &[u8], number: u64) -> Result<(), String> {
+		let mut transaction = sp_database::Transaction::new();
+
+		transaction.set(crate::columns::META, key, &number.encode());
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
 	pub fn ethereum_schema(&self) -> Result<Option<Vec<(EthereumStorageSchema, H256)>>, String> {
 		match self
 			.db
@@ -203,6 +254,15 @@ pub struct MappingCommitment<Block: BlockT> {
 	pub ethereum_transaction_hashes: Vec<H256>,
 }
 
+/// Mapping of a Substrate block, as written by `MappingDb::write_batch`.
+#[derive(Debug)]
+pub enum BlockMapping<Block: BlockT> {
+	/// The block holds an Ethereum block.
+	Hashes(MappingCommitment<Block>),
+	/// The block holds no Ethereum block.
+	None(Block::Hash),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
 pub struct TransactionMetadata<Block: BlockT> {
 	pub block_hash: Block::Hash,
@@ -281,13 +341,16 @@ impl<Block: BlockT> MappingDb<Block> {
 
 		let substrate_hashes = match self.block_hash(&commitment.ethereum_block_hash) {
 			Ok(Some(mut data)) => {
-				data.push(commitment.block_hash);
-				log::warn!(
-					target: "fc-db",
-					"Possible equivocation at ethereum block hash {} {:?}",
-					&commitment.ethereum_block_hash,
-					&data
-				);
+				// Writing the same block again, e.g. when reindexing, keeps a single entry.
+				if !data.contains(&commitment.block_hash) {
+					data.push(commitment.block_hash);
+					log::warn!(
+						target: "fc-db",
+						"Possible equivocation at ethereum block hash {} {:?}",
+						&commitment.ethereum_block_hash,
+						&data
+					);
+				}
 				data
 			}
 			_ => vec![commitment.block_hash],
@@ -305,6 +368,7 @@ impl<Block: BlockT> MappingDb<Block> {
 			.enumerate()
 		{
 			let mut metadata = self.transaction_metadata(&ethereum_transaction_hash)?;
+			metadata.retain(|metadata| metadata.block_hash != commitment.block_hash);
 			metadata.push(TransactionMetadata::<Block> {
 				block_hash: commitment.block_hash,
 				ethereum_block_hash: commitment.ethereum_block_hash,
@@ -329,4 +393,314 @@ impl<Block: BlockT> MappingDb<Block> {
 
 		Ok(())
 	}
+
+	/// Write the mapping of a block.
+	pub fn write(&self, mapping: BlockMapping<Block>) -> Result<(), String> {
+		match mapping {
+			BlockMapping::Hashes(commitment) => self.write_hashes(commitment),
+			BlockMapping::None(block_hash) => self.write_none(block_hash),
+		}
+	}
+
+	/// Write the mappings of many blocks and the catch-up checkpoint in a single transaction,
+	/// so an interrupted catch-up resumes without indexing a block twice.
+	pub fn write_batch(
+		&self,
+		mappings: Vec<BlockMapping<Block>>,
+		catch_up_checkpoint: u64,
+	) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+		let mut block_hashes = HashMap::<H256, Vec<Block::Hash>>::new();
+		let mut transaction_metadata = HashMap::<H256, Vec<TransactionMetadata<Block>>>::new();
+
+		for mapping in mappings {
+			let commitment = match mapping {
+				BlockMapping::Hashes(commitment) => commitment,
+				BlockMapping::None(block_hash) => {
+					transaction.set(
+						crate::columns::SYNCED_MAPPING,
+						&block_hash.encode(),
+						&true.encode(),
+					);
+					continue;
+				}
+			};
+
+			let substrate_hashes = match block_hashes.entry(commitment.ethereum_block_hash) {
+				Entry::Occupied(entry) => entry.into_mut(),
+				Entry::Vacant(entry) => entry.insert(
+					self.block_hash(&commitment.ethereum_block_hash)?
+						.unwrap_or_default(),
+				),
+			};
+			if !substrate_hashes.contains(&commitment.block_hash) {
+				substrate_hashes.push(commitment.block_hash);
+			}
+			if substrate_hashes.len() > 1 {
+				log::warn!(
+					target: "fc-db",
+					"Possible equivocation at ethereum block hash {} {:?}",
+					&commitment.ethereum_block_hash,
+					substrate_hashes
+				);
+			}
+
+			for (i, ethereum_transaction_hash) in commitment
+				.ethereum_transaction_hashes
+				.into_iter()
+				.enumerate()
+			{
+				let metadata = match transaction_metadata.entry(ethereum_transaction_hash) {
+					Entry::Occupied(entry) => entry.into_mut(),
+					Entry::Vacant(entry) => {
+						entry.insert(self.transaction_metadata(&ethereum_transaction_hash)?)
+					}
+				};
+				metadata.retain(|metadata| metadata.block_hash != commitment.block_hash);
+				metadata.push(TransactionMetadata::<Block> {
+					block_hash: commitment.block_hash,
+					ethereum_block_hash: commitment.ethereum_block_hash,
+					ethereum_index: i as u32,
+				});
+			}
+
+			transaction.set(
+				crate::columns::SYNCED_MAPPING,
+				&commitment.block_hash.encode(),
+				&true.encode(),
+			);
+		}
+
+		for (ethereum_block_hash, substrate_hashes) in block_hashes {
+			transaction.set(
+				crate::columns::BLOCK_MAPPING,
+				&ethereum_block_hash.encode(),
+				&substrate_hashes.encode(),
+			);
+		}
+		for (ethereum_transaction_hash, metadata) in transaction_metadata {
+			transaction.set(
+				crate::columns::TRANSACTION_MAPPING,
+				&ethereum_transaction_hash.encode(),
+				&metadata.encode(),
+			);
+		}
+		transaction.set(
+			crate::columns::META,
+			crate::static_keys::CATCH_UP_CHECKPOINT,
+			&catch_up_checkpoint.encode(),
+		);
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Remove the mapping of a block, keeping the entries of other blocks sharing its
+	/// Ethereum block or transactions.
+	pub fn remove(&self, mapping: BlockMapping<Block>) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+
+		let block_hash = match mapping {
+			BlockMapping::Hashes(commitment) => {
+				let mut substrate_hashes = self
+					.block_hash(&commitment.ethereum_block_hash)?
+					.unwrap_or_default();
+				substrate_hashes.retain(|hash| hash != &commitment.block_hash);
+				if substrate_hashes.is_empty() {
+					transaction.remove(
+						crate::columns::BLOCK_MAPPING,
+						&commitment.ethereum_block_hash.encode(),
+					);
+				} else {
+					transaction.set(
+						crate::columns::BLOCK_MAPPING,
+						&commitment.ethereum_block_hash.encode(),
+						&substrate_hashes.encode(),
+					);
+				}
+
+				for ethereum_transaction_hash in commitment.ethereum_transaction_hashes {
+					let mut metadata = self.transaction_metadata(&ethereum_transaction_hash)?;
+					metadata.retain(|metadata| metadata.block_hash != commitment.block_hash);
+					if metadata.is_empty() {
+						transaction.remove(
+							crate::columns::TRANSACTION_MAPPING,
+							&ethereum_transaction_hash.encode(),
+						);
+					} else {
+						transaction.set(
+							crate::columns::TRANSACTION_MAPPING,
+							&ethereum_transaction_hash.encode(),
+							&metadata.encode(),
+						);
+					}
+				}
+
+				commitment.block_hash
+			}
+			BlockMapping::None(block_hash) => block_hash,
+		};
+
+		transaction.remove(crate::columns::SYNCED_MAPPING, &block_hash.encode());
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use sp_core::H256;
+	use sp_runtime::{
+		generic::{Block, Header},
+		traits::BlakeTwo256,
+	};
+	use substrate_test_runtime_client::{DefaultTestClientBuilderExt, TestClientBuilder};
+	use tempfile::tempdir;
+
+	use crate::{BlockMapping, MappingCommitment};
+
+	type OpaqueBlock =
+		Block<Header<u64, BlakeTwo256>, substrate_test_runtime_client::runtime::Extrinsic>;
+
+	fn open_backend(path: std::path::PathBuf) -> crate::Backend<OpaqueBlock> {
+		let (client, _) = TestClientBuilder::new()
+			.build_with_native_executor::<substrate_test_runtime_client::runtime::RuntimeApi, _>(
+			None,
+		);
+		crate::Backend::<OpaqueBlock>::new(
+			Arc::new(client),
+			&crate::DatabaseSettings {
+				source: sc_client_db::DatabaseSource::RocksDb {
+					path,
+					cache_size: 0,
+				},
+			},
+		)
+		.expect("a temporary db was created")
+	}
+
+	fn commitment(
+		block_hash: H256,
+		ethereum_block_hash: H256,
+		ethereum_transaction_hashes: Vec<H256>,
+	) -> BlockMapping<OpaqueBlock> {
+		BlockMapping::Hashes(MappingCommitment {
+			block_hash,
+			ethereum_block_hash,
+			ethereum_transaction_hashes,
+		})
+	}
+
+	#[test]
+	fn write_batch_works() {
+		let tmp = tempdir().expect("create a temporary directory");
+		let backend = open_backend(tmp.path().to_owned());
+
+		let (empty_block, block_a, block_b) = (H256::random(), H256::random(), H256::random());
+		let (ethereum_block, transaction) = (H256::random(), H256::random());
+		// Two blocks of the same batch mapping the same Ethereum block and transaction.
+		backend
+			.mapping()
+			.write_batch(
+				vec![
+					BlockMapping::None(empty_block),
+					commitment(block_a, ethereum_block, vec![transaction]),
+					commitment(block_b, ethereum_block, vec![transaction]),
+				],
+				42,
+			)
+			.expect("batch is written");
+
+		assert_eq!(backend.meta().catch_up_checkpoint(), Ok(Some(42)));
+		for block in [empty_block, block_a, block_b] {
+			assert_eq!(backend.mapping().is_synced(&block), Ok(true));
+		}
+		assert_eq!(
+			backend.mapping().block_hash(&ethereum_block),
+			Ok(Some(vec![block_a, block_b]))
+		);
+		let metadata = backend
+			.mapping()
+			.transaction_metadata(&transaction)
+			.expect("metadata is readable");
+		assert_eq!(
+			metadata.iter().map(|m| m.block_hash).collect::<Vec<_>>(),
+			vec![block_a, block_b]
+		);
+	}
+
+	#[test]
+	fn meta_numbers_are_persisted() {
+		let tmp = tempdir().expect("create a temporary directory");
+		{
+			let backend = open_backend(tmp.path().to_owned());
+			assert_eq!(backend.meta().lowest_unsynced(), Ok(None));
+			backend
+				.meta()
+				.write_lowest_unsynced(7)
+				.expect("number is written");
+		}
+
+		let backend = open_backend(tmp.path().to_owned());
+		assert_eq!(backend.meta().lowest_unsynced(), Ok(Some(7)));
+	}
+
+	#[test]
+	fn remove_keeps_other_blocks() {
+		let tmp = tempdir().expect("create a temporary directory");
+		let backend = open_backend(tmp.path().to_owned());
+
+		let (block_a, block_b) = (H256::random(), H256::random());
+		let (ethereum_block, transaction) = (H256::random(), H256::random());
+		for block in [block_a, block_b] {
+			// Writing a mapping twice keeps a single entry.
+			for _ in 0..2 {
+				backend
+					.mapping()
+					.write_hashes(MappingCommitment {
+						block_hash: block,
+						ethereum_block_hash: ethereum_block,
+						ethereum_transaction_hashes: vec![transaction],
+					})
+					.expect("mapping is written");
+			}
+		}
+		assert_eq!(
+			backend.mapping().block_hash(&ethereum_block),
+			Ok(Some(vec![block_a, block_b]))
+		);
+
+		backend
+			.mapping()
+			.remove(commitment(block_a, ethereum_block, vec![transaction]))
+			.expect("mapping is removed");
+
+		assert_eq!(backend.mapping().is_synced(&block_a), Ok(false));
+		assert_eq!(backend.mapping().is_synced(&block_b), Ok(true));
+		assert_eq!(
+			backend.mapping().block_hash(&ethereum_block),
+			Ok(Some(vec![block_b]))
+		);
+		let metadata = backend
+			.mapping()
+			.transaction_metadata(&transaction)
+			.expect("metadata is readable");
+		assert_eq!(
+			metadata.iter().map(|m| m.block_hash).collect::<Vec<_>>(),
+			vec![block_b]
+		);
+	}
 }
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! SQLite backed index of the Ethereum logs, used to answer `eth_getLogs`
+//! queries without scanning the bloom filter of every block in the range.
+
+use std::{marker::PhantomData, path::Path};
+
+use parking_lot::Mutex;
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension};
+use scale_codec::{Decode, Encode};
+// Substrate
+use sp_core::{H160, H256};
+use sp_runtime::traits::Block as BlockT;
+
+/// Maximum number of topics a log can have.
+const MAX_TOPICS: usize = 4;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		substrate_block_hash BLOB NOT NULL PRIMARY KEY,
+		block_number INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS blocks_number_idx ON blocks (block_number);
+	CREATE TABLE IF NOT EXISTS indexed_ranges (
+		low INTEGER NOT NULL,
+		high INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS logs (
+		substrate_block_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		transaction_index INTEGER NOT NULL,
+		log_index INTEGER NOT NULL,
+		address BLOB NOT NULL,
+		topic_0 BLOB,
+		topic_1 BLOB,
+		topic_2 BLOB,
+		topic_3 BLOB,
+		PRIMARY KEY (substrate_block_hash, log_index)
+	);
+	CREATE INDEX IF NOT EXISTS logs_number_idx ON logs (block_number);
+	CREATE INDEX IF NOT EXISTS logs_address_idx ON logs (address, block_number);
+	CREATE INDEX IF NOT EXISTS logs_topic_0_idx ON logs (topic_0, block_number);
+	CREATE INDEX IF NOT EXISTS logs_topic_1_idx ON logs (topic_1, block_number);
+	CREATE INDEX IF NOT EXISTS logs_topic_2_idx ON logs (topic_2, block_number);
+	CREATE INDEX IF NOT EXISTS logs_topic_3_idx ON logs (topic_3, block_number);
+";
+
+/// A log emitted in an indexed block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexedLog {
+	/// Index of the emitting transaction in the block.
+	pub transaction_index: u32,
+	/// Index of the log in the block.
+	pub log_index: u32,
+	pub address: H160,
+	pub topics: Vec<H256>,
+}
+
+/// Conditions of a log index query. A block matches when at least one of its logs
+/// was emitted by one of `addresses` and has, for every constrained position, one
+/// of the listed topics. Empty `addresses` and `None` topics match anything.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LogFilter {
+	pub from_block: u64,
+	pub to_block: u64,
+	pub addresses: Vec<H160>,
+	pub topics: Vec<Option<Vec<H256>>>,
+}
+
+pub struct LogIndexDb<Block: BlockT> {
+	connection: Mutex<Connection>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> LogIndexDb<Block> {
+	/// Open the index stored in the file at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent).map_err(|e| format!("{:?}", e))?;
+		}
+		let connection = Connection::open(path).map_err(|e| format!("{:?}", e))?;
+		// Only the mapping sync worker writes, readers are not blocked by it.
+		connection
+			.pragma_update(None, "journal_mode", "WAL")
+			.map_err(|e| format!("{:?}", e))?;
+		connection
+			.pragma_update(None, "synchronous", "NORMAL")
+			.map_err(|e| format!("{:?}", e))?;
+		Self::with_connection(connection)
+	}
+
+	/// Open an index living in memory only.
+	pub fn open_in_memory() -> Result<Self, String> {
+		Self::with_connection(Connection::open_in_memory().map_err(|e| format!("{:?}", e))?)
+	}
+
+	fn with_connection(connection: Connection) -> Result<Self, String> {
+		connection
+			.execute_batch(SCHEMA)
+			.map_err(|e| format!("{:?}", e))?;
+		let db = Self {
+			connection: Mutex::new(connection),
+			_marker: PhantomData,
+		};
+		db.init_indexed_ranges()?;
+		Ok(db)
+	}
+
+	/// Rebuild the indexed ranges of an index created before they were tracked.
+	fn init_indexed_ranges(&self) -> Result<(), String> {
+		let mut connection = self.connection.lock();
+		let has_ranges = connection
+			.prepare("SELECT 1 FROM indexed_ranges")
+			.and_then(|mut statement| statement.exists([]))
+			.map_err(|e| format!("{:?}", e))?;
+		if has_ranges {
+			return Ok(());
+		}
+
+		let numbers = connection
+			.prepare("SELECT DISTINCT block_number FROM blocks ORDER BY block_number ASC")
+			.and_then(|mut statement| {
+				let numbers = statement
+					.query_map([], |row| row.get::<_, u64>(0))?
+					.collect::<rusqlite::Result<Vec<_>>>();
+				numbers
+			})
+			.map_err(|e| format!("{:?}", e))?;
+		let transaction = connection.transaction().map_err(|e| format!("{:?}", e))?;
+		for (low, high) in runs(numbers) {
+			extend_indexed_ranges(&transaction, low, high)?;
+		}
+		transaction.commit().map_err(|e| format!("{:?}", e))
+	}
+
+	pub fn is_indexed(&self, block_hash: &Block::Hash) -> Result<bool, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT 1 FROM blocks WHERE substrate_block_hash = ?1",
+				params![block_hash.encode()],
+				|_| Ok(()),
+			)
+			.optional()
+			.map(|row| row.is_some())
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// The largest part of `from..=to` whose block numbers are all indexed.
+	///
+	/// A number counts as indexed once one of its blocks is: the index holds the blocks of
+	/// every fork, callers check the canonicality of the blocks a query returns.
+	pub fn indexed_range(&self, from: u64, to: u64) -> Result<Option<(u64, u64)>, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT max(low, ?1), min(high, ?2) FROM indexed_ranges
+				WHERE low <= ?2 AND high >= ?1
+				ORDER BY min(high, ?2) - max(low, ?1) DESC LIMIT 1",
+				params![sql_number(from), sql_number(to)],
+				|row| Ok((row.get::<_, u64>(0)?, row.get::<_, u64>(1)?)),
+			)
+			.optional()
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// Store the logs of a block, replacing any previously indexed logs for it.
+	pub fn index_block(
+		&self,
+		block_hash: Block::Hash,
+		block_number: u64,
+		logs: &[IndexedLog],
+	) -> Result<(), String> {
+		self.index_blocks(&[(block_hash, block_number, logs.to_vec())])
+	}
+
+	/// Store the logs of many blocks in a single transaction.
+	pub fn index_blocks(
+		&self,
+		blocks: &[(Block::Hash, u64, Vec<IndexedLog>)],
+	) -> Result<(), String> {
+		let mut connection = self.connection.lock();
+		let transaction = connection.transaction().map_err(|e| format!("{:?}", e))?;
+		for (block_hash, block_number, logs) in blocks {
+			let block_hash = block_hash.encode();
+
+			transaction
+				.execute(
+					"DELETE FROM logs WHERE substrate_block_hash = ?1",
+					params![block_hash],
+				)
+				.map_err(|e| format!("{:?}", e))?;
+			{
+				let mut statement = transaction
+					.prepare(
+						"INSERT INTO logs (
+							substrate_block_hash, block_number, transaction_index, log_index,
+							address, topic_0, topic_1, topic_2, topic_3
+						) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+					)
+					.map_err(|e| format!("{:?}", e))?;
+				for log in logs {
+					let topic = |i: usize| log.topics.get(i).map(|topic| topic.as_bytes().to_vec());
+					statement
+						.execute(params![
+							block_hash,
+							block_number,
+							log.transaction_index,
+							log.log_index,
+							log.address.as_bytes(),
+							topic(0),
+							topic(1),
+							topic(2),
+							topic(3),
+						])
+						.map_err(|e| format!("{:?}", e))?;
+				}
+			}
+			transaction
+				.execute(
+					"INSERT OR REPLACE INTO blocks (substrate_block_hash, block_number) VALUES (?1, ?2)",
+					params![block_hash, block_number],
+				)
+				.map_err(|e| format!("{:?}", e))?;
+		}
+		for (low, high) in runs(blocks.iter().map(|(_, number, _)| *number).collect()) {
+			extend_indexed_ranges(&transaction, low, high)?;
+		}
+
+		transaction.commit().map_err(|e| format!("{:?}", e))
+	}
+
+	/// Blocks having at least one log matching `filter`, in ascending block number order.
+	/// The result contains blocks of every fork, it is up to the caller to skip the
+	/// non canonical ones.
+	pub fn filter_blocks(&self, filter: &LogFilter) -> Result<Vec<(u64, Block::Hash)>, String> {
+		let mut query = String::from(
+			"SELECT DISTINCT block_number, substrate_block_hash FROM logs
+			WHERE block_number BETWEEN ? AND ?",
+		);
+		let mut values = vec![
+			Value::Integer(sql_number(filter.from_block)),
+			Value::Integer(sql_number(filter.to_block)),
+		];
+
+		let mut push_condition = |column: &str, candidates: Vec<Vec<u8>>| {
+			query.push_str(&format!(
+				" AND {} IN ({})",
+				column,
+				vec!["?"; candidates.len()].join(", ")
+			));
+			values.extend(candidates.into_iter().map(Value::Blob));
+		};
+		if !filter.addresses.is_empty() {
+			push_condition(
+				"address",
+				filter
+					.addresses
+					.iter()
+					.map(|address| address.as_bytes().to_vec())
+					.collect(),
+			);
+		}
+		for (i, topics) in filter.topics.iter().take(MAX_TOPICS).enumerate() {
+			match topics {
+				Some(topics) if !topics.is_empty() => push_condition(
+					&format!("topic_{}", i),
+					topics
+						.iter()
+						.map(|topic| topic.as_bytes().to_vec())
+						.collect(),
+				),
+				_ => (),
+			}
+		}
+		query.push_str(" ORDER BY block_number ASC");
+
+		let connection = self.connection.lock();
+		let mut statement = connection.prepare(&query).map_err(|e| format!("{:?}", e))?;
+		let rows = statement
+			.query_map(params_from_iter(values), |row| {
+				Ok((row.get::<_, u64>(0)?, row.get::<_, Vec<u8>>(1)?))
+			})
+			.map_err(|e| format!("{:?}", e))?;
+
+		let mut blocks = Vec::new();
+		for row in rows {
+			let (number, hash) = row.map_err(|e| format!("{:?}", e))?;
+			let hash = Block::Hash::decode(&mut &hash[..]).map_err(|e| format!("{:?}", e))?;
+			blocks.push((number, hash));
+		}
+		Ok(blocks)
+	}
+}
+
+/// Block numbers are stored as SQLite signed integers.
+fn sql_number(number: u64) -> i64 {
+	number.min(i64::MAX as u64) as i64
+}
+
+/// Contiguous runs of block numbers, as inclusive bounds.
+fn runs(mut numbers: Vec<u64>) -> Vec<(u64, u64)> {
+	numbers.sort_unstable();
+	numbers.dedup();
+	let mut runs: Vec<(u64, u64)> = Vec::new();
+	for number in numbers {
+		match runs.last_mut() {
+			Some((_, high)) if *high + 1 == number => *high = number,
+			_ => runs.push((number, number)),
+		}
+	}
+	runs
+}
+
+/// Add `low..=high` to the indexed ranges, merging it with the ranges it overlaps or
+/// touches so that they stay disjoint.
+fn extend_indexed_ranges(
+	transaction: &rusqlite::Transaction,
+	low: u64,
+	high: u64,
+) -> Result<(), String> {
+	let (low, high) = (sql_number(low), sql_number(high));
+	let touching = "low <= ?2 + 1 AND high + 1 >= ?1";
+	let (merged_low, merged_high) = transaction
+		.query_row(
+			&format!(
+				"SELECT min(ifnull(min(low), ?1), ?1), max(ifnull(max(high), ?2), ?2)
+				FROM indexed_ranges WHERE {}",
+				touching
+			),
+			params![low, high],
+			|row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+		)
+		.map_err(|e| format!("{:?}", e))?;
+	transaction
+		.execute(
+			&format!("DELETE FROM indexed_ranges WHERE {}", touching),
+			params![low, high],
+		)
+		.map_err(|e| format!("{:?}", e))?;
+	transaction
+		.execute(
+			"INSERT INTO indexed_ranges (low, high) VALUES (?1, ?2)",
+			params![merged_low, merged_high],
+		)
+		.map_err(|e| format!("{:?}", e))?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{
+		generic::{Block, Header},
+		traits::BlakeTwo256,
+	};
+
+	type OpaqueBlock =
+		Block<Header<u64, BlakeTwo256>, substrate_test_runtime_client::runtime::Extrinsic>;
+
+	fn log(log_index: u32, address: u64, topics: Vec<H256>) -> IndexedLog {
+		IndexedLog {
+			transaction_index: 0,
+			log_index,
+			address: H160::from_low_u64_be(address),
+			topics,
+		}
+	}
+
+	#[test]
+	fn filter_blocks_works() {
+		let db = LogIndexDb::<OpaqueBlock>::open_in_memory().expect("in memory db opens");
+		let topic_a = H256::from_low_u64_be(0xa);
+		let topic_b = H256::from_low_u64_be(0xb);
+		let hash_1 = H256::repeat_byte(1);
+		let hash_2 = H256::repeat_byte(2);
+		let hash_3 = H256::repeat_byte(3);
+
+		db.index_block(hash_1, 1, &[log(0, 1, vec![topic_a])])
+			.unwrap();
+		db.index_block(hash_2, 2, &[]).unwrap();
+		db.index_block(hash_3, 3, &[log(0, 2, vec![topic_b, topic_a])])
+			.unwrap();
+
+		assert!(db.is_indexed(&hash_2).unwrap());
+		assert!(!db.is_indexed(&H256::repeat_byte(4)).unwrap());
+		assert_eq!(db.indexed_range(0, 4).unwrap(), Some((1, 3)));
+
+		let filter = |addresses: Vec<u64>, topics: Vec<Option<Vec<H256>>>| LogFilter {
+			from_block: 1,
+			to_block: 3,
+			addresses: addresses.into_iter().map(H160::from_low_u64_be).collect(),
+			topics,
+		};
+		assert_eq!(
+			db.filter_blocks(&filter(vec![], vec![])).unwrap(),
+			vec![(1, hash_1), (3, hash_3)]
+		);
+		assert_eq!(
+			db.filter_blocks(&filter(vec![2], vec![])).unwrap(),
+			vec![(3, hash_3)]
+		);
+		assert_eq!(
+			db.filter_blocks(&filter(vec![], vec![Some(vec![topic_a])]))
+				.unwrap(),
+			vec![(1, hash_1)]
+		);
+		assert_eq!(
+			db.filter_blocks(&filter(vec![], vec![None, Some(vec![topic_a])]))
+				.unwrap(),
+			vec![(3, hash_3)]
+		);
+		assert_eq!(
+			db.filter_blocks(&filter(vec![1], vec![Some(vec![topic_b])]))
+				.unwrap(),
+			vec![]
+		);
+
+		// Re-indexing a block replaces its logs.
+		db.index_block(hash_1, 1, &[]).unwrap();
+		assert_eq!(db.filter_blocks(&filter(vec![1], vec![])).unwrap(), vec![]);
+	}
+
+	#[test]
+	fn indexed_range_tracks_contiguous_blocks() {
+		let db = LogIndexDb::<OpaqueBlock>::open_in_memory().expect("in memory db opens");
+		let hash = |n: u8| H256::repeat_byte(n);
+
+		assert_eq!(db.indexed_range(0, 10).unwrap(), None);
+		db.index_blocks(&[
+			(hash(1), 1, vec![]),
+			(hash(3), 3, vec![]),
+			(hash(2), 2, vec![]),
+			(hash(6), 6, vec![]),
+		])
+		.unwrap();
+		// Forks do not extend the ranges.
+		db.index_block(hash(0xf2), 2, &[]).unwrap();
+		assert_eq!(db.indexed_range(0, 10).unwrap(), Some((1, 3)));
+		assert_eq!(db.indexed_range(2, 10).unwrap(), Some((2, 3)));
+		assert_eq!(db.indexed_range(5, 10).unwrap(), Some((6, 6)));
+		assert_eq!(db.indexed_range(4, 5).unwrap(), None);
+
+		// Filling the gap merges the ranges.
+		db.index_blocks(&[(hash(5), 5, vec![]), (hash(4), 4, vec![])])
+			.unwrap();
+		assert_eq!(db.indexed_range(0, 10).unwrap(), Some((1, 6)));
+		assert_eq!(db.indexed_range(3, 4).unwrap(), Some((3, 4)));
+	}
+
+	#[test]
+	fn indexed_ranges_are_rebuilt_from_indexed_blocks() {
+		let tmp = tempfile::tempdir().expect("create a temporary directory");
+		let path = tmp.path().join("logs.db");
+		{
+			let db = LogIndexDb::<OpaqueBlock>::open(&path).expect("db opens");
+			for number in [1u8, 2, 4] {
+				db.index_block(H256::repeat_byte(number), number as u64, &[])
+					.unwrap();
+			}
+			// An index created before the ranges were tracked.
+			db.connection
+				.lock()
+				.execute("DELETE FROM indexed_ranges", [])
+				.unwrap();
+			assert_eq!(db.indexed_range(0, 10).unwrap(), None);
+		}
+
+		let db = LogIndexDb::<OpaqueBlock>::open(&path).expect("db opens");
+		assert_eq!(db.indexed_range(0, 10).unwrap(), Some((1, 2)));
+		assert_eq!(db.indexed_range(3, 10).unwrap(), Some((4, 4)));
+	}
+}
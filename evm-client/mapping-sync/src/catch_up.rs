@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{num::NonZeroUsize, thread};
+
+// Substrate
+use sc_client_api::BlockOf;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto, Zero},
+};
+// Frontier
+use fp_rpc::EthereumRuntimeRPCApi;
+
+/// Settings of the mapping sync catch-up mode.
+#[derive(Copy, Clone, Debug)]
+pub struct CatchUpConfig {
+	/// Number of blocks written to the mapping database in a single transaction.
+	pub batch_size: u32,
+	/// Number of threads reading the blocks of a batch.
+	pub threads: NonZeroUsize,
+}
+
+impl Default for CatchUpConfig {
+	fn default() -> Self {
+		Self {
+			batch_size: 1000,
+			threads: thread::available_parallelism()
+				.unwrap_or(NonZeroUsize::new(1).expect("1 is non-zero; qed")),
+		}
+	}
+}
+
+/// A block read by a catch-up batch, along with its logs if the log index is enabled.
+type IndexedBlock<Block> = (
+	fc_db::BlockMapping<Block>,
+	Option<(<Block as BlockT>::Hash, u64, Vec<fc_db::sql::IndexedLog>)>,
+);
+
+/// Index the next batch of finalized blocks following the catch-up checkpoint.
+///
+/// Finalized blocks are read by number, in parallel, and written along with the new
+/// checkpoint in a single transaction. Returns `false` once the checkpoint reached the
+/// last finalized block, from which point blocks are left to `sync_blocks`.
+pub fn catch_up_batch<Block: BlockT, C>(
+	client: &C,
+	frontier_backend: &fc_db::Backend<Block>,
+	log_index: Option<&fc_db::sql::LogIndexDb<Block>>,
+	sync_from: <Block::Header as HeaderT>::Number,
+	config: CatchUpConfig,
+) -> Result<bool, String>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let sync_from = UniqueSaturatedInto::<u64>::unique_saturated_into(sync_from);
+	let finalized_number =
+		UniqueSaturatedInto::<u64>::unique_saturated_into(client.info().finalized_number);
+
+	let from = match frontier_backend.meta().catch_up_checkpoint()? {
+		Some(checkpoint) => sync_from.max(checkpoint + 1),
+		None => sync_from,
+	};
+	if from > finalized_number {
+		return Ok(false);
+	}
+	let to = finalized_number.min(from + u64::from(config.batch_size.max(1)) - 1);
+
+	let with_logs = log_index.is_some();
+	let numbers = (from..=to).collect::<Vec<_>>();
+	let chunk_size = (numbers.len() + config.threads.get() - 1) / config.threads.get();
+	let blocks = thread::scope(|scope| {
+		let workers = numbers
+			.chunks(chunk_size)
+			.map(|chunk| {
+				scope.spawn(move || {
+					chunk
+						.iter()
+						.filter_map(|number| {
+							read_block(client, frontier_backend, with_logs, *number).transpose()
+						})
+						.collect::<Result<Vec<_>, String>>()
+				})
+			})
+			.collect::<Vec<_>>();
+		workers
+			.into_iter()
+			.map(|worker| {
+				worker
+					.join()
+					.map_err(|_| "Catch-up worker panicked".to_string())?
+			})
+			.collect::<Result<Vec<_>, String>>()
+	})?;
+
+	let (mappings, logs): (Vec<_>, Vec<_>) = blocks.into_iter().flatten().unzip();
+	// Logs are replaced when indexed again, so they are stored before the checkpoint moves.
+	if let Some(log_index) = log_index {
+		log_index.index_blocks(&logs.into_iter().flatten().collect::<Vec<_>>())?;
+	}
+	frontier_backend.mapping().write_batch(mappings, to)?;
+
+	log::debug!(
+		target: "mapping-sync",
+		"Caught up blocks #{}..#{} of #{}",
+		from,
+		to,
+		finalized_number,
+	);
+	Ok(true)
+}
+
+/// Read the mapping of a canonical block, or `None` if it is already synced.
+fn read_block<Block: BlockT, C>(
+	client: &C,
+	frontier_backend: &fc_db::Backend<Block>,
+	with_logs: bool,
+	number: u64,
+) -> Result<Option<IndexedBlock<Block>>, String>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let header = client
+		.header(BlockId::Number(number.unique_saturated_into()))
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| format!("Header #{} not found", number))?;
+	if frontier_backend.mapping().is_synced(&header.hash())? {
+		return Ok(None);
+	}
+
+	let mapping = if header.number() == &Zero::zero() {
+		crate::genesis_block_mapping(client, &header)?
+	} else {
+		crate::block_mapping(&header)?
+	};
+	let logs = if with_logs {
+		Some((header.hash(), number, crate::block_logs(client, &header)?))
+	} else {
+		None
+	};
+	Ok(Some((mapping, logs)))
+}
@@ -18,8 +18,10 @@
 
 #![allow(clippy::too_many_arguments)]
 
+mod catch_up;
 mod worker;
 
+pub use catch_up::{catch_up_batch, CatchUpConfig};
 pub use worker::{MappingSyncWorker, SyncStrategy};
 
 // Substrate
@@ -28,7 +30,7 @@ use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT, Zero},
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto, Zero},
 };
 // Frontier
 use fp_consensus::FindLogError;
@@ -38,24 +40,24 @@ pub fn sync_block<Block: BlockT>(
 	backend: &fc_db::Backend<Block>,
 	header: &Block::Header,
 ) -> Result<(), String> {
+	backend.mapping().write(block_mapping(header)?)
+}
+
+/// Read the mapping of a block from the Ethereum log of its digest.
+pub fn block_mapping<Block: BlockT>(
+	header: &Block::Header,
+) -> Result<fc_db::BlockMapping<Block>, String> {
 	match fp_consensus::find_log(header.digest()) {
 		Ok(log) => {
 			let post_hashes = log.into_hashes();
 
-			let mapping_commitment = fc_db::MappingCommitment {
+			Ok(fc_db::BlockMapping::Hashes(fc_db::MappingCommitment {
 				block_hash: header.hash(),
 				ethereum_block_hash: post_hashes.block_hash,
 				ethereum_transaction_hashes: post_hashes.transaction_hashes,
-			};
-			backend.mapping().write_hashes(mapping_commitment)?;
-
-			Ok(())
-		}
-		Err(FindLogError::NotFound) => {
-			backend.mapping().write_none(header.hash())?;
-
-			Ok(())
+			}))
 		}
+		Err(FindLogError::NotFound) => Ok(fc_db::BlockMapping::None(header.hash())),
 		Err(FindLogError::MultipleLogs) => Err("Multiple logs found".to_string()),
 	}
 }
@@ -65,6 +67,20 @@ pub fn sync_genesis_block<Block: BlockT, C>(
 	backend: &fc_db::Backend<Block>,
 	header: &Block::Header,
 ) -> Result<(), String>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	backend
+		.mapping()
+		.write(genesis_block_mapping(client, header)?)
+}
+
+/// Read the mapping of the genesis block, which has no digest, from the runtime.
+pub fn genesis_block_mapping<Block: BlockT, C>(
+	client: &C,
+	header: &Block::Header,
+) -> Result<fc_db::BlockMapping<Block>, String>
 where
 	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
 	C::Api: EthereumRuntimeRPCApi<Block>,
@@ -93,23 +109,77 @@ where
 			.ok_or_else(|| "Ethereum genesis block not found".to_string())?
 			.header
 			.hash();
-		let mapping_commitment = fc_db::MappingCommitment::<Block> {
+		Ok(fc_db::BlockMapping::Hashes(fc_db::MappingCommitment {
 			block_hash: header.hash(),
 			ethereum_block_hash: block_hash,
 			ethereum_transaction_hashes: Vec::new(),
-		};
-		backend.mapping().write_hashes(mapping_commitment)?;
+		}))
 	} else {
-		backend.mapping().write_none(header.hash())?;
+		Ok(fc_db::BlockMapping::None(header.hash()))
+	}
+}
+
+/// Store the logs of the block in the log index.
+pub fn index_block_logs<Block: BlockT, C>(
+	client: &C,
+	log_index: &fc_db::sql::LogIndexDb<Block>,
+	header: &Block::Header,
+) -> Result<(), String>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	log_index.index_block(
+		header.hash(),
+		UniqueSaturatedInto::<u64>::unique_saturated_into(*header.number()),
+		&block_logs(client, header)?,
+	)
+}
+
+/// Read the logs of the block from the runtime.
+pub fn block_logs<Block: BlockT, C>(
+	client: &C,
+	header: &Block::Header,
+) -> Result<Vec<fc_db::sql::IndexedLog>, String>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let id = BlockId::Hash(header.hash());
+
+	let statuses = if client
+		.runtime_api()
+		.has_api::<dyn EthereumRuntimeRPCApi<Block>>(&id)
+		.map_err(|e| format!("{:?}", e))?
+	{
+		client
+			.runtime_api()
+			.current_transaction_statuses(&id)
+			.map_err(|e| format!("{:?}", e))?
+			.unwrap_or_default()
+	} else {
+		Vec::new()
 	};
 
-	Ok(())
+	let mut logs = Vec::new();
+	for status in statuses {
+		for log in status.logs {
+			logs.push(fc_db::sql::IndexedLog {
+				transaction_index: status.transaction_index,
+				log_index: logs.len() as u32,
+				address: log.address,
+				topics: log.topics,
+			});
+		}
+	}
+	Ok(logs)
 }
 
 pub fn sync_one_block<Block: BlockT, C, B>(
 	client: &C,
 	substrate_backend: &B,
 	frontier_backend: &fc_db::Backend<Block>,
+	log_index: Option<&fc_db::sql::LogIndexDb<Block>>,
 	sync_from: <Block::Header as HeaderT>::Number,
 	strategy: SyncStrategy,
 ) -> Result<bool, String>
@@ -149,6 +219,9 @@ where
 
 	if operating_header.number() == &Zero::zero() {
 		sync_genesis_block(client, frontier_backend, &operating_header)?;
+		if let Some(log_index) = log_index {
+			index_block_logs(client, log_index, &operating_header)?;
+		}
 
 		frontier_backend
 			.meta()
@@ -161,6 +234,9 @@ where
 			return Ok(false);
 		}
 		sync_block(frontier_backend, &operating_header)?;
+		if let Some(log_index) = log_index {
+			index_block_logs(client, log_index, &operating_header)?;
+		}
 
 		current_syncing_tips.push(*operating_header.parent_hash());
 		frontier_backend
@@ -174,6 +250,7 @@ pub fn sync_blocks<Block: BlockT, C, B>(
 	client: &C,
 	substrate_backend: &B,
 	frontier_backend: &fc_db::Backend<Block>,
+	log_index: Option<&fc_db::sql::LogIndexDb<Block>>,
 	limit: usize,
 	sync_from: <Block::Header as HeaderT>::Number,
 	strategy: SyncStrategy,
@@ -191,6 +268,7 @@ where
 				client,
 				substrate_backend,
 				frontier_backend,
+				log_index,
 				sync_from,
 				strategy,
 			)?;
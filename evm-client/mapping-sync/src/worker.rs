@@ -16,14 +16,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{pin::Pin, sync::Arc, thread, time::Duration};
 
 use futures::{
+	channel::oneshot,
 	prelude::*,
 	task::{Context, Poll},
 };
 use futures_timer::Delay;
-use log::debug;
+use log::{debug, info};
 // Substrate
 use sc_client_api::{BlockOf, ImportNotifications};
 use sp_api::ProvideRuntimeApi;
@@ -32,6 +33,8 @@ use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 // Frontier
 use fp_rpc::EthereumRuntimeRPCApi;
 
+use crate::CatchUpConfig;
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum SyncStrategy {
 	Normal,
@@ -46,11 +49,14 @@ pub struct MappingSyncWorker<Block: BlockT, C, B> {
 	client: Arc<C>,
 	substrate_backend: Arc<B>,
 	frontier_backend: Arc<fc_db::Backend<Block>>,
+	log_index: Option<Arc<fc_db::sql::LogIndexDb<Block>>>,
 
 	have_next: bool,
 	retry_times: usize,
 	sync_from: <Block::Header as HeaderT>::Number,
 	strategy: SyncStrategy,
+	catch_up: Option<CatchUpConfig>,
+	catch_up_batch: Option<oneshot::Receiver<Result<bool, String>>>,
 }
 
 impl<Block: BlockT, C, B> Unpin for MappingSyncWorker<Block, C, B> {}
@@ -62,6 +68,7 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 		client: Arc<C>,
 		substrate_backend: Arc<B>,
 		frontier_backend: Arc<fc_db::Backend<Block>>,
+		log_index: Option<Arc<fc_db::sql::LogIndexDb<Block>>>,
 		retry_times: usize,
 		sync_from: <Block::Header as HeaderT>::Number,
 		strategy: SyncStrategy,
@@ -74,18 +81,28 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 			client,
 			substrate_backend,
 			frontier_backend,
+			log_index,
 
 			have_next: true,
 			retry_times,
 			sync_from,
 			strategy,
+			catch_up: None,
+			catch_up_batch: None,
 		}
 	}
+
+	/// Index the finalized blocks in parallel batches, on a dedicated thread, while following
+	/// the imported blocks.
+	pub fn with_catch_up(mut self, config: CatchUpConfig) -> Self {
+		self.catch_up = Some(config);
+		self
+	}
 }
 
 impl<Block: BlockT, C, B> Stream for MappingSyncWorker<Block, C, B>
 where
-	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf + 'static,
 	C::Api: EthereumRuntimeRPCApi<Block>,
 	B: sc_client_api::Backend<Block>,
 {
@@ -94,6 +111,27 @@ where
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<()>> {
 		let mut fire = false;
 
+		if let Some(batch) = self.catch_up_batch.as_mut() {
+			if let Poll::Ready(result) = Future::poll(Pin::new(batch), cx) {
+				self.catch_up_batch = None;
+				match result {
+					Ok(Ok(true)) => {
+						fire = true;
+					}
+					Ok(Ok(false)) => {
+						info!(target: "mapping-sync", "Catch-up reached the finalized block.");
+						self.catch_up = None;
+					}
+					Ok(Err(e)) => {
+						debug!(target: "mapping-sync", "Catch-up failed with error {:?}, retrying.", e);
+					}
+					Err(oneshot::Canceled) => {
+						debug!(target: "mapping-sync", "Catch-up thread stopped, retrying.");
+					}
+				}
+			}
+		}
+
 		loop {
 			match Stream::poll_next(Pin::new(&mut self.import_notifications), cx) {
 				Poll::Pending => break,
@@ -121,10 +159,17 @@ where
 		if fire {
 			self.inner_delay = None;
 
+			if let Some(config) = self.catch_up {
+				if self.catch_up_batch.is_none() {
+					self.spawn_catch_up_batch(config);
+				}
+			}
+
 			match crate::sync_blocks(
 				self.client.as_ref(),
 				self.substrate_backend.blockchain(),
 				self.frontier_backend.as_ref(),
+				self.log_index.as_deref(),
 				self.retry_times,
 				self.sync_from,
 				self.strategy,
@@ -144,3 +189,38 @@ where
 		}
 	}
 }
+
+impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf + 'static,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	/// Index the next catch-up batch on its own thread, so that the imported blocks keep being
+	/// synced while it runs. The batch result is polled, and the worker woken up, from
+	/// `poll_next`.
+	fn spawn_catch_up_batch(&mut self, config: CatchUpConfig) {
+		let (sender, receiver) = oneshot::channel();
+		let client = self.client.clone();
+		let frontier_backend = self.frontier_backend.clone();
+		let log_index = self.log_index.clone();
+		let sync_from = self.sync_from;
+
+		let spawned = thread::Builder::new()
+			.name("frontier-catch-up".into())
+			.spawn(move || {
+				let result = crate::catch_up_batch(
+					client.as_ref(),
+					frontier_backend.as_ref(),
+					log_index.as_deref(),
+					sync_from,
+					config,
+				);
+				let _ = sender.send(result);
+			});
+		if let Err(e) = spawned {
+			debug!(target: "mapping-sync", "Failed to spawn the catch-up thread: {:?}", e);
+			return;
+		}
+		self.catch_up_batch = Some(receiver);
+	}
+}
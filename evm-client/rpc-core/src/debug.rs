@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
+
+use crate::types::{BlockNumber, CallRequest, TraceParams, TraceResult};
+
+/// Debug rpc interface.
+#[rpc(server)]
+pub trait DebugApi {
+	/// Replays a transaction, returning its execution trace.
+	#[method(name = "debug_traceTransaction")]
+	fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		params: Option<TraceParams>,
+	) -> Result<TraceResult>;
+
+	/// Executes a call on top of the given block, returning its execution trace.
+	#[method(name = "debug_traceCall")]
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		params: Option<TraceParams>,
+	) -> Result<TraceResult>;
+}
@@ -18,6 +18,8 @@
 
 //! Eth rpc interface.
 
+use std::collections::BTreeMap;
+
 use ethereum_types::{H160, H256, H64, U256, U64};
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
 
@@ -125,6 +127,10 @@ pub trait EthApi {
 	#[method(name = "eth_getTransactionReceipt")]
 	async fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>>;
 
+	/// Returns the receipts of all the transactions of a block.
+	#[method(name = "eth_getBlockReceipts")]
+	async fn block_receipts(&self, number: BlockNumber) -> Result<Option<Vec<Receipt>>>;
+
 	// ########################################################################
 	// State
 	// ########################################################################
@@ -145,18 +151,45 @@ pub trait EthApi {
 	#[method(name = "eth_getCode")]
 	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes>;
 
+	/// Returns the account and storage values of the given account, including their
+	/// Merkle proofs (EIP-1186).
+	#[method(name = "eth_getProof")]
+	fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> Result<EthAccount>;
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
 
 	/// Call contract, returning the output data.
 	#[method(name = "eth_call")]
-	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes>;
+	fn call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<Bytes>;
 
 	/// Estimate gas needed for execution of given contract.
 	#[method(name = "eth_estimateGas")]
-	async fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>)
-		-> Result<U256>;
+	async fn estimate_gas(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<U256>;
+
+	/// Generate an access list for the given call, along with the gas it uses.
+	#[method(name = "eth_createAccessList")]
+	fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> Result<AccessListResult>;
 
 	// ########################################################################
 	// Fee
@@ -216,6 +249,18 @@ pub trait EthApi {
 	/// Sends signed transaction, returning its hash.
 	#[method(name = "eth_sendRawTransaction")]
 	async fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
+
+	// ########################################################################
+	// Sign
+	// ########################################################################
+
+	/// Signs the EIP-191 prefixed data with the given account.
+	#[method(name = "eth_sign")]
+	fn sign(&self, address: H160, data: Bytes) -> Result<Bytes>;
+
+	/// Signs EIP-712 typed structured data with the given account.
+	#[method(name = "eth_signTypedData_v4")]
+	fn sign_typed_data(&self, address: H160, typed_data: TypedData) -> Result<Bytes>;
 }
 
 /// Eth filters rpc api (polling).
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Personal rpc interface.
+
+use ethereum_types::{H160, H256};
+use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
+
+use crate::types::{Bytes, TransactionRequest};
+
+/// Personal rpc interface.
+#[rpc(server)]
+#[async_trait]
+pub trait PersonalApi {
+	/// Creates a new account, encrypted with the given password.
+	#[method(name = "personal_newAccount")]
+	async fn new_account(&self, password: String) -> Result<H160>;
+
+	/// Imports a hex encoded private key, encrypted with the given password.
+	#[method(name = "personal_importRawKey")]
+	async fn import_raw_key(&self, key: String, password: String) -> Result<H160>;
+
+	/// Returns the accounts stored by the node.
+	#[method(name = "personal_listAccounts")]
+	fn list_accounts(&self) -> Result<Vec<H160>>;
+
+	/// Unlocks an account for the given number of seconds, 300 by default, or until
+	/// the node stops for 0.
+	#[method(name = "personal_unlockAccount")]
+	async fn unlock_account(
+		&self,
+		address: H160,
+		password: String,
+		duration: Option<u64>,
+	) -> Result<bool>;
+
+	/// Sends a transaction signed by an account decrypted with the given password,
+	/// without unlocking it.
+	#[method(name = "personal_sendTransaction")]
+	async fn send_transaction(&self, request: TransactionRequest, password: String)
+		-> Result<H256>;
+
+	/// Signs the EIP-191 prefixed data with the given account, decrypted with the
+	/// password if given, or otherwise unlocked.
+	#[method(name = "personal_sign")]
+	async fn sign(&self, data: Bytes, address: H160, password: Option<String>) -> Result<Bytes>;
+}
@@ -16,10 +16,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
+
 use crate::types::Bytes;
 use ethereum::AccessListItem;
-use ethereum_types::{H160, U256};
-use serde::Deserialize;
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
 
 /// Call request
 #[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
@@ -50,3 +52,68 @@ pub struct CallRequest {
 	#[serde(rename = "type")]
 	pub transaction_type: Option<U256>,
 }
+
+/// Account state override of `eth_call` and `eth_estimateGas`
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStateOverride {
+	/// Fake balance to set for the account before executing the call
+	pub balance: Option<U256>,
+	/// Fake nonce to set for the account before executing the call
+	pub nonce: Option<U256>,
+	/// Fake EVM bytecode to inject into the account before executing the call
+	pub code: Option<Bytes>,
+	/// Fake key-value mapping to override all slots in the account storage before
+	/// executing the call
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Fake key-value mapping to override individual slots in the account storage before
+	/// executing the call
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// Result of `eth_createAccessList`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+	/// Addresses and storage slots accessed by the call
+	pub access_list: Vec<AccessListItem>,
+	/// Gas used by the call when executed with `access_list`
+	pub gas_used: U256,
+	/// Error of the call, if it did not succeed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn state_override_deserialization() {
+		let overrides: BTreeMap<H160, CallStateOverride> = serde_json::from_str(
+			r#"{
+				"0x0000000000000000000000000000000000000001": {
+					"balance": "0x10",
+					"code": "0x6000",
+					"stateDiff": {
+						"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+					}
+				}
+			}"#,
+		)
+		.unwrap();
+		let state_override = &overrides[&H160::from_low_u64_be(1)];
+		assert_eq!(state_override.balance, Some(U256::from(16)));
+		assert_eq!(state_override.nonce, None);
+		assert_eq!(state_override.code, Some(Bytes(vec![0x60, 0x00])));
+		assert_eq!(state_override.state, None);
+		assert_eq!(
+			state_override.state_diff,
+			Some(BTreeMap::from([(
+				H256::from_low_u64_be(1),
+				H256::from_low_u64_be(2)
+			)]))
+		);
+	}
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Bytes;
+
+/// Options of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceParams {
+	/// Name of the tracer to use, only `callTracer` is supported.
+	/// Struct logs are returned when missing.
+	pub tracer: Option<String>,
+	pub disable_storage: Option<bool>,
+	pub disable_memory: Option<bool>,
+	pub disable_stack: Option<bool>,
+	/// Accepted for compatibility, traces are not interrupted.
+	pub timeout: Option<String>,
+}
+
+/// Result of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+	StructLogs(StructLogsTrace),
+	CallTracer(CallFrame),
+}
+
+/// Default (struct logger) trace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogsTrace {
+	/// Gas used by the transaction.
+	pub gas: u64,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// Returned data, hex encoded without prefix.
+	pub return_value: String,
+	pub struct_logs: Vec<StructLogEntry>,
+}
+
+/// A single executed opcode.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogEntry {
+	pub pc: u64,
+	/// Opcode name.
+	pub op: String,
+	/// Gas remaining before the opcode is executed.
+	pub gas: u64,
+	pub gas_cost: u64,
+	pub depth: u32,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory, as 32 bytes words hex encoded without prefix.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Storage slots, hex encoded without prefix.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+/// A frame of a `callTracer` trace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+	/// `CALL`, `STATICCALL`, `CREATE`, ...
+	#[serde(rename = "type")]
+	pub call_type: String,
+	pub from: H160,
+	pub to: H160,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	pub gas: U256,
+	pub gas_used: U256,
+	pub input: Bytes,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub output: Option<Bytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub revert_reason: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trace_params_deserialization() {
+		let params: TraceParams =
+			serde_json::from_str(r#"{"tracer":"callTracer","timeout":"10s"}"#).unwrap();
+		assert_eq!(params.tracer.as_deref(), Some("callTracer"));
+		assert_eq!(params.disable_storage, None);
+
+		let params: TraceParams =
+			serde_json::from_str(r#"{"disableStorage":true,"disableMemory":false}"#).unwrap();
+		assert_eq!(params.tracer, None);
+		assert_eq!(params.disable_storage, Some(true));
+		assert_eq!(params.disable_memory, Some(false));
+	}
+
+	#[test]
+	fn struct_logs_serialization() {
+		let trace = TraceResult::StructLogs(StructLogsTrace {
+			gas: 21_000,
+			failed: false,
+			return_value: String::new(),
+			struct_logs: vec![StructLogEntry {
+				pc: 0,
+				op: "PUSH1".into(),
+				gas: 78_811,
+				gas_cost: 3,
+				depth: 1,
+				stack: Some(vec![]),
+				memory: None,
+				storage: None,
+			}],
+		});
+		assert_eq!(
+			serde_json::to_string(&trace).unwrap(),
+			r#"{"gas":21000,"failed":false,"returnValue":"","structLogs":[{"pc":0,"op":"PUSH1","gas":78811,"gasCost":3,"depth":1,"stack":[]}]}"#
+		);
+	}
+}
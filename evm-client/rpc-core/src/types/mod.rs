@@ -23,14 +23,18 @@ mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod debug;
 mod fee;
 mod filter;
 mod index;
 mod log;
 mod receipt;
 mod sync;
+mod trace;
 mod transaction;
 mod transaction_request;
+mod txpool;
+mod typed_data;
 mod work;
 
 pub mod pubsub;
@@ -40,7 +44,8 @@ pub use self::{
 	block::{Block, BlockTransactions, Header, Rich, RichBlock, RichHeader},
 	block_number::BlockNumber,
 	bytes::Bytes,
-	call_request::CallRequest,
+	call_request::{AccessListResult, CallRequest, CallStateOverride},
+	debug::{CallFrame, StructLogEntry, StructLogsTrace, TraceParams, TraceResult},
 	fee::{FeeHistory, FeeHistoryCache, FeeHistoryCacheItem, FeeHistoryCacheLimit},
 	filter::{
 		Filter, FilterAddress, FilterChanges, FilterPool, FilterPoolItem, FilterType,
@@ -53,7 +58,13 @@ pub use self::{
 		ChainStatus, EthProtocolInfo, PeerCount, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 		Peers, PipProtocolInfo, SyncInfo, SyncStatus, TransactionStats,
 	},
+	trace::{
+		Action, ActionResult, ActionType, CallAction, CallType, CreateAction, LocalizedTrace,
+		SuicideAction, TraceFilter,
+	},
 	transaction::{LocalTransactionStatus, RichRawTransaction, Transaction},
 	transaction_request::{TransactionMessage, TransactionRequest},
+	txpool::{Summary, TransactionMap, TxPoolResult},
+	typed_data::{TypedData, TypedDataField},
 	work::Work,
 };
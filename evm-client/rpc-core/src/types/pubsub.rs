@@ -18,7 +18,7 @@
 
 //! Pub-Sub types.
 
-use crate::types::{Filter, Log, RichHeader};
+use crate::types::{Filter, Log, RichHeader, Transaction};
 use ethereum_types::H256;
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Value};
@@ -32,6 +32,8 @@ pub enum Result {
 	Log(Box<Log>),
 	/// Transaction hash
 	TransactionHash(H256),
+	/// Full transaction
+	Transaction(Box<Transaction>),
 	/// SyncStatus
 	SyncState(PubSubSyncStatus),
 }
@@ -62,6 +64,7 @@ impl Serialize for Result {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::Transaction(ref transaction) => transaction.serialize(serializer),
 			Result::SyncState(ref sync) => sync.serialize(serializer),
 		}
 	}
@@ -80,6 +83,8 @@ pub enum Kind {
 	NewPendingTransactions,
 	/// Node syncing status subscription.
 	Syncing,
+	/// Finalized block headers subscription.
+	FinalizedHeads,
 }
 
 /// Subscription kind.
@@ -89,6 +94,8 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Boolean parameter, whether to return full transactions on `newPendingTransactions`.
+	Bool(bool),
 }
 
 impl Default for Params {
@@ -108,8 +115,29 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if let Value::Bool(full) = v {
+			return Ok(Params::Bool(full));
+		}
+
 		from_value(v)
 			.map(Params::Logs)
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn params_deserialization() {
+		let params: Params = serde_json::from_str("null").unwrap();
+		assert_eq!(params, Params::None);
+
+		let params: Params = serde_json::from_str("true").unwrap();
+		assert_eq!(params, Params::Bool(true));
+
+		let params: Params = serde_json::from_str("{}").unwrap();
+		assert!(matches!(params, Params::Logs(_)));
+	}
+}
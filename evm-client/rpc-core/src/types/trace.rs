@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{BlockNumber, Bytes};
+
+/// `trace_filter` parameters.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+	/// From this block.
+	pub from_block: Option<BlockNumber>,
+	/// To this block.
+	pub to_block: Option<BlockNumber>,
+	/// Only traces sent from one of these addresses.
+	pub from_address: Option<Vec<H160>>,
+	/// Only traces sent to one of these addresses.
+	pub to_address: Option<Vec<H160>>,
+	/// Number of matching traces to skip.
+	pub after: Option<usize>,
+	/// Maximum number of traces to return.
+	pub count: Option<usize>,
+}
+
+impl TraceFilter {
+	/// Whether a trace going from `from` to `to` matches the address filters.
+	pub fn matches(&self, from: &H160, to: &H160) -> bool {
+		let matches = |addresses: &Option<Vec<H160>>, address: &H160| match addresses {
+			Some(addresses) if !addresses.is_empty() => addresses.contains(address),
+			_ => true,
+		};
+		matches(&self.from_address, from) && matches(&self.to_address, to)
+	}
+}
+
+/// Trace of a single call, create or suicide, located within its block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTrace {
+	pub action: Action,
+	pub result: Option<ActionResult>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Number of direct sub-traces.
+	pub subtraces: usize,
+	/// Path from the top level call, as the index of each sub-trace.
+	pub trace_address: Vec<usize>,
+	pub transaction_hash: H256,
+	pub transaction_position: usize,
+	pub block_hash: H256,
+	pub block_number: U256,
+	#[serde(rename = "type")]
+	pub action_type: ActionType,
+}
+
+/// Kind of traced action.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionType {
+	Call,
+	Create,
+	Suicide,
+}
+
+/// Traced action.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Action {
+	Call(CallAction),
+	Create(CreateAction),
+	Suicide(SuicideAction),
+}
+
+impl Action {
+	pub fn action_type(&self) -> ActionType {
+		match self {
+			Action::Call(_) => ActionType::Call,
+			Action::Create(_) => ActionType::Create,
+			Action::Suicide(_) => ActionType::Suicide,
+		}
+	}
+}
+
+/// Kind of call.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallType {
+	Call,
+	CallCode,
+	DelegateCall,
+	StaticCall,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+	pub call_type: CallType,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: U256,
+	pub input: Bytes,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+	pub from: H160,
+	pub value: U256,
+	pub gas: U256,
+	pub init: Bytes,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuicideAction {
+	pub address: H160,
+	pub refund_address: H160,
+	pub balance: U256,
+}
+
+/// Result of a successful action.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ActionResult {
+	#[serde(rename_all = "camelCase")]
+	Call { gas_used: U256, output: Bytes },
+	#[serde(rename_all = "camelCase")]
+	Create {
+		gas_used: U256,
+		code: Bytes,
+		address: H160,
+	},
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trace_filter_deserialization() {
+		let s = r#"{"fromBlock":"0x1","toBlock":"latest","toAddress":["0x0000000000000000000000000000000000000001"],"count":10}"#;
+		let filter: TraceFilter = serde_json::from_str(s).unwrap();
+		assert_eq!(
+			filter,
+			TraceFilter {
+				from_block: Some(BlockNumber::Num(1)),
+				to_block: Some(BlockNumber::Latest),
+				from_address: None,
+				to_address: Some(vec![H160::from_low_u64_be(1)]),
+				after: None,
+				count: Some(10),
+			}
+		);
+		assert!(filter.matches(&H160::zero(), &H160::from_low_u64_be(1)));
+		assert!(!filter.matches(&H160::from_low_u64_be(1), &H160::zero()));
+	}
+
+	#[test]
+	fn localized_trace_serialization() {
+		let trace = LocalizedTrace {
+			action: Action::Suicide(SuicideAction {
+				address: H160::zero(),
+				refund_address: H160::zero(),
+				balance: U256::from(1),
+			}),
+			result: None,
+			error: None,
+			subtraces: 0,
+			trace_address: vec![0],
+			transaction_hash: H256::zero(),
+			transaction_position: 0,
+			block_hash: H256::zero(),
+			block_number: U256::from(1),
+			action_type: ActionType::Suicide,
+		};
+		let serialized = serde_json::to_value(&trace).unwrap();
+		assert_eq!(serialized["type"], "suicide");
+		assert_eq!(serialized["action"]["balance"], "0x1");
+		assert_eq!(serialized["result"], serde_json::Value::Null);
+		assert_eq!(serialized["traceAddress"], serde_json::json!([0]));
+	}
+}
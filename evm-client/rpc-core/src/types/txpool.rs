@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, U256};
+use serde::{Serialize, Serializer};
+
+/// Transactions of the pool, by sender and nonce.
+pub type TransactionMap<T> = BTreeMap<H160, BTreeMap<u64, T>>;
+
+/// Content of the pool, split between ready and future transactions.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct TxPoolResult<T> {
+	/// Transactions ready to be included in a block.
+	pub pending: T,
+	/// Transactions waiting for a nonce gap to be filled.
+	pub queued: T,
+}
+
+/// Summary of a pool transaction, as returned by `txpool_inspect`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Summary {
+	/// Recipient, `None` for contract creations.
+	pub to: Option<H160>,
+	/// Transferred value.
+	pub value: U256,
+	/// Gas limit.
+	pub gas: U256,
+	/// Gas price, or max fee per gas for EIP-1559 transactions.
+	pub gas_price: U256,
+}
+
+impl Serialize for Summary {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let to = match self.to {
+			Some(to) => format!("{:?}", to),
+			None => "contract creation".to_string(),
+		};
+		serializer.serialize_str(&format!(
+			"{}: {} wei + {} gas × {} wei",
+			to, self.value, self.gas, self.gas_price
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn inspect_serialization() {
+		let sender = H160::from_low_u64_be(1);
+		let summary = |to| Summary {
+			to,
+			value: U256::from(10),
+			gas: U256::from(21_000),
+			gas_price: U256::from(1_000_000_000u64),
+		};
+		let result = TxPoolResult {
+			pending: BTreeMap::from([(
+				sender,
+				BTreeMap::from([(2, summary(Some(H160::from_low_u64_be(2))))]),
+			)]),
+			queued: BTreeMap::from([(sender, BTreeMap::from([(4, summary(None))]))]),
+		};
+		assert_eq!(
+			serde_json::to_string(&result).unwrap(),
+			"{\"pending\":{\"0x0000000000000000000000000000000000000001\":{\"2\":\"0x0000000000000000000000000000000000000002: 10 wei + 21000 gas × 1000000000 wei\"}},\
+			\"queued\":{\"0x0000000000000000000000000000000000000001\":{\"4\":\"contract creation: 10 wei + 21000 gas × 1000000000 wei\"}}}"
+		);
+	}
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use serde::{de::Error, Deserialize, Deserializer};
+use serde_json::Value;
+
+/// A member of an EIP-712 struct type.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct TypedDataField {
+	/// Name of the member.
+	pub name: String,
+	/// Solidity type of the member, either an atomic, dynamic, struct or array type.
+	#[serde(rename = "type")]
+	pub r#type: String,
+}
+
+/// JSON object of [`TypedData`].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypedDataObject {
+	types: BTreeMap<String, Vec<TypedDataField>>,
+	primary_type: String,
+	domain: Value,
+	message: Value,
+}
+
+/// EIP-712 typed structured data, as signed by `eth_signTypedData_v4`.
+///
+/// Wallets send it either as a JSON object or as a string holding that object,
+/// both are accepted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedData {
+	/// Struct types, including the `EIP712Domain` type.
+	pub types: BTreeMap<String, Vec<TypedDataField>>,
+	/// Type of the message.
+	pub primary_type: String,
+	/// Values of the `EIP712Domain` members.
+	pub domain: Value,
+	/// Values of the message members.
+	pub message: Value,
+}
+
+impl<'a> Deserialize<'a> for TypedData {
+	fn deserialize<D>(deserializer: D) -> Result<TypedData, D::Error>
+	where
+		D: Deserializer<'a>,
+	{
+		let object = match Value::deserialize(deserializer)? {
+			Value::String(json) => serde_json::from_str::<TypedDataObject>(&json),
+			value => serde_json::from_value::<TypedDataObject>(value),
+		}
+		.map_err(D::Error::custom)?;
+
+		Ok(TypedData {
+			types: object.types,
+			primary_type: object.primary_type,
+			domain: object.domain,
+			message: object.message,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn typed_data_deserialize_from_object_and_string() {
+		let object = r#"{
+			"types": {
+				"EIP712Domain": [{ "name": "name", "type": "string" }],
+				"Person": [{ "name": "wallet", "type": "address" }]
+			},
+			"primaryType": "Person",
+			"domain": { "name": "Ether Mail" },
+			"message": { "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" }
+		}"#;
+		let string = serde_json::to_string(object).unwrap();
+
+		let from_object: TypedData = serde_json::from_str(object).unwrap();
+		let from_string: TypedData = serde_json::from_str(&string).unwrap();
+		assert_eq!(from_object, from_string);
+		assert_eq!(from_object.primary_type, "Person");
+		assert_eq!(
+			from_object.types["Person"],
+			vec![TypedDataField {
+				name: "wallet".into(),
+				r#type: "address".into(),
+			}]
+		);
+	}
+}
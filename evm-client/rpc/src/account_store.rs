@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::Write,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+use ctr::cipher::{NewCipher, StreamCipher};
+use ethereum_types::{H160, H256};
+use hmac::Hmac;
+use jsonrpsee::core::Error;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+// Substrate
+use sp_core::hashing::keccak_256;
+
+use crate::{
+	internal_err,
+	signer::{self, EthSigner},
+};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Number of PBKDF2 rounds deriving the encryption key of new key files.
+const KDF_ROUNDS: u32 = 262_144;
+
+/// Ethereum accounts stored as encrypted key files, in the Web3 Secret Storage format.
+///
+/// Every stored account is listed, but only signs while unlocked.
+#[derive(Clone)]
+pub struct EthAccountStore {
+	path: PathBuf,
+	kdf_rounds: u32,
+	unlocked: Arc<Mutex<BTreeMap<H160, UnlockedAccount>>>,
+}
+
+struct UnlockedAccount {
+	secret: libsecp256k1::SecretKey,
+	/// Instant the account locks again, `None` if it stays unlocked.
+	until: Option<Instant>,
+}
+
+impl EthAccountStore {
+	/// Open the store of key files held in the given directory, creating it if needed.
+	pub fn open(path: PathBuf) -> Result<Self, String> {
+		fs::create_dir_all(&path).map_err(|e| format!("{:?}", e))?;
+		Ok(Self {
+			path,
+			kdf_rounds: KDF_ROUNDS,
+			unlocked: Default::default(),
+		})
+	}
+
+	/// Generate a new account, encrypted with the given password.
+	pub fn new_account(&self, password: &str) -> Result<H160, String> {
+		let secret = loop {
+			if let Ok(secret) = libsecp256k1::SecretKey::parse(&rand::random()) {
+				break secret;
+			}
+		};
+		self.store(&secret, password)
+	}
+
+	/// Import an account from its private key, encrypted with the given password.
+	pub fn import_raw_key(&self, key: &[u8], password: &str) -> Result<H160, String> {
+		let secret =
+			libsecp256k1::SecretKey::parse_slice(key).map_err(|_| "invalid private key")?;
+		self.store(&secret, password)
+	}
+
+	/// Addresses of the stored accounts.
+	pub fn addresses(&self) -> Result<Vec<H160>, String> {
+		let mut addresses = Vec::new();
+		for entry in fs::read_dir(&self.path).map_err(|e| format!("{:?}", e))? {
+			let path = entry.map_err(|e| format!("{:?}", e))?.path();
+			let address = path
+				.file_stem()
+				.filter(|_| {
+					path.extension()
+						.map_or(false, |extension| extension == "json")
+				})
+				.and_then(|name| name.to_str())
+				.and_then(|name| name.parse::<H160>().ok());
+			if let Some(address) = address {
+				addresses.push(address);
+			}
+		}
+		addresses.sort();
+		Ok(addresses)
+	}
+
+	/// Unlock an account for the given duration, or until the node stops if `None`.
+	pub fn unlock(
+		&self,
+		address: &H160,
+		password: &str,
+		duration: Option<Duration>,
+	) -> Result<(), String> {
+		let secret = self.secret(address, password)?;
+		self.unlocked.lock().map_err(|_| "poisoned lock")?.insert(
+			*address,
+			UnlockedAccount {
+				secret,
+				until: duration.map(|duration| Instant::now() + duration),
+			},
+		);
+		Ok(())
+	}
+
+	/// Decrypt the private key of an account.
+	pub fn secret(
+		&self,
+		address: &H160,
+		password: &str,
+	) -> Result<libsecp256k1::SecretKey, String> {
+		let json = fs::read(self.key_file(address)).map_err(|_| "account not found")?;
+		let key_file: KeyFile = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+		let secret = key_file.crypto.decrypt(password.as_bytes())?;
+		libsecp256k1::SecretKey::parse_slice(&secret).map_err(|_| "invalid private key".into())
+	}
+
+	fn store(&self, secret: &libsecp256k1::SecretKey, password: &str) -> Result<H160, String> {
+		let address = signer::secret_key_address(secret);
+		let key_file = KeyFile {
+			version: 3,
+			id: uuid(),
+			address: hex::encode(address),
+			crypto: Crypto::encrypt(&secret.serialize(), password.as_bytes(), self.kdf_rounds),
+		};
+
+		let mut options = fs::OpenOptions::new();
+		options.write(true).create_new(true);
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::OpenOptionsExt;
+			options.mode(0o600);
+		}
+		let mut file = options
+			.open(self.key_file(&address))
+			.map_err(|e| format!("{:?}", e))?;
+		serde_json::to_writer(&mut file, &key_file).map_err(|e| e.to_string())?;
+		file.flush().map_err(|e| format!("{:?}", e))?;
+		Ok(address)
+	}
+
+	fn key_file(&self, address: &H160) -> PathBuf {
+		self.path.join(format!("{:x}.json", address))
+	}
+}
+
+impl EthSigner for EthAccountStore {
+	fn accounts(&self) -> Vec<H160> {
+		self.addresses().unwrap_or_else(|err| {
+			log::warn!(target: "rpc", "Failed to list the Ethereum accounts: {}", err);
+			Vec::new()
+		})
+	}
+
+	fn sign_hash(&self, hash: H256, address: &H160) -> Result<[u8; 65], Error> {
+		let mut unlocked = self
+			.unlocked
+			.lock()
+			.map_err(|_| internal_err("poisoned lock"))?;
+		let now = Instant::now();
+		unlocked.retain(|_, account| account.until.map_or(true, |until| until > now));
+
+		match unlocked.get(address) {
+			Some(account) => signer::sign_with_secret(&account.secret, hash),
+			None if self.key_file(address).exists() => Err(internal_err("account is locked")),
+			None => Err(internal_err("signer not available")),
+		}
+	}
+}
+
+/// A Web3 Secret Storage key file.
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+	version: u32,
+	id: String,
+	address: String,
+	crypto: Crypto,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Crypto {
+	cipher: String,
+	cipherparams: CipherParams,
+	ciphertext: String,
+	kdf: String,
+	kdfparams: KdfParams,
+	mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CipherParams {
+	iv: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+	c: u32,
+	dklen: u32,
+	prf: String,
+	salt: String,
+}
+
+impl Crypto {
+	fn encrypt(secret: &[u8], password: &[u8], rounds: u32) -> Self {
+		let salt: [u8; 32] = rand::random();
+		let iv: [u8; 16] = rand::random();
+		let derived_key = derive_key(password, &salt, rounds);
+
+		let mut ciphertext = secret.to_vec();
+		Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+			.expect("Key and IV have valid lengths; qed")
+			.apply_keystream(&mut ciphertext);
+
+		Crypto {
+			cipher: "aes-128-ctr".into(),
+			cipherparams: CipherParams {
+				iv: hex::encode(iv),
+			},
+			mac: hex::encode(mac(&derived_key, &ciphertext)),
+			ciphertext: hex::encode(ciphertext),
+			kdf: "pbkdf2".into(),
+			kdfparams: KdfParams {
+				c: rounds,
+				dklen: 32,
+				prf: "hmac-sha256".into(),
+				salt: hex::encode(salt),
+			},
+		}
+	}
+
+	fn decrypt(&self, password: &[u8]) -> Result<Vec<u8>, String> {
+		if self.cipher != "aes-128-ctr" {
+			return Err(format!("unsupported cipher {}", self.cipher));
+		}
+		if self.kdf != "pbkdf2" || self.kdfparams.prf != "hmac-sha256" || self.kdfparams.dklen != 32
+		{
+			return Err(format!("unsupported key derivation {}", self.kdf));
+		}
+
+		let salt = hex::decode(&self.kdfparams.salt).map_err(|e| e.to_string())?;
+		let iv = hex::decode(&self.cipherparams.iv).map_err(|e| e.to_string())?;
+		let mut ciphertext = hex::decode(&self.ciphertext).map_err(|e| e.to_string())?;
+		let derived_key = derive_key(password, &salt, self.kdfparams.c);
+
+		if hex::encode(mac(&derived_key, &ciphertext)) != self.mac {
+			return Err("could not decrypt key with given password".into());
+		}
+		Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+			.map_err(|_| "invalid cipher parameters")?
+			.apply_keystream(&mut ciphertext);
+		Ok(ciphertext)
+	}
+}
+
+fn derive_key(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 32] {
+	let mut derived_key = [0u8; 32];
+	pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, rounds, &mut derived_key);
+	derived_key
+}
+
+fn mac(derived_key: &[u8; 32], ciphertext: &[u8]) -> [u8; 32] {
+	keccak_256(&[&derived_key[16..], ciphertext].concat())
+}
+
+/// A random version 4 UUID.
+fn uuid() -> String {
+	let mut bytes: [u8; 16] = rand::random();
+	bytes[6] = (bytes[6] & 0x0f) | 0x40;
+	bytes[8] = (bytes[8] & 0x3f) | 0x80;
+	let hex = hex::encode(bytes);
+	format!(
+		"{}-{}-{}-{}-{}",
+		&hex[0..8],
+		&hex[8..12],
+		&hex[12..16],
+		&hex[16..20],
+		&hex[20..32]
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn open_store(path: PathBuf) -> EthAccountStore {
+		EthAccountStore {
+			kdf_rounds: 1024,
+			..EthAccountStore::open(path).unwrap()
+		}
+	}
+
+	#[test]
+	fn import_raw_key_round_trips() {
+		let tmp = tempfile::tempdir().expect("create a temporary directory");
+		let store = open_store(tmp.path().to_path_buf());
+
+		let key = [0x11; 32];
+		let address = store.import_raw_key(&key, "password").unwrap();
+		assert_eq!(store.addresses().unwrap(), vec![address]);
+		assert_eq!(store.secret(&address, "password").unwrap().serialize(), key);
+		assert!(store.secret(&address, "wrong password").is_err());
+		assert!(store.import_raw_key(&key, "password").is_err());
+	}
+
+	#[test]
+	fn accounts_only_sign_while_unlocked() {
+		let tmp = tempfile::tempdir().expect("create a temporary directory");
+		let store = open_store(tmp.path().to_path_buf());
+
+		let address = store.new_account("password").unwrap();
+		assert_eq!(store.accounts(), vec![address]);
+		assert!(store.sign_hash(H256::zero(), &address).is_err());
+
+		store.unlock(&address, "password", None).unwrap();
+		assert!(store.sign_hash(H256::zero(), &address).is_ok());
+
+		store
+			.unlock(&address, "password", Some(Duration::ZERO))
+			.unwrap();
+		assert!(store.sign_hash(H256::zero(), &address).is_err());
+	}
+}
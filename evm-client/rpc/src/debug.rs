@@ -0,0 +1,426 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H256, U256};
+use evm::{ExitError, ExitReason};
+use jsonrpsee::core::RpcResult as Result;
+// Substrate
+use sc_client_api::BlockBackend;
+use sc_rpc::DenyUnsafe;
+use sp_api::{ApiExt, ApiRef, Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+	SaturatedConversion,
+};
+// Frontier
+use fc_rpc_core::{types::*, DebugApiServer};
+use fp_evm::tracing::{CallTrace, CallType, StructLog, TraceType, TransactionTrace};
+use fp_rpc::{DebugRuntimeApi, EthereumRuntimeRPCApi};
+
+use crate::{frontier_backend_client, internal_err};
+
+/// Debug API implementation.
+///
+/// Every method is unsafe, as replaying blocks is expensive.
+pub struct Debug<B, C> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	deny_unsafe: DenyUnsafe,
+	safe_depth: Option<u32>,
+	_marker: PhantomData<B>,
+}
+
+impl<B, C> Debug<B, C> {
+	pub fn new(client: Arc<C>, backend: Arc<fc_db::Backend<B>>, deny_unsafe: DenyUnsafe) -> Self {
+		Self {
+			client,
+			backend,
+			deny_unsafe,
+			safe_depth: None,
+			_marker: PhantomData,
+		}
+	}
+
+	pub fn with_safe_depth(mut self, safe_depth: Option<u32>) -> Self {
+		self.safe_depth = safe_depth;
+		self
+	}
+}
+
+impl<B, C> DebugApiServer for Debug<B, C>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + BlockBackend<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: DebugRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+{
+	fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		params: Option<TraceParams>,
+	) -> Result<TraceResult> {
+		self.deny_unsafe.check_if_safe()?;
+		let trace_type = trace_type(params)?;
+
+		let (id, _) = match transaction_block::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			transaction_hash,
+		)? {
+			Some(found) => found,
+			None => return Err(internal_err("transaction not found")),
+		};
+		let (api, parent_id, extrinsics) = replay_api::<B, C>(self.client.as_ref(), id)?;
+		let trace = api
+			.trace_transaction(&parent_id, extrinsics, transaction_hash, trace_type)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		Ok(trace_result(trace))
+	}
+
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		params: Option<TraceParams>,
+	) -> Result<TraceResult> {
+		self.deny_unsafe.check_if_safe()?;
+		let trace_type = trace_type(params)?;
+
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			gas,
+			value,
+			data,
+			nonce,
+			access_list,
+			..
+		} = request;
+
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+			self.safe_depth,
+		)?
+		.unwrap_or_else(|| BlockId::Hash(self.client.info().best_hash));
+
+		let api = self.client.runtime_api();
+		if !api.has_api::<dyn DebugRuntimeApi<B>>(&id).unwrap_or(false) {
+			return Err(internal_err("runtime does not support tracing"));
+		}
+
+		// Use the given gas limit or the block one.
+		let gas_limit = match gas {
+			Some(gas) => gas,
+			None => {
+				api.current_block(&id)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?
+					.header
+					.gas_limit
+			}
+		};
+
+		let trace = api
+			.trace_call(
+				&id,
+				from.unwrap_or_default(),
+				to,
+				data.map(|d| d.0).unwrap_or_default(),
+				value.unwrap_or_default(),
+				gas_limit,
+				max_fee_per_gas.or(gas_price),
+				max_priority_fee_per_gas.or(gas_price),
+				nonce,
+				access_list.map(|list| {
+					list.into_iter()
+						.map(|item| (item.address, item.storage_keys))
+						.collect()
+				}),
+				trace_type,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		Ok(trace_result(trace))
+	}
+}
+
+/// Returns the block containing a transaction, along with the transaction index.
+pub(crate) fn transaction_block<B, C>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	transaction_hash: H256,
+) -> Result<Option<(BlockId<B>, usize)>>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+{
+	let (eth_block_hash, index) = match frontier_backend_client::load_transactions::<B, C>(
+		client,
+		backend,
+		transaction_hash,
+		true,
+	)? {
+		Some(found) => found,
+		None => return Ok(None),
+	};
+	Ok(
+		frontier_backend_client::load_hash::<B, C>(client, backend, eth_block_hash)?
+			.map(|id| (id, index as usize)),
+	)
+}
+
+/// Prepares a runtime api to replay the extrinsics of a block: the block is initialized on
+/// top of its parent state, whose id is returned along with the extrinsics to apply.
+pub(crate) fn replay_api<B, C>(
+	client: &C,
+	id: BlockId<B>,
+) -> Result<(ApiRef<'_, C::Api>, BlockId<B>, Vec<B::Extrinsic>)>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + BlockBackend<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: DebugRuntimeApi<B>,
+{
+	let substrate_hash = client
+		.expect_block_hash_from_id(&id)
+		.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+	let header = match client.header(id) {
+		Ok(Some(header)) => header,
+		_ => {
+			return Err(internal_err(format!(
+				"Cannot get header for block {:?}",
+				id
+			)))
+		}
+	};
+	let extrinsics = match client.block_body(substrate_hash) {
+		Ok(Some(extrinsics)) => extrinsics,
+		_ => return Err(internal_err(format!("Cannot get body for block {:?}", id))),
+	};
+	let parent_id = BlockId::Hash(*header.parent_hash());
+
+	let api = client.runtime_api();
+	if !api
+		.has_api::<dyn DebugRuntimeApi<B>>(&parent_id)
+		.unwrap_or(false)
+	{
+		return Err(internal_err("runtime does not support tracing"));
+	}
+	api.initialize_block(&parent_id, &header)
+		.map_err(|err| internal_err(format!("Runtime api access error: {:?}", err)))?;
+
+	Ok((api, parent_id, extrinsics))
+}
+
+fn trace_type(params: Option<TraceParams>) -> Result<TraceType> {
+	let params = params.unwrap_or_default();
+	match params.tracer.as_deref() {
+		None => Ok(TraceType::Raw {
+			disable_storage: params.disable_storage.unwrap_or(false),
+			disable_memory: params.disable_memory.unwrap_or(false),
+			disable_stack: params.disable_stack.unwrap_or(false),
+		}),
+		Some("callTracer") => Ok(TraceType::CallTree),
+		Some(tracer) => Err(internal_err(format!("tracer {} is not supported", tracer))),
+	}
+}
+
+fn trace_result(trace: TransactionTrace) -> TraceResult {
+	match trace {
+		TransactionTrace::Raw {
+			gas,
+			failed,
+			return_value,
+			struct_logs,
+		} => TraceResult::StructLogs(StructLogsTrace {
+			gas: gas.saturated_into(),
+			failed,
+			return_value: hex::encode(return_value),
+			struct_logs: struct_logs.into_iter().map(struct_log_entry).collect(),
+		}),
+		TransactionTrace::CallTree(root) => TraceResult::CallTracer(call_frame(root)),
+	}
+}
+
+fn struct_log_entry(log: StructLog) -> StructLogEntry {
+	StructLogEntry {
+		pc: log.pc,
+		op: opcode_name(log.op),
+		gas: log.gas,
+		gas_cost: log.gas_cost,
+		depth: log.depth,
+		stack: log.stack.map(|stack| {
+			stack
+				.iter()
+				.map(|item| U256::from_big_endian(item.as_bytes()))
+				.collect()
+		}),
+		memory: log
+			.memory
+			.map(|memory| memory.chunks(32).map(hex::encode).collect()),
+		storage: log.storage.map(|storage| {
+			storage
+				.into_iter()
+				.map(|(key, value)| (format!("{:x}", key), format!("{:x}", value)))
+				.collect()
+		}),
+	}
+}
+
+fn call_frame(trace: CallTrace) -> CallFrame {
+	let call_type = match trace.call_type {
+		CallType::Call => "CALL",
+		CallType::CallCode => "CALLCODE",
+		CallType::DelegateCall => "DELEGATECALL",
+		CallType::StaticCall => "STATICCALL",
+		CallType::Create => "CREATE",
+		CallType::Create2 => "CREATE2",
+		CallType::SelfDestruct => "SELFDESTRUCT",
+	};
+	let value = match trace.call_type {
+		CallType::DelegateCall | CallType::StaticCall => None,
+		_ => Some(trace.value),
+	};
+	let (error, revert_reason) = match &trace.exit_reason {
+		Some(ExitReason::Revert(_)) => (
+			Some("execution reverted".to_string()),
+			crate::decode_revert_reason(&trace.output),
+		),
+		Some(ExitReason::Error(ExitError::OutOfGas)) => (Some("out of gas".to_string()), None),
+		Some(ExitReason::Error(err)) => (Some(format!("evm error: {:?}", err)), None),
+		Some(ExitReason::Fatal(err)) => (Some(format!("evm fatal: {:?}", err)), None),
+		Some(ExitReason::Succeed(_)) | None => (None, None),
+	};
+	let output = match trace.call_type {
+		CallType::SelfDestruct => None,
+		_ => Some(Bytes(trace.output)),
+	};
+
+	CallFrame {
+		call_type: call_type.to_string(),
+		from: trace.from,
+		to: trace.to,
+		value,
+		gas: trace.gas,
+		gas_used: trace.gas_used,
+		input: Bytes(trace.input),
+		output,
+		error,
+		revert_reason,
+		calls: trace.calls.into_iter().map(call_frame).collect(),
+	}
+}
+
+fn opcode_name(opcode: u8) -> String {
+	let name = match opcode {
+		0x00 => "STOP",
+		0x01 => "ADD",
+		0x02 => "MUL",
+		0x03 => "SUB",
+		0x04 => "DIV",
+		0x05 => "SDIV",
+		0x06 => "MOD",
+		0x07 => "SMOD",
+		0x08 => "ADDMOD",
+		0x09 => "MULMOD",
+		0x0a => "EXP",
+		0x0b => "SIGNEXTEND",
+		0x10 => "LT",
+		0x11 => "GT",
+		0x12 => "SLT",
+		0x13 => "SGT",
+		0x14 => "EQ",
+		0x15 => "ISZERO",
+		0x16 => "AND",
+		0x17 => "OR",
+		0x18 => "XOR",
+		0x19 => "NOT",
+		0x1a => "BYTE",
+		0x1b => "SHL",
+		0x1c => "SHR",
+		0x1d => "SAR",
+		0x20 => "SHA3",
+		0x30 => "ADDRESS",
+		0x31 => "BALANCE",
+		0x32 => "ORIGIN",
+		0x33 => "CALLER",
+		0x34 => "CALLVALUE",
+		0x35 => "CALLDATALOAD",
+		0x36 => "CALLDATASIZE",
+		0x37 => "CALLDATACOPY",
+		0x38 => "CODESIZE",
+		0x39 => "CODECOPY",
+		0x3a => "GASPRICE",
+		0x3b => "EXTCODESIZE",
+		0x3c => "EXTCODECOPY",
+		0x3d => "RETURNDATASIZE",
+		0x3e => "RETURNDATACOPY",
+		0x3f => "EXTCODEHASH",
+		0x40 => "BLOCKHASH",
+		0x41 => "COINBASE",
+		0x42 => "TIMESTAMP",
+		0x43 => "NUMBER",
+		0x44 => "DIFFICULTY",
+		0x45 => "GASLIMIT",
+		0x46 => "CHAINID",
+		0x47 => "SELFBALANCE",
+		0x48 => "BASEFEE",
+		0x50 => "POP",
+		0x51 => "MLOAD",
+		0x52 => "MSTORE",
+		0x53 => "MSTORE8",
+		0x54 => "SLOAD",
+		0x55 => "SSTORE",
+		0x56 => "JUMP",
+		0x57 => "JUMPI",
+		0x58 => "PC",
+		0x59 => "MSIZE",
+		0x5a => "GAS",
+		0x5b => "JUMPDEST",
+		0x5c => "TLOAD",
+		0x5d => "TSTORE",
+		0x5e => "MCOPY",
+		0x5f => "PUSH0",
+		0x60..=0x7f => return format!("PUSH{}", opcode - 0x5f),
+		0x80..=0x8f => return format!("DUP{}", opcode - 0x7f),
+		0x90..=0x9f => return format!("SWAP{}", opcode - 0x8f),
+		0xa0..=0xa4 => return format!("LOG{}", opcode - 0xa0),
+		0xf0 => "CREATE",
+		0xf1 => "CALL",
+		0xf2 => "CALLCODE",
+		0xf3 => "RETURN",
+		0xf4 => "DELEGATECALL",
+		0xf5 => "CREATE2",
+		0xfa => "STATICCALL",
+		0xfd => "REVERT",
+		0xfe => "INVALID",
+		0xff => "SELFDESTRUCT",
+		_ => return format!("opcode 0x{:x} not defined", opcode),
+	};
+	name.to_string()
+}
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! EIP-712 hashing of typed structured data.
+
+use ethereum_types::{H256, U256};
+use serde_json::Value;
+// Substrate
+use sp_core::hashing::keccak_256;
+// Frontier
+use fc_rpc_core::types::{TypedData, TypedDataField};
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// Hash of typed structured data signed by `eth_signTypedData_v4`.
+pub(crate) fn typed_data_hash(typed_data: &TypedData) -> Result<H256, String> {
+	let encoder = Encoder {
+		types: &typed_data.types,
+	};
+
+	let mut message = vec![0x19, 0x01];
+	message.extend_from_slice(&encoder.hash_struct(DOMAIN_TYPE, &typed_data.domain)?[..]);
+	if typed_data.primary_type != DOMAIN_TYPE {
+		message.extend_from_slice(
+			&encoder.hash_struct(&typed_data.primary_type, &typed_data.message)?[..],
+		);
+	}
+	Ok(H256::from(keccak_256(&message)))
+}
+
+struct Encoder<'a> {
+	types: &'a std::collections::BTreeMap<String, Vec<TypedDataField>>,
+}
+
+impl<'a> Encoder<'a> {
+	fn fields(&self, struct_type: &str) -> Result<&'a [TypedDataField], String> {
+		self.types
+			.get(struct_type)
+			.map(Vec::as_slice)
+			.ok_or_else(|| format!("unknown type {}", struct_type))
+	}
+
+	/// `encodeType` of a struct: its own signature followed by the signatures of
+	/// the struct types it references, sorted by name.
+	fn encode_type(&self, struct_type: &str) -> Result<String, String> {
+		let mut dependencies = Vec::new();
+		self.dependencies(struct_type, &mut dependencies)?;
+		dependencies.retain(|dependency| dependency != struct_type);
+		dependencies.sort();
+
+		let mut encoded = String::new();
+		for name in std::iter::once(struct_type).chain(dependencies.iter().map(String::as_str)) {
+			let members = self
+				.fields(name)?
+				.iter()
+				.map(|field| format!("{} {}", field.r#type, field.name))
+				.collect::<Vec<_>>();
+			encoded.push_str(&format!("{}({})", name, members.join(",")));
+		}
+		Ok(encoded)
+	}
+
+	fn dependencies(&self, struct_type: &str, found: &mut Vec<String>) -> Result<(), String> {
+		if found.iter().any(|name| name == struct_type) {
+			return Ok(());
+		}
+		found.push(struct_type.to_string());
+		for field in self.fields(struct_type)? {
+			let base_type = base_type(&field.r#type);
+			if self.types.contains_key(base_type) {
+				self.dependencies(base_type, found)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn hash_struct(&self, struct_type: &str, value: &Value) -> Result<H256, String> {
+		let mut encoded = keccak_256(self.encode_type(struct_type)?.as_bytes()).to_vec();
+		for field in self.fields(struct_type)? {
+			let member = value.get(&field.name).unwrap_or(&Value::Null);
+			encoded.extend_from_slice(
+				&self
+					.encode_value(&field.r#type, member)
+					.map_err(|e| format!("{}: {}", field.name, e))?[..],
+			);
+		}
+		Ok(H256::from(keccak_256(&encoded)))
+	}
+
+	/// `encodeData` of a single member, always 32 bytes long.
+	fn encode_value(&self, value_type: &str, value: &Value) -> Result<H256, String> {
+		if let Some(item_type) = array_item_type(value_type) {
+			let items = match value {
+				Value::Array(items) => items.as_slice(),
+				Value::Null => &[],
+				_ => return Err(format!("expected an array of {}", item_type)),
+			};
+			let mut encoded = Vec::with_capacity(items.len() * 32);
+			for item in items {
+				encoded.extend_from_slice(&self.encode_value(item_type, item)?[..]);
+			}
+			return Ok(H256::from(keccak_256(&encoded)));
+		}
+
+		if self.types.contains_key(value_type) {
+			// Missing structs are encoded as zero, as done by the reference implementation.
+			return match value {
+				Value::Null => Ok(H256::zero()),
+				value => self.hash_struct(value_type, value),
+			};
+		}
+
+		match value_type {
+			"string" => match value {
+				Value::String(string) => Ok(H256::from(keccak_256(string.as_bytes()))),
+				_ => Err("expected a string".into()),
+			},
+			"bytes" => Ok(H256::from(keccak_256(&hex_bytes(value)?))),
+			"bool" => match value {
+				Value::Bool(flag) => Ok(H256::from_low_u64_be(*flag as u64)),
+				_ => Err("expected a boolean".into()),
+			},
+			"address" => {
+				let bytes = hex_bytes(value)?;
+				if bytes.len() != 20 {
+					return Err("expected a 20 bytes address".into());
+				}
+				let mut word = H256::zero();
+				word[12..].copy_from_slice(&bytes);
+				Ok(word)
+			}
+			_ => {
+				if let Some(size) = value_type.strip_prefix("bytes") {
+					let size = parse_size(size, 1, 32)?;
+					let bytes = hex_bytes(value)?;
+					if bytes.len() > size {
+						return Err(format!("expected at most {} bytes", size));
+					}
+					let mut word = H256::zero();
+					word[..bytes.len()].copy_from_slice(&bytes);
+					Ok(word)
+				} else if let Some(bits) = value_type.strip_prefix("uint") {
+					let bits = parse_size(bits, 8, 256)?;
+					let (negative, number) = parse_integer(value)?;
+					if negative {
+						return Err("expected an unsigned integer".into());
+					}
+					if number.bits() > bits {
+						return Err(format!("integer out of range for {}", value_type));
+					}
+					Ok(word_from_u256(number))
+				} else if let Some(bits) = value_type.strip_prefix("int") {
+					let bits = parse_size(bits, 8, 256)?;
+					let (negative, number) = parse_integer(value)?;
+					// The range of `intN` is `[-2^(N-1), 2^(N-1) - 1]`.
+					let bound = U256::one() << (bits - 1);
+					if number > bound || (number == bound && !negative) {
+						return Err(format!("integer out of range for {}", value_type));
+					}
+					let number = if negative {
+						(!number).overflowing_add(U256::one()).0
+					} else {
+						number
+					};
+					Ok(word_from_u256(number))
+				} else {
+					Err(format!("unknown type {}", value_type))
+				}
+			}
+		}
+	}
+}
+
+/// Type without its array suffixes.
+fn base_type(value_type: &str) -> &str {
+	value_type.split('[').next().unwrap_or(value_type)
+}
+
+/// Item type of an array type, `None` if the type is not an array.
+fn array_item_type(value_type: &str) -> Option<&str> {
+	if !value_type.ends_with(']') {
+		return None;
+	}
+	value_type.rfind('[').map(|bracket| &value_type[..bracket])
+}
+
+/// Parse the size of `bytesN`, `intN` and `uintN` types, an empty size standing for
+/// the maximum.
+fn parse_size(size: &str, min: usize, max: usize) -> Result<usize, String> {
+	if size.is_empty() {
+		return Ok(max);
+	}
+	match size.parse::<usize>() {
+		Ok(size) if size >= min && size <= max => Ok(size),
+		_ => Err(format!("invalid type size {}", size)),
+	}
+}
+
+fn hex_bytes(value: &Value) -> Result<Vec<u8>, String> {
+	let string = value
+		.as_str()
+		.ok_or_else(|| "expected a hex string".to_string())?;
+	hex::decode(string.strip_prefix("0x").unwrap_or(string)).map_err(|e| e.to_string())
+}
+
+/// Parse an integer given as a JSON number, or a decimal or hex string, returning
+/// its sign and absolute value.
+fn parse_integer(value: &Value) -> Result<(bool, U256), String> {
+	let string = match value {
+		Value::Number(number) => number.to_string(),
+		Value::String(string) => string.clone(),
+		_ => return Err("expected an integer".into()),
+	};
+	let (negative, digits) = match string.strip_prefix('-') {
+		Some(digits) => (true, digits),
+		None => (false, string.as_str()),
+	};
+	let number = match digits.strip_prefix("0x") {
+		Some(hex) => U256::from_str_radix(hex, 16).ok(),
+		None => U256::from_dec_str(digits).ok(),
+	};
+	number
+		.map(|number| (negative, number))
+		.ok_or_else(|| format!("invalid integer {}", string))
+}
+
+fn word_from_u256(number: U256) -> H256 {
+	let mut word = H256::zero();
+	number.to_big_endian(&mut word[..]);
+	word
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn mail() -> TypedData {
+		serde_json::from_value(serde_json::json!({
+			"types": {
+				"EIP712Domain": [
+					{ "name": "name", "type": "string" },
+					{ "name": "version", "type": "string" },
+					{ "name": "chainId", "type": "uint256" },
+					{ "name": "verifyingContract", "type": "address" }
+				],
+				"Person": [
+					{ "name": "name", "type": "string" },
+					{ "name": "wallet", "type": "address" }
+				],
+				"Mail": [
+					{ "name": "from", "type": "Person" },
+					{ "name": "to", "type": "Person" },
+					{ "name": "contents", "type": "string" }
+				]
+			},
+			"primaryType": "Mail",
+			"domain": {
+				"name": "Ether Mail",
+				"version": "1",
+				"chainId": 1,
+				"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+			},
+			"message": {
+				"from": {
+					"name": "Cow",
+					"wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+				},
+				"to": {
+					"name": "Bob",
+					"wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+				},
+				"contents": "Hello, Bob!"
+			}
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn encode_type_appends_sorted_dependencies() {
+		let typed_data = mail();
+		let encoder = Encoder {
+			types: &typed_data.types,
+		};
+		assert_eq!(
+			encoder.encode_type("Mail").unwrap(),
+			"Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+		);
+	}
+
+	#[test]
+	fn typed_data_hash_matches_eip712_example() {
+		assert_eq!(
+			typed_data_hash(&mail()).unwrap(),
+			"0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+				.parse::<H256>()
+				.unwrap()
+		);
+	}
+
+	#[test]
+	fn encode_value_handles_arrays_and_signed_integers() {
+		let typed_data = mail();
+		let encoder = Encoder {
+			types: &typed_data.types,
+		};
+		assert_eq!(
+			encoder
+				.encode_value("int8", &serde_json::json!(-1))
+				.unwrap(),
+			H256::repeat_byte(0xff)
+		);
+		assert_eq!(
+			encoder
+				.encode_value("uint256[]", &serde_json::json!([1, "0x02"]))
+				.unwrap(),
+			H256::from(keccak_256(
+				&[word_from_u256(1.into()), word_from_u256(2.into())].concat()
+			))
+		);
+		assert!(encoder
+			.encode_value("uint8", &serde_json::json!("-1"))
+			.is_err());
+	}
+
+	#[test]
+	fn encode_value_rejects_out_of_range_integers() {
+		let typed_data = mail();
+		let encoder = Encoder {
+			types: &typed_data.types,
+		};
+		let encode = |value_type, value| encoder.encode_value(value_type, &value);
+
+		assert_eq!(
+			encode("uint8", serde_json::json!(255)).unwrap(),
+			word_from_u256(255.into())
+		);
+		assert!(encode("uint8", serde_json::json!(256)).is_err());
+		assert!(encode("uint32", serde_json::json!("0x100000000")).is_err());
+
+		assert_eq!(
+			encode("int8", serde_json::json!(127)).unwrap(),
+			word_from_u256(127.into())
+		);
+		assert!(encode("int8", serde_json::json!(128)).is_err());
+		assert_eq!(
+			encode("int8", serde_json::json!(-128)).unwrap(),
+			word_from_u256(U256::MAX - 127)
+		);
+		assert!(encode("int8", serde_json::json!(-129)).is_err());
+
+		let max = serde_json::json!(U256::MAX.to_string());
+		assert!(encode("uint256", max.clone()).is_ok());
+		assert!(encode("int256", max).is_err());
+	}
+}
@@ -100,6 +100,7 @@ where
 			client.as_ref(),
 			backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		)? {
 			Some(id) => id,
 			None => return Ok(None),
@@ -172,6 +173,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		)? {
 			Some(id) => id,
 			None => return Ok(None),
@@ -19,10 +19,12 @@
 use lru::LruCache;
 use scale_codec::Encode;
 
+use crate::metrics::CacheMetrics;
+
 pub struct LRUCacheByteLimited<K, V> {
 	cache: LruCache<K, V>,
 	max_size: u64,
-	metrics: Option<LRUCacheByteLimitedMetrics>,
+	metrics: Option<CacheMetrics>,
 	size: u64,
 }
 
@@ -33,7 +35,7 @@ impl<K: Eq + core::hash::Hash, V: Encode> LRUCacheByteLimited<K, V> {
 		prometheus_registry: Option<prometheus_endpoint::Registry>,
 	) -> Self {
 		let metrics = match prometheus_registry {
-			Some(registry) => match LRUCacheByteLimitedMetrics::register(cache_name, &registry) {
+			Some(registry) => match CacheMetrics::register(cache_name, &registry) {
 				Ok(metrics) => Some(metrics),
 				Err(e) => {
 					log::error!(target: "eth-cache", "Failed to register metrics: {:?}", e);
@@ -87,43 +89,6 @@ impl<K: Eq + core::hash::Hash, V: Encode> LRUCacheByteLimited<K, V> {
 	}
 }
 
-struct LRUCacheByteLimitedMetrics {
-	hits: prometheus::IntCounter,
-	miss: prometheus::IntCounter,
-	size: prometheus_endpoint::Gauge<prometheus_endpoint::U64>,
-}
-
-impl LRUCacheByteLimitedMetrics {
-	pub(crate) fn register(
-		cache_name: &'static str,
-		registry: &prometheus_endpoint::Registry,
-	) -> std::result::Result<Self, prometheus_endpoint::PrometheusError> {
-		Ok(Self {
-			hits: prometheus_endpoint::register(
-				prometheus::IntCounter::new(
-					format!("frontier_eth_{}_hits", cache_name),
-					format!("Hits of eth {} cache.", cache_name),
-				)?,
-				registry,
-			)?,
-			miss: prometheus_endpoint::register(
-				prometheus::IntCounter::new(
-					format!("frontier_eth_{}_miss", cache_name),
-					format!("Misses of eth {} cache.", cache_name),
-				)?,
-				registry,
-			)?,
-			size: prometheus_endpoint::register(
-				prometheus_endpoint::Gauge::new(
-					format!("frontier_eth_{}_size", cache_name),
-					format!("Size of eth {} data cache.", cache_name),
-				)?,
-				registry,
-			)?,
-		})
-	}
-}
-
 #[cfg(test)]
 mod tests {
 	use super::*;
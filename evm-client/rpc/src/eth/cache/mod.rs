@@ -48,6 +48,7 @@ use fp_storage::EthereumStorageSchema;
 use self::lru_cache::LRUCacheByteLimited;
 use crate::{
 	frontier_backend_client,
+	metrics::EthMetrics,
 	overrides::{OverrideHandle, StorageOverride},
 };
 
@@ -75,6 +76,9 @@ enum EthBlockDataCacheMessage<B: BlockT> {
 	},
 }
 
+/// Maximum number of blocks checked per import when measuring the mapping sync lag.
+const MAPPING_SYNC_LAG_SCAN: u64 = 10_000;
+
 /// Manage LRU caches for block data and their transaction statuses.
 /// These are large and take a lot of time to fetch from the database.
 /// Storing them in an LRU cache will allow to reduce database accesses
@@ -459,4 +463,77 @@ where
 			}
 		}
 	}
+
+	pub async fn metrics_task(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		filter_pool: Option<FilterPool>,
+		metrics: Arc<EthMetrics>,
+	) {
+		let mut notification_st = client.import_notification_stream();
+		// The scan resumes where it stopped before a restart. Blocks up to the catch-up
+		// checkpoint are synced, so it never starts below it.
+		let meta = backend.meta();
+		let mut lowest_unsynced = std::cmp::max(
+			meta.lowest_unsynced().ok().flatten().unwrap_or(0),
+			match meta.catch_up_checkpoint() {
+				Ok(Some(checkpoint)) => checkpoint + 1,
+				_ => 0,
+			},
+		);
+		let mut stored = lowest_unsynced;
+
+		while let Some(notification) = notification_st.next().await {
+			// Filters are also dropped by `filter_pool_task`, refresh the count on each import.
+			if let Some(Ok(filter_pool)) = filter_pool.as_ref().map(|pool| pool.lock()) {
+				metrics.active_filters.set(filter_pool.len() as u64);
+			}
+			// Blocks enacted by a reorg may not be synced yet.
+			if let Some(tree_route) = notification.tree_route {
+				let common_number: u64 = tree_route.common_block().number.unique_saturated_into();
+				lowest_unsynced = lowest_unsynced.min(common_number + 1);
+			}
+			let best_number: u64 = client.info().best_number.unique_saturated_into();
+			lowest_unsynced = Self::lowest_unsynced(
+				client.as_ref(),
+				backend.as_ref(),
+				lowest_unsynced,
+				best_number,
+			);
+			// Stored so that a restart does not scan the whole chain again.
+			if lowest_unsynced != stored && meta.write_lowest_unsynced(lowest_unsynced).is_ok() {
+				stored = lowest_unsynced;
+			}
+			metrics
+				.mapping_sync_lag
+				.set((best_number + 1).saturating_sub(lowest_unsynced));
+		}
+	}
+
+	/// Lowest canonical block not synced in the mapping database, scanning from `from`.
+	///
+	/// The mapping sync worker follows the imported blocks while catching up on older ones,
+	/// so the synced blocks are not a contiguous range: the scan stops at the first gap. At
+	/// most `MAPPING_SYNC_LAG_SCAN` blocks are checked per import to bound the work done on
+	/// a long chain.
+	fn lowest_unsynced(
+		client: &C,
+		backend: &fc_db::Backend<B>,
+		from: u64,
+		best_number: u64,
+	) -> u64 {
+		let is_synced = |number: u64| -> Option<bool> {
+			let hash = client.hash(number.unique_saturated_into()).ok()??;
+			backend.mapping().is_synced(&hash).ok()
+		};
+
+		let mut number = from;
+		while number <= best_number
+			&& number - from < MAPPING_SYNC_LAG_SCAN
+			&& is_synced(number) == Some(true)
+		{
+			number += 1;
+		}
+		number
+	}
 }
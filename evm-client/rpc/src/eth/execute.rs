@@ -16,9 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::{cell::Cell, collections::BTreeMap, sync::Arc};
 
-use ethereum_types::{H256, U256};
+use ethereum_types::{H160, H256, U256};
 use evm::{ExitError, ExitReason};
 use jsonrpsee::core::RpcResult as Result;
 // Substrate
@@ -31,7 +31,7 @@ use sp_blockchain::{BlockStatus, HeaderBackend};
 use sp_runtime::{
 	generic::BlockId,
 	traits::{BlakeTwo256, Block as BlockT},
-	SaturatedConversion,
+	Permill,
 };
 // Frontier
 use fc_rpc_core::types::*;
@@ -40,11 +40,24 @@ use fp_rpc::EthereumRuntimeRPCApi;
 use crate::{
 	eth::{pending_runtime_api, Eth},
 	frontier_backend_client, internal_err,
+	metrics::EthMetrics,
 };
 
 /// Default JSONRPC error code return by geth
 pub const JSON_RPC_ERROR_DEFAULT: i32 = -32000;
 
+/// JSONRPC error code of a reverted execution, as returned by geth
+pub const JSON_RPC_ERROR_REVERT: i32 = 3;
+
+/// Default tolerance of the `eth_estimateGas` binary search, 1.5% as in geth
+pub const DEFAULT_ESTIMATE_GAS_TOLERANCE: Permill = Permill::from_parts(15_000);
+
+/// Lower bound of the gas estimate
+const MIN_GAS_PER_TX: U256 = U256([21_000, 0, 0, 0]);
+
+/// Gas made available to the callee of a call transferring value
+const CALL_STIPEND: U256 = U256([2_300, 0, 0, 0]);
+
 /// Allow to adapt a request for `estimate_gas`.
 /// Can be used to estimate gas of some contracts using a different function
 /// in the case the normal gas estimation doesn't work.
@@ -63,6 +76,20 @@ impl EstimateGasAdapter for () {
 	}
 }
 
+/// Reports the number of executions of an estimation once it is done.
+struct ProbeCounter<'a> {
+	count: Cell<u64>,
+	metrics: Option<&'a EthMetrics>,
+}
+
+impl Drop for ProbeCounter<'_> {
+	fn drop(&mut self) {
+		if let Some(metrics) = self.metrics {
+			metrics.estimate_gas_probes.observe(self.count.get() as f64);
+		}
+	}
+}
+
 impl<B, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE, H, A, EGA>
 where
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
@@ -74,7 +101,12 @@ where
 	A: ChainApi<Block = B> + 'static,
 	EGA: EstimateGasAdapter,
 {
-	pub fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes> {
+	pub fn call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<Bytes> {
 		let CallRequest {
 			from,
 			to,
@@ -102,6 +134,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			number,
+			self.safe_depth,
 		)? {
 			Some(id) => (id, self.client.runtime_api()),
 			None => {
@@ -123,6 +156,13 @@ where
 				return Err(internal_err("failed to retrieve Runtime Api version"));
 			};
 
+		let state_overrides = runtime_state_overrides(state_overrides)?;
+		if state_overrides.is_some() && api_version < 6 {
+			return Err(internal_err(
+				"state overrides are not supported by the runtime",
+			));
+		}
+
 		let block = if api_version > 1 {
 			api.current_block(&id)
 				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -201,9 +241,36 @@ where
 
 					error_on_execution_failure(&info.exit_reason, &info.value)?;
 					Ok(Bytes(info.value))
-				} else if api_version == 4 {
+				} else if api_version >= 4 && api_version < 6 {
 					// Post-london + access list support
 					let access_list = access_list.unwrap_or_default();
+					#[allow(deprecated)]
+					let info = api.call_before_version_6(
+						&id,
+						from.unwrap_or_default(),
+						to,
+						data,
+						value.unwrap_or_default(),
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						false,
+						Some(
+							access_list
+								.into_iter()
+								.map(|item| (item.address, item.storage_keys))
+								.collect(),
+						),
+					)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+					error_on_execution_failure(&info.exit_reason, &info.value)?;
+					Ok(Bytes(info.value))
+				} else if api_version >= 6 {
+					// Post-london + access list + state overrides support
+					let access_list = access_list.unwrap_or_default();
 					let info = api
 						.call(
 							&id,
@@ -222,6 +289,7 @@ where
 									.map(|item| (item.address, item.storage_keys))
 									.collect(),
 							),
+							state_overrides,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
@@ -278,9 +346,39 @@ where
 						.account_code_at(&id, info.value)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
 					Ok(Bytes(code))
-				} else if api_version == 4 {
+				} else if api_version >= 4 && api_version < 6 {
 					// Post-london + access list support
 					let access_list = access_list.unwrap_or_default();
+					#[allow(deprecated)]
+					let info = api.create_before_version_6(
+						&id,
+						from.unwrap_or_default(),
+						data,
+						value.unwrap_or_default(),
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						false,
+						Some(
+							access_list
+								.into_iter()
+								.map(|item| (item.address, item.storage_keys))
+								.collect(),
+						),
+					)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+					error_on_execution_failure(&info.exit_reason, &[])?;
+
+					let code = api
+						.account_code_at(&id, info.value)
+						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+					Ok(Bytes(code))
+				} else if api_version >= 6 {
+					// Post-london + access list + state overrides support
+					let access_list = access_list.unwrap_or_default();
 					let info = api
 						.create(
 							&id,
@@ -298,6 +396,7 @@ where
 									.map(|item| (item.address, item.storage_keys))
 									.collect(),
 							),
+							state_overrides,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
@@ -315,19 +414,163 @@ where
 		}
 	}
 
-	pub async fn estimate_gas(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<U256> {
+	pub fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> Result<AccessListResult> {
+		/// Maximum number of executions made while looking for a stable access list.
+		const MAX_ITERATIONS: usize = 10;
+
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			gas,
+			value,
+			data,
+			nonce,
+			access_list,
+			..
+		} = request;
+
+		let (max_fee_per_gas, max_priority_fee_per_gas) = {
+			let details = fee_details(gas_price, max_fee_per_gas, max_priority_fee_per_gas)?;
+			(details.max_fee_per_gas, details.max_priority_fee_per_gas)
+		};
+
+		let native_id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+			self.safe_depth,
+		)?;
+		// Runtime calls made through the same `ApiRef` share their storage changes, so a
+		// fresh one is used for each execution.
+		let runtime_api = || match native_id {
+			Some(id) => Ok((id, self.client.runtime_api())),
+			None => {
+				// Not mapped in the db, assume pending.
+				let id = BlockId::Hash(self.client.info().best_hash);
+				let api = pending_runtime_api(self.client.as_ref(), self.graph.as_ref())?;
+				Ok((id, api))
+			}
+		};
+
+		let (id, api) = runtime_api()?;
+		if let Ok(BlockStatus::Unknown) = self.client.status(id) {
+			return Err(crate::err(JSON_RPC_ERROR_DEFAULT, "header not found", None));
+		}
+
+		match api.api_version::<dyn EthereumRuntimeRPCApi<B>>(&id) {
+			Ok(Some(api_version)) if api_version >= 5 => (),
+			Ok(Some(_)) => {
+				return Err(internal_err(
+					"eth_createAccessList is not supported by the runtime",
+				))
+			}
+			_ => return Err(internal_err("failed to retrieve Runtime Api version")),
+		}
+
+		let block_gas_limit = api
+			.current_block(&id)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?
+			.header
+			.gas_limit;
+		let max_gas_limit = block_gas_limit * self.execute_gas_limit_multiplier;
+
+		// use given gas limit or query current block's limit
+		let gas_limit = match gas {
+			Some(amount) => {
+				if amount > max_gas_limit {
+					return Err(internal_err(format!(
+						"provided gas limit is too high (can be up to {}x the block gas limit)",
+						self.execute_gas_limit_multiplier
+					)));
+				}
+				amount
+			}
+			None => max_gas_limit,
+		};
+		drop(api);
+
+		let data = data.map(|d| d.0).unwrap_or_default();
+		let mut access_list: Vec<(H160, Vec<H256>)> = access_list
+			.unwrap_or_default()
+			.into_iter()
+			.map(|item| (item.address, item.storage_keys))
+			.collect();
+
+		// Warming up an address or slot changes the gas available to the sub-calls, and thus
+		// possibly the code path taken, so execute again until the access list is stable.
+		let mut iterations = 0;
+		loop {
+			let (id, api) = runtime_api()?;
+			let (info, accessed) = api
+				.create_access_list(
+					&id,
+					from.unwrap_or_default(),
+					to,
+					data.clone(),
+					value.unwrap_or_default(),
+					gas_limit,
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					Some(access_list.clone()),
+				)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+			iterations += 1;
+			if accessed == access_list || iterations >= MAX_ITERATIONS {
+				let error = match error_on_execution_failure(&info.exit_reason, &info.value) {
+					Ok(()) => None,
+					Err(jsonrpsee::core::Error::Call(
+						jsonrpsee::types::error::CallError::Custom(err),
+					)) => Some(err.message().to_string()),
+					Err(err) => Some(err.to_string()),
+				};
+				return Ok(AccessListResult {
+					access_list: accessed
+						.into_iter()
+						.map(|(address, storage_keys)| ethereum::AccessListItem {
+							address,
+							storage_keys,
+						})
+						.collect(),
+					gas_used: info.used_gas,
+					error,
+				});
+			}
+			access_list = accessed;
+		}
+	}
+
+	pub async fn estimate_gas(
+		&self,
+		request: CallRequest,
+		_: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<U256> {
 		let client = Arc::clone(&self.client);
 		let block_data_cache = Arc::clone(&self.block_data_cache);
 
-		// Define the lower bound of estimate
-		const MIN_GAS_PER_TX: U256 = U256([21_000, 0, 0, 0]);
-
 		// Get best hash (TODO missing support for estimating gas historically)
 		let best_hash = client.info().best_hash;
 
 		// Adapt request for gas estimation.
 		let request = EGA::adapt_request(request);
 
+		let state_override = |address: H160| {
+			state_overrides
+				.as_ref()
+				.and_then(|state_overrides| state_overrides.get(&address))
+		};
+
 		// For simple transfer to simple account, return MIN_GAS_PER_TX directly
 		let is_simple_transfer = match &request.data {
 			None => true,
@@ -335,10 +578,13 @@ where
 		};
 		if is_simple_transfer {
 			if let Some(to) = request.to {
-				let to_code = client
-					.runtime_api()
-					.account_code_at(&BlockId::Hash(best_hash), to)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+				let to_code = match state_override(to).and_then(|o| o.code.as_ref()) {
+					Some(code) => code.0.clone(),
+					None => client
+						.runtime_api()
+						.account_code_at(&BlockId::Hash(best_hash), to)
+						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?,
+				};
 				if to_code.is_empty() {
 					return Ok(MIN_GAS_PER_TX);
 				}
@@ -397,10 +643,14 @@ where
 		if let Some(from) = request.from {
 			let gas_price = gas_price.unwrap_or_default();
 			if gas_price > U256::zero() {
-				let balance = api
-					.account_basic(&BlockId::Hash(best_hash), from)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-					.balance;
+				let balance = match state_override(from).and_then(|o| o.balance) {
+					Some(balance) => balance,
+					None => {
+						api.account_basic(&BlockId::Hash(best_hash), from)
+							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+							.balance
+					}
+				};
 				let mut available = balance;
 				if let Some(value) = request.value {
 					if value > available {
@@ -423,12 +673,18 @@ where
 			}
 		}
 
+		let state_overrides = runtime_state_overrides(state_overrides)?;
+		let has_state_overrides = state_overrides.is_some();
+
 		struct ExecutableResult {
 			data: Vec<u8>,
 			exit_reason: ExitReason,
 			used_gas: U256,
 		}
 
+		// The binary search needs the actual gas accounting of the execution.
+		let estimate_mode = false;
+
 		// Create a helper to check if a gas allowance results in an executable transaction.
 		//
 		// A new ApiRef instance needs to be used per execution to avoid the overlayed state to affect
@@ -442,7 +698,7 @@ where
 		// To solve that, and if we introduce historical gas estimation, we'd need to increase that default.
 		#[rustfmt::skip]
 			let executable = move |
-				request, gas_limit, api_version, api: sp_api::ApiRef<'_, C::Api>, estimate_mode
+				request, gas_limit, api_version, api: sp_api::ApiRef<'_, C::Api>
 			| -> Result<ExecutableResult> {
 				let CallRequest {
 					from,
@@ -495,9 +751,33 @@ where
 							)
 							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
-						} else {
+						} else if api_version < 6 {
 							// Post-london + access list support
 							let access_list = access_list.unwrap_or_default();
+							#[allow(deprecated)]
+							api.call_before_version_6(
+								&BlockId::Hash(best_hash),
+								from.unwrap_or_default(),
+								to,
+								data,
+								value.unwrap_or_default(),
+								gas_limit,
+								max_fee_per_gas,
+								max_priority_fee_per_gas,
+								nonce,
+								estimate_mode,
+								Some(
+									access_list
+										.into_iter()
+										.map(|item| (item.address, item.storage_keys))
+										.collect(),
+								),
+							)
+							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
+						} else {
+							// Post-london + access list + state overrides support
+							let access_list = access_list.unwrap_or_default();
 							api.call(
 								&BlockId::Hash(best_hash),
 								from.unwrap_or_default(),
@@ -515,6 +795,7 @@ where
 										.map(|item| (item.address, item.storage_keys))
 										.collect(),
 								),
+								state_overrides.clone(),
 							)
 							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
@@ -554,9 +835,32 @@ where
 							)
 							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
-						} else {
+						} else if api_version < 6 {
 							// Post-london + access list support
 							let access_list = access_list.unwrap_or_default();
+							#[allow(deprecated)]
+							api.create_before_version_6(
+								&BlockId::Hash(best_hash),
+								from.unwrap_or_default(),
+								data,
+								value.unwrap_or_default(),
+								gas_limit,
+								max_fee_per_gas,
+								max_priority_fee_per_gas,
+								nonce,
+								estimate_mode,
+								Some(
+									access_list
+										.into_iter()
+										.map(|item| (item.address, item.storage_keys))
+										.collect(),
+								),
+							)
+							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
+						} else {
+							// Post-london + access list + state overrides support
+							let access_list = access_list.unwrap_or_default();
 							api.create(
 								&BlockId::Hash(best_hash),
 								from.unwrap_or_default(),
@@ -573,6 +877,7 @@ where
 										.map(|item| (item.address, item.storage_keys))
 										.collect(),
 								),
+								state_overrides.clone(),
 							)
 							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 							.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
@@ -597,20 +902,29 @@ where
 			return Err(internal_err("failed to retrieve Runtime Api version"));
 		};
 
-		// Verify that the transaction succeed with highest capacity
+		if has_state_overrides && api_version < 6 {
+			return Err(internal_err(
+				"state overrides are not supported by the runtime",
+			));
+		}
+
+		// Count the executions needed by the estimation.
+		let probes = ProbeCounter {
+			count: Cell::new(0),
+			metrics: self.metrics.as_deref(),
+		};
+		let probe = |request: CallRequest, gas_limit: U256| {
+			probes.count.set(probes.count.get() + 1);
+			executable(request, gas_limit, api_version, client.runtime_api())
+		};
+
+		// Verify that the transaction succeeds with the highest capacity.
 		let cap = highest;
-		let estimate_mode = !cfg!(feature = "rpc-binary-search-estimate");
 		let ExecutableResult {
 			data,
 			exit_reason,
 			used_gas,
-		} = executable(
-			request.clone(),
-			highest,
-			api_version,
-			client.runtime_api(),
-			estimate_mode,
-		)?;
+		} = probe(request.clone(), highest)?;
 		match exit_reason {
 			ExitReason::Succeed(_) => (),
 			ExitReason::Error(ExitError::OutOfGas) => {
@@ -631,13 +945,7 @@ where
 						data,
 						exit_reason,
 						used_gas: _,
-					} = executable(
-						request.clone(),
-						max_gas_limit,
-						api_version,
-						client.runtime_api(),
-						estimate_mode,
-					)?;
+					} = probe(request.clone(), max_gas_limit)?;
 					match exit_reason {
 						ExitReason::Succeed(_) => {
 							return Err(internal_err(format!(
@@ -656,57 +964,72 @@ where
 			other => error_on_execution_failure(&other, &data)?,
 		};
 
-		#[cfg(not(feature = "rpc-binary-search-estimate"))]
-		{
-			Ok(used_gas)
-		}
-		#[cfg(feature = "rpc-binary-search-estimate")]
-		{
-			// On binary search, evm estimate mode is disabled
-			let estimate_mode = false;
-			// Define the lower bound of the binary search
-			let mut lowest = MIN_GAS_PER_TX;
+		search_gas_limit(
+			used_gas,
+			highest,
+			self.estimate_gas_tolerance,
+			|gas_limit| {
+				let ExecutableResult {
+					data, exit_reason, ..
+				} = probe(request.clone(), gas_limit)?;
+				Ok((exit_reason, data))
+			},
+		)
+	}
+}
 
-			// Start close to the used gas for faster binary search
-			let mut mid = std::cmp::min(used_gas * 3, (highest + lowest) / 2);
+/// Hones in on the lowest gas limit an execution succeeds with, from the gas it used and a gas
+/// limit it is known to succeed with, until the bounds are within `tolerance` of the estimate.
+/// `execute` runs the execution with a given gas limit.
+fn search_gas_limit(
+	used_gas: U256,
+	mut highest: U256,
+	tolerance: Permill,
+	mut execute: impl FnMut(U256) -> Result<(ExitReason, Vec<u8>)>,
+) -> Result<U256> {
+	// The execution cannot succeed with less than the gas it used.
+	let mut lowest = std::cmp::max(used_gas, MIN_GAS_PER_TX).saturating_sub(U256::one());
 
-			// Execute the binary search and hone in on an executable gas limit.
-			let mut previous_highest = highest;
-			while (highest - lowest) > U256::one() {
-				let ExecutableResult {
-					data,
-					exit_reason,
-					used_gas: _,
-				} = executable(
-					request.clone(),
-					mid,
-					api_version,
-					client.runtime_api(),
-					estimate_mode,
-				)?;
-				match exit_reason {
-					ExitReason::Succeed(_) => {
-						highest = mid;
-						// If the variation in the estimate is less than 10%,
-						// then the estimate is considered sufficiently accurate.
-						if (previous_highest - highest) * 10 / previous_highest < U256::one() {
-							return Ok(highest);
-						}
-						previous_highest = highest;
-					}
-					ExitReason::Revert(_)
-					| ExitReason::Error(ExitError::OutOfGas)
-					| ExitReason::Error(ExitError::InvalidCode(_)) => {
-						lowest = mid;
-					}
-					other => error_on_execution_failure(&other, &data)?,
-				}
-				mid = (highest + lowest) / 2;
-			}
+	// Most transactions only need their used gas, plus the stipend and the 1/64th of the
+	// gas retained at each call level, try that before bisecting.
+	let optimistic = used_gas
+		.saturating_add(CALL_STIPEND)
+		.saturating_mul(64.into())
+		/ 63;
+	if optimistic < highest {
+		let (exit_reason, data) = execute(optimistic)?;
+		match exit_reason {
+			ExitReason::Succeed(_) => highest = optimistic,
+			ExitReason::Revert(_)
+			| ExitReason::Error(ExitError::OutOfGas)
+			| ExitReason::Error(ExitError::InvalidCode(_)) => lowest = optimistic,
+			other => error_on_execution_failure(&other, &data)?,
+		}
+	}
 
-			Ok(highest)
+	// Execute the binary search and hone in on an executable gas limit.
+	while highest - lowest > U256::one() {
+		// Stop once the gap between the bounds is within the tolerance.
+		let margin = U256::from(tolerance.deconstruct()) * highest
+			/ U256::from(Permill::one().deconstruct());
+		if highest - lowest <= margin {
+			break;
+		}
+
+		// Gas limits far above the used gas are rarely needed, keep the probes close
+		// to the lower bound.
+		let mid = std::cmp::min((highest + lowest) / 2, lowest.saturating_mul(2.into()));
+		let (exit_reason, data) = execute(mid)?;
+		match exit_reason {
+			ExitReason::Succeed(_) => highest = mid,
+			ExitReason::Revert(_)
+			| ExitReason::Error(ExitError::OutOfGas)
+			| ExitReason::Error(ExitError::InvalidCode(_)) => lowest = mid,
+			other => error_on_execution_failure(&other, &data)?,
 		}
 	}
+
+	Ok(highest)
 }
 
 pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> Result<()> {
@@ -723,25 +1046,11 @@ pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> Result<()
 			))
 		}
 		ExitReason::Revert(_) => {
-			const LEN_START: usize = 36;
-			const MESSAGE_START: usize = 68;
-
-			let mut message = "VM Exception while processing transaction: revert".to_string();
-			// A minimum size of error function selector (4) + offset (32) + string length (32)
-			// should contain a utf-8 encoded revert reason.
-			if data.len() > MESSAGE_START {
-				let message_len =
-					U256::from(&data[LEN_START..MESSAGE_START]).saturated_into::<usize>();
-				let message_end = MESSAGE_START.saturating_add(message_len);
-
-				if data.len() >= message_end {
-					let body: &[u8] = &data[MESSAGE_START..message_end];
-					if let Ok(reason) = std::str::from_utf8(body) {
-						message = format!("{} {}", message, reason);
-					}
-				}
-			}
-			Err(crate::internal_err_with_data(message, data))
+			let message = match crate::decode_revert_reason(data) {
+				Some(reason) => format!("execution reverted: {}", reason),
+				None => "execution reverted".to_string(),
+			};
+			Err(crate::err(JSON_RPC_ERROR_REVERT, message, Some(data)))
 		}
 		ExitReason::Fatal(e) => Err(crate::internal_err_with_data(
 			format!("evm fatal: {:?}", e),
@@ -801,3 +1110,137 @@ fn fee_details(
 		}
 	}
 }
+
+/// Converts the `eth_call` state overrides into their runtime representation.
+fn runtime_state_overrides(
+	state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+) -> Result<Option<Vec<(H160, fp_evm::StateOverride)>>> {
+	state_overrides
+		.map(|state_overrides| {
+			state_overrides
+				.into_iter()
+				.map(|(address, state_override)| {
+					if state_override.state.is_some() && state_override.state_diff.is_some() {
+						return Err(internal_err(format!(
+							"account {:?} has both 'state' and 'stateDiff'",
+							address
+						)));
+					}
+					Ok((
+						address,
+						fp_evm::StateOverride {
+							balance: state_override.balance,
+							nonce: state_override.nonce,
+							code: state_override.code.map(|code| code.0),
+							state: state_override
+								.state
+								.map(|state| state.into_iter().collect()),
+							state_diff: state_override
+								.state_diff
+								.map(|state_diff| state_diff.into_iter().collect()),
+						},
+					))
+				})
+				.collect()
+		})
+		.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use evm::{ExitFatal, ExitRevert, ExitSucceed};
+
+	use super::*;
+
+	/// Runs the search over an execution which runs out of gas below `needed`, returning the
+	/// estimate and the gas limits probed.
+	fn search(used_gas: u64, needed: u64, highest: u64, tolerance: Permill) -> (U256, Vec<U256>) {
+		let mut probes = Vec::new();
+		let estimate = search_gas_limit(used_gas.into(), highest.into(), tolerance, |gas_limit| {
+			probes.push(gas_limit);
+			Ok(if gas_limit >= U256::from(needed) {
+				(ExitReason::Succeed(ExitSucceed::Returned), Vec::new())
+			} else {
+				(ExitReason::Error(ExitError::OutOfGas), Vec::new())
+			})
+		})
+		.unwrap();
+		(estimate, probes)
+	}
+
+	#[test]
+	fn search_without_tolerance_finds_exact_gas_limit() {
+		let (estimate, _) = search(50_000, 60_000, 10_000_000, Permill::zero());
+		assert_eq!(estimate, U256::from(60_000));
+	}
+
+	#[test]
+	fn search_stops_within_tolerance() {
+		let (estimate, _) = search(50_000, 60_000, 10_000_000, DEFAULT_ESTIMATE_GAS_TOLERANCE);
+		assert!(estimate >= U256::from(60_000));
+		assert!(estimate - 60_000 <= estimate * 15 / 1000);
+	}
+
+	#[test]
+	fn search_probes_optimistic_gas_limit_first() {
+		let (estimate, probes) = search(50_000, 50_000, 10_000_000, DEFAULT_ESTIMATE_GAS_TOLERANCE);
+		// (50_000 + 2_300) * 64 / 63
+		let optimistic = U256::from(53_130);
+		assert_eq!(
+			probes,
+			vec![
+				optimistic,
+				U256::from(51_564),
+				U256::from(50_781),
+				U256::from(50_390)
+			]
+		);
+		assert_eq!(estimate, U256::from(50_390));
+	}
+
+	#[test]
+	fn search_skips_optimistic_gas_limit_above_highest() {
+		let (estimate, probes) = search(50_000, 50_000, 52_000, Permill::zero());
+		assert!(probes
+			.iter()
+			.all(|gas_limit| *gas_limit < U256::from(52_000)));
+		assert_eq!(estimate, U256::from(50_000));
+	}
+
+	#[test]
+	fn search_keeps_probes_close_to_lower_bound() {
+		let (estimate, probes) = search(
+			21_000,
+			1_000_000,
+			30_000_000,
+			DEFAULT_ESTIMATE_GAS_TOLERANCE,
+		);
+		// The optimistic probe, (21_000 + 2_300) * 64 / 63, runs out of gas and the next one
+		// doubles it rather than bisecting up to half the highest gas limit.
+		assert_eq!(probes[0], U256::from(23_669));
+		assert_eq!(probes[1], U256::from(47_338));
+		assert!(estimate >= U256::from(1_000_000));
+	}
+
+	#[test]
+	fn search_treats_reverts_as_lack_of_gas_and_fails_on_other_errors() {
+		let estimate = search_gas_limit(
+			50_000.into(),
+			10_000_000.into(),
+			Permill::zero(),
+			|gas_limit| {
+				Ok(if gas_limit >= U256::from(60_000) {
+					(ExitReason::Succeed(ExitSucceed::Returned), Vec::new())
+				} else {
+					(ExitReason::Revert(ExitRevert::Reverted), Vec::new())
+				})
+			},
+		);
+		assert_eq!(estimate.unwrap(), U256::from(60_000));
+
+		let estimate = search_gas_limit(50_000.into(), 10_000_000.into(), Permill::zero(), |_| {
+			Ok((ExitReason::Fatal(ExitFatal::NotSupported), Vec::new()))
+		});
+		assert!(estimate.is_err());
+	}
+}
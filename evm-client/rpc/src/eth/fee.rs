@@ -70,6 +70,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			Some(newest_block),
+			self.safe_depth,
 		) {
 			let header = match self.client.header(id) {
 				Ok(Some(h)) => h,
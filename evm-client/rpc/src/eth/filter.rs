@@ -34,15 +34,19 @@ use sp_runtime::{
 use fc_rpc_core::{types::*, EthFilterApiServer};
 use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
 
-use crate::{eth::cache::EthBlockDataCacheTask, frontier_backend_client, internal_err};
+use crate::{
+	eth::cache::EthBlockDataCacheTask, frontier_backend_client, internal_err, metrics::EthMetrics,
+};
 
 pub struct EthFilter<B: BlockT, C, BE> {
 	client: Arc<C>,
 	backend: Arc<fc_db::Backend<B>>,
+	log_index: Option<Arc<fc_db::sql::LogIndexDb<B>>>,
 	filter_pool: FilterPool,
 	max_stored_filters: usize,
 	max_past_logs: u32,
 	block_data_cache: Arc<EthBlockDataCacheTask<B>>,
+	metrics: Option<Arc<EthMetrics>>,
 	_marker: PhantomData<BE>,
 }
 
@@ -50,6 +54,7 @@ impl<B: BlockT, C, BE> EthFilter<B, C, BE> {
 	pub fn new(
 		client: Arc<C>,
 		backend: Arc<fc_db::Backend<B>>,
+		log_index: Option<Arc<fc_db::sql::LogIndexDb<B>>>,
 		filter_pool: FilterPool,
 		max_stored_filters: usize,
 		max_past_logs: u32,
@@ -58,13 +63,26 @@ impl<B: BlockT, C, BE> EthFilter<B, C, BE> {
 		Self {
 			client,
 			backend,
+			log_index,
 			filter_pool,
 			max_stored_filters,
 			max_past_logs,
 			block_data_cache,
+			metrics: None,
 			_marker: PhantomData,
 		}
 	}
+
+	pub fn with_metrics(mut self, metrics: Option<Arc<EthMetrics>>) -> Self {
+		self.metrics = metrics;
+		self
+	}
+
+	fn update_active_filters(&self, count: usize) {
+		if let Some(metrics) = &self.metrics {
+			metrics.active_filters.set(count as u64);
+		}
+	}
 }
 
 impl<B, C, BE> EthFilter<B, C, BE>
@@ -100,6 +118,7 @@ where
 					at_block: block_number,
 				},
 			);
+			self.update_active_filters(locked.len());
 			Ok(key)
 		} else {
 			Err(internal_err("Filter pool is not available."))
@@ -119,18 +138,22 @@ where
 	BE::State: StateBackend<BlakeTwo256>,
 {
 	fn new_filter(&self, filter: Filter) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_newFilter");
 		self.create_filter(FilterType::Log(filter))
 	}
 
 	fn new_block_filter(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_newBlockFilter");
 		self.create_filter(FilterType::Block)
 	}
 
 	fn new_pending_transaction_filter(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_newPendingTransactionFilter");
 		Err(internal_err("Method not available."))
 	}
 
 	async fn filter_changes(&self, index: Index) -> Result<FilterChanges> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getFilterChanges");
 		// There are multiple branches that needs to return async blocks.
 		// Also, each branch need to (synchronously) do stuff with the pool
 		// (behind a lock), and the lock should be released before entering
@@ -235,6 +258,7 @@ where
 
 		let client = Arc::clone(&self.client);
 		let block_data_cache = Arc::clone(&self.block_data_cache);
+		let log_index = self.log_index.clone();
 		let max_past_logs = self.max_past_logs;
 
 		match path {
@@ -268,6 +292,7 @@ where
 				let _ = filter_range_logs(
 					client.as_ref(),
 					&block_data_cache,
+					log_index.as_deref(),
 					&mut ret,
 					max_past_logs,
 					&filter,
@@ -282,6 +307,7 @@ where
 	}
 
 	async fn filter_logs(&self, index: Index) -> Result<Vec<Log>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getFilterLogs");
 		let key = U256::from(index.value());
 		let pool = self.filter_pool.clone();
 
@@ -332,6 +358,7 @@ where
 		let _ = filter_range_logs(
 			client.as_ref(),
 			&block_data_cache,
+			self.log_index.as_deref(),
 			&mut ret,
 			max_past_logs,
 			&filter,
@@ -343,11 +370,13 @@ where
 	}
 
 	fn uninstall_filter(&self, index: Index) -> Result<bool> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_uninstallFilter");
 		let key = U256::from(index.value());
 		let pool = self.filter_pool.clone();
 		// Try to lock.
 		let response = if let Ok(locked) = &mut pool.lock() {
 			if locked.remove(&key).is_some() {
+				self.update_active_filters(locked.len());
 				Ok(true)
 			} else {
 				Err(internal_err(format!("Filter id {:?} does not exist.", key)))
@@ -359,6 +388,7 @@ where
 	}
 
 	async fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getLogs");
 		let client = Arc::clone(&self.client);
 		let block_data_cache = Arc::clone(&self.block_data_cache);
 		let backend = Arc::clone(&self.backend);
@@ -411,6 +441,7 @@ where
 			let _ = filter_range_logs(
 				client.as_ref(),
 				&block_data_cache,
+				self.log_index.as_deref(),
 				&mut ret,
 				max_past_logs,
 				&filter,
@@ -426,6 +457,7 @@ where
 async fn filter_range_logs<B: BlockT, C, BE>(
 	client: &C,
 	block_data_cache: &EthBlockDataCacheTask<B>,
+	log_index: Option<&fc_db::sql::LogIndexDb<B>>,
 	ret: &mut Vec<Log>,
 	max_past_logs: u32,
 	filter: &Filter,
@@ -441,9 +473,123 @@ where
 	BE::State: StateBackend<BlakeTwo256>,
 {
 	// Max request duration of 10 seconds.
-	let max_duration = time::Duration::from_secs(10);
-	let begin_request = time::Instant::now();
+	let limits = RangeLimits {
+		max_past_logs,
+		begin_request: time::Instant::now(),
+		max_duration: time::Duration::from_secs(10),
+	};
+
+	// Use the log index for the part of the range it holds, scan the blooms for the rest.
+	let indexed = match log_index {
+		Some(log_index) => log_index
+			.indexed_range(from.unique_saturated_into(), to.unique_saturated_into())
+			.map_err(internal_err)?
+			.map(|(low, high)| (log_index, low, high)),
+		None => None,
+	};
+	let (log_index, low, high) = match indexed {
+		Some(indexed) => indexed,
+		None => {
+			return filter_range_blooms::<B, C, BE>(
+				client,
+				block_data_cache,
+				ret,
+				&limits,
+				filter,
+				from,
+				to,
+			)
+			.await
+		}
+	};
+	let (low_number, high_number): (NumberFor<B>, NumberFor<B>) =
+		(low.unique_saturated_into(), high.unique_saturated_into());
+
+	if from < low_number {
+		filter_range_blooms::<B, C, BE>(
+			client,
+			block_data_cache,
+			ret,
+			&limits,
+			filter,
+			from,
+			low_number - One::one(),
+		)
+		.await?;
+	}
+
+	let blocks = canonical_indexed_blocks(client, log_index, &log_index_filter(filter, low, high))?;
+	for substrate_hash in blocks {
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
+			client,
+			BlockId::Hash(substrate_hash),
+		);
+		let block = block_data_cache.current_block(schema, substrate_hash).await;
+		let statuses = block_data_cache
+			.current_transaction_statuses(schema, substrate_hash)
+			.await;
+		if let (Some(block), Some(statuses)) = (block, statuses) {
+			filter_block_logs(ret, filter, block, statuses);
+		}
+		limits.check(ret)?;
+	}
 
+	if high_number < to {
+		filter_range_blooms::<B, C, BE>(
+			client,
+			block_data_cache,
+			ret,
+			&limits,
+			filter,
+			high_number + One::one(),
+			to,
+		)
+		.await?;
+	}
+	Ok(())
+}
+
+/// Query the log index, keeping the canonical blocks only: the index holds the blocks of
+/// every fork.
+fn canonical_indexed_blocks<B, C>(
+	client: &C,
+	log_index: &fc_db::sql::LogIndexDb<B>,
+	filter: &fc_db::sql::LogFilter,
+) -> Result<Vec<B::Hash>>
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+{
+	let mut blocks = Vec::new();
+	for (number, substrate_hash) in log_index.filter_blocks(filter).map_err(internal_err)? {
+		let canonical_hash = client
+			.hash(number.unique_saturated_into())
+			.map_err(|err| internal_err(format!("{:?}", err)))?;
+		if canonical_hash == Some(substrate_hash) {
+			blocks.push(substrate_hash);
+		}
+	}
+	Ok(blocks)
+}
+
+/// Scan the blocks `from..=to` whose bloom filter may match the filter.
+async fn filter_range_blooms<B: BlockT, C, BE>(
+	client: &C,
+	block_data_cache: &EthBlockDataCacheTask<B>,
+	ret: &mut Vec<Log>,
+	limits: &RangeLimits,
+	filter: &Filter,
+	from: NumberFor<B>,
+	to: NumberFor<B>,
+) -> Result<()>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
 	let mut current_number = from;
 
 	// Pre-calculate BloomInput for reuse.
@@ -478,19 +624,7 @@ where
 				}
 			}
 		}
-		// Check for restrictions
-		if ret.len() as u32 > max_past_logs {
-			return Err(internal_err(format!(
-				"query returned more than {} results",
-				max_past_logs
-			)));
-		}
-		if begin_request.elapsed() > max_duration {
-			return Err(internal_err(format!(
-				"query timeout of {} seconds exceeded",
-				max_duration.as_secs()
-			)));
-		}
+		limits.check(ret)?;
 		if current_number == to {
 			break;
 		} else {
@@ -500,6 +634,75 @@ where
 	Ok(())
 }
 
+/// Limits of an `eth_getLogs` request.
+struct RangeLimits {
+	max_past_logs: u32,
+	begin_request: time::Instant,
+	max_duration: time::Duration,
+}
+
+impl RangeLimits {
+	fn check(&self, ret: &[Log]) -> Result<()> {
+		check_range_limits(
+			ret,
+			self.max_past_logs,
+			self.begin_request,
+			self.max_duration,
+		)
+	}
+}
+
+fn check_range_limits(
+	ret: &[Log],
+	max_past_logs: u32,
+	begin_request: time::Instant,
+	max_duration: time::Duration,
+) -> Result<()> {
+	if ret.len() as u32 > max_past_logs {
+		return Err(internal_err(format!(
+			"query returned more than {} results",
+			max_past_logs
+		)));
+	}
+	if begin_request.elapsed() > max_duration {
+		return Err(internal_err(format!(
+			"query timeout of {} seconds exceeded",
+			max_duration.as_secs()
+		)));
+	}
+	Ok(())
+}
+
+/// Translate the filter into a log index query. The query may match more logs than
+/// the filter, blocks it returns are checked against the filter afterwards.
+fn log_index_filter(filter: &Filter, from: u64, to: u64) -> fc_db::sql::LogFilter {
+	let addresses = match &filter.address {
+		Some(VariadicValue::Single(address)) => vec![*address],
+		Some(VariadicValue::Multiple(addresses)) => addresses.clone(),
+		_ => Vec::new(),
+	};
+	let single = |topic: &Option<VariadicValue<Option<H256>>>| match topic {
+		Some(VariadicValue::Single(Some(topic))) => Some(vec![*topic]),
+		Some(VariadicValue::Multiple(topics)) if !topics.is_empty() => {
+			topics.iter().copied().collect::<Option<Vec<_>>>()
+		}
+		_ => None,
+	};
+	let topics = match &filter.topics {
+		Some(VariadicValue::Multiple(topics)) => topics.iter().map(single).collect(),
+		Some(VariadicValue::Single(Some(VariadicValue::Single(Some(topic))))) => {
+			vec![Some(vec![*topic])]
+		}
+		_ => Vec::new(),
+	};
+	fc_db::sql::LogFilter {
+		from_block: from,
+		to_block: to,
+		addresses,
+		topics,
+	}
+}
+
 fn filter_block_logs<'a>(
 	ret: &'a mut Vec<Log>,
 	filter: &'a Filter,
@@ -560,3 +763,127 @@ fn filter_block_logs<'a>(
 	}
 	ret
 }
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H160;
+	use futures::executor;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::BlockOrigin;
+	use substrate_test_runtime_client::{
+		prelude::*, runtime::Block, DefaultTestClientBuilderExt, TestClient, TestClientBuilder,
+	};
+
+	use super::*;
+
+	fn filter(address: Option<FilterAddress>, topics: Option<Topic>) -> Filter {
+		Filter {
+			from_block: None,
+			to_block: None,
+			block_hash: None,
+			address,
+			topics,
+		}
+	}
+
+	fn import(client: &mut Arc<TestClient>, parent: H256, key: u8) -> H256 {
+		let mut builder = client
+			.new_block_at(&BlockId::Hash(parent), Default::default(), false)
+			.unwrap();
+		builder.push_storage_change(vec![key], None).unwrap();
+		let block = builder.build().unwrap().block;
+		let hash = block.header.hash();
+		executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		hash
+	}
+
+	#[test]
+	fn log_index_filter_translates_addresses() {
+		let a = H160::repeat_byte(0xa);
+		let b = H160::repeat_byte(0xb);
+
+		let query = log_index_filter(&filter(None, None), 1, 5);
+		assert_eq!(
+			query,
+			fc_db::sql::LogFilter {
+				from_block: 1,
+				to_block: 5,
+				addresses: Vec::new(),
+				topics: Vec::new(),
+			}
+		);
+		let query = log_index_filter(&filter(Some(VariadicValue::Single(a)), None), 1, 5);
+		assert_eq!(query.addresses, vec![a]);
+		let query = log_index_filter(
+			&filter(Some(VariadicValue::Multiple(vec![a, b])), None),
+			1,
+			5,
+		);
+		assert_eq!(query.addresses, vec![a, b]);
+	}
+
+	#[test]
+	fn log_index_filter_translates_topics() {
+		let a = H256::repeat_byte(0xa);
+		let b = H256::repeat_byte(0xb);
+		let c = H256::repeat_byte(0xc);
+
+		let single = filter(
+			None,
+			Some(VariadicValue::Single(Some(VariadicValue::Single(Some(a))))),
+		);
+		assert_eq!(log_index_filter(&single, 0, 0).topics, vec![Some(vec![a])]);
+
+		// Positional topics, `None` matches anything at its position.
+		let positional = filter(
+			None,
+			Some(VariadicValue::Multiple(vec![
+				Some(VariadicValue::Single(Some(a))),
+				None,
+				Some(VariadicValue::Multiple(vec![Some(b), Some(c)])),
+			])),
+		);
+		assert_eq!(
+			log_index_filter(&positional, 0, 0).topics,
+			vec![Some(vec![a]), None, Some(vec![b, c])]
+		);
+
+		// A wildcard among the alternatives leaves the position unconstrained.
+		let wildcard = filter(
+			None,
+			Some(VariadicValue::Multiple(vec![Some(
+				VariadicValue::Multiple(vec![Some(a), None]),
+			)])),
+		);
+		assert_eq!(log_index_filter(&wildcard, 0, 0).topics, vec![None]);
+	}
+
+	#[test]
+	fn canonical_indexed_blocks_skips_forks() {
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let log_index =
+			fc_db::sql::LogIndexDb::<Block>::open_in_memory().expect("in memory db opens");
+		let address = H160::repeat_byte(0xa);
+		let log = |log_index| fc_db::sql::IndexedLog {
+			transaction_index: 0,
+			log_index,
+			address,
+			topics: Vec::new(),
+		};
+
+		// G -> A1 -> A2 is the best chain, G -> B1 a fork.
+		let genesis = client.info().genesis_hash;
+		let a1 = import(&mut client, genesis, 1);
+		let a2 = import(&mut client, a1, 1);
+		let b1 = import(&mut client, genesis, 2);
+		log_index.index_block(a1, 1, &[log(0)]).unwrap();
+		log_index.index_block(b1, 1, &[log(0)]).unwrap();
+		log_index.index_block(a2, 2, &[log(0)]).unwrap();
+
+		let query = log_index_filter(&filter(Some(VariadicValue::Single(address)), None), 0, 2);
+		assert_eq!(
+			canonical_indexed_blocks(client.as_ref(), &log_index, &query).unwrap(),
+			vec![a1, a2]
+		);
+	}
+}
@@ -33,10 +33,12 @@ impl Geth {
 			Ok(PError::AlreadyImported(_)) => "already known".to_string(),
 			Ok(PError::TemporarilyBanned) => "already known".into(),
 			Ok(PError::TooLowPriority { .. }) => "replacement transaction underpriced".into(),
+			Ok(PError::ImmediatelyDropped) => "txpool is full".into(),
 			Ok(PError::InvalidTransaction(inner)) => match inner {
 				InvalidTransaction::Stale => "nonce too low".into(),
 				InvalidTransaction::Payment => "insufficient funds for gas * price + value".into(),
 				InvalidTransaction::ExhaustsResources => "exceeds block gas limit".into(),
+				InvalidTransaction::BadProof => "invalid sender".into(),
 				InvalidTransaction::Custom(inner) => match inner.into() {
 					VError::UnknownError => "unknown error".into(),
 					VError::InvalidChainId => "invalid chain id".into(),
@@ -46,6 +48,9 @@ impl Geth {
 					VError::MaxFeePerGasTooLow => {
 						"max priority fee per gas higher than max fee per gas".into()
 					}
+					VError::GasPriceTooLow => "max fee per gas less than block base fee".into(),
+					VError::InvalidFeeInput => "invalid fee input".into(),
+					VError::InitcodeTooLarge => "max initcode size exceeded".into(),
 				},
 				_ => "unknown error".into(),
 			},
@@ -24,6 +24,7 @@ mod fee;
 mod filter;
 pub mod format;
 mod mining;
+mod sign;
 mod state;
 mod submit;
 mod transaction;
@@ -34,9 +35,13 @@ use ethereum::{BlockV2 as EthereumBlock, TransactionV2 as EthereumTransaction};
 use ethereum_types::{H160, H256, H512, H64, U256, U64};
 use jsonrpsee::core::{async_trait, RpcResult as Result};
 // Substrate
-use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sc_client_api::{
+	backend::{Backend, StateBackend, StorageProvider},
+	ProofProvider,
+};
 use sc_network::NetworkService;
 use sc_network_common::ExHashT;
+use sc_rpc::DenyUnsafe;
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
 use sp_api::{Core, HeaderT, ProvideRuntimeApi};
@@ -46,12 +51,18 @@ use sp_core::hashing::keccak_256;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto},
+	Permill,
 };
 // Frontier
 use fc_rpc_core::{types::*, EthApiServer};
-use fp_rpc::{ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi, TransactionStatus};
+use fp_rpc::{
+	ConvertTransactionRuntimeApi, EthereumProofRuntimeApi, EthereumRuntimeRPCApi, TransactionStatus,
+};
 
-use crate::{internal_err, overrides::OverrideHandle, public_key, signer::EthSigner};
+use self::execute::DEFAULT_ESTIMATE_GAS_TOLERANCE;
+use crate::{
+	internal_err, metrics::EthMetrics, overrides::OverrideHandle, public_key, signer::EthSigner,
+};
 
 pub use self::{
 	cache::{EthBlockDataCacheTask, EthTask},
@@ -76,6 +87,14 @@ pub struct Eth<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA = ()> {
 	/// When using eth_call/eth_estimateGas, the maximum allowed gas limit will be
 	/// block.gas_limit * execute_gas_limit_multiplier
 	execute_gas_limit_multiplier: u64,
+	/// Relative gap between the bounds of the eth_estimateGas binary search below which
+	/// the upper bound is returned.
+	estimate_gas_tolerance: Permill,
+	/// Number of confirmations of the `safe` block, which is the finalized block when `None`.
+	safe_depth: Option<u32>,
+	/// Whether to deny the unsafe methods, signing arbitrary data with the node accounts.
+	deny_unsafe: DenyUnsafe,
+	metrics: Option<Arc<EthMetrics>>,
 	_marker: PhantomData<(B, BE, EGA)>,
 }
 
@@ -109,6 +128,10 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi> Eth<B, C, P, CT, BE, H, A
 			fee_history_cache,
 			fee_history_cache_limit,
 			execute_gas_limit_multiplier,
+			estimate_gas_tolerance: DEFAULT_ESTIMATE_GAS_TOLERANCE,
+			safe_depth: None,
+			deny_unsafe: DenyUnsafe::Yes,
+			metrics: None,
 			_marker: PhantomData,
 		}
 	}
@@ -132,6 +155,10 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE,
 			fee_history_cache,
 			fee_history_cache_limit,
 			execute_gas_limit_multiplier,
+			estimate_gas_tolerance,
+			safe_depth,
+			deny_unsafe,
+			metrics,
 			_marker: _,
 		} = self;
 
@@ -149,18 +176,43 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE,
 			fee_history_cache,
 			fee_history_cache_limit,
 			execute_gas_limit_multiplier,
+			estimate_gas_tolerance,
+			safe_depth,
+			deny_unsafe,
+			metrics,
 			_marker: PhantomData,
 		}
 	}
+
+	pub fn with_estimate_gas_tolerance(mut self, tolerance: Permill) -> Self {
+		self.estimate_gas_tolerance = tolerance;
+		self
+	}
+
+	pub fn with_safe_depth(mut self, safe_depth: Option<u32>) -> Self {
+		self.safe_depth = safe_depth;
+		self
+	}
+
+	pub fn with_deny_unsafe(mut self, deny_unsafe: DenyUnsafe) -> Self {
+		self.deny_unsafe = deny_unsafe;
+		self
+	}
+
+	pub fn with_metrics(mut self, metrics: Option<Arc<EthMetrics>>) -> Self {
+		self.metrics = metrics;
+		self
+	}
 }
 
 #[async_trait]
 impl<B, C, P, CT, BE, H: ExHashT, A, EGA> EthApiServer for Eth<B, C, P, CT, BE, H, A, EGA>
 where
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
-	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + ProofProvider<B>,
 	C: HeaderBackend<B> + Send + Sync + 'static,
 	C::Api: BlockBuilderApi<B> + ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+	C::Api: EthereumProofRuntimeApi<B>,
 	P: TransactionPool<Block = B> + Send + Sync + 'static,
 	CT: fp_rpc::ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
 	BE: Backend<B> + 'static,
@@ -173,26 +225,32 @@ where
 	// ########################################################################
 
 	fn protocol_version(&self) -> Result<u64> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_protocolVersion");
 		self.protocol_version()
 	}
 
 	fn syncing(&self) -> Result<SyncStatus> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_syncing");
 		self.syncing()
 	}
 
 	fn author(&self) -> Result<H160> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_coinbase");
 		self.author()
 	}
 
 	fn accounts(&self) -> Result<Vec<H160>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_accounts");
 		self.accounts()
 	}
 
 	fn block_number(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_blockNumber");
 		self.block_number()
 	}
 
 	fn chain_id(&self) -> Result<Option<U64>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_chainId");
 		self.chain_id()
 	}
 
@@ -201,30 +259,39 @@ where
 	// ########################################################################
 
 	async fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getBlockByHash");
 		self.block_by_hash(hash, full).await
 	}
 
 	async fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getBlockByNumber");
 		self.block_by_number(number, full).await
 	}
 
 	fn block_transaction_count_by_hash(&self, hash: H256) -> Result<Option<U256>> {
+		let _timer =
+			crate::metrics::start_call(&self.metrics, "eth_getBlockTransactionCountByHash");
 		self.block_transaction_count_by_hash(hash)
 	}
 
 	fn block_transaction_count_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
+		let _timer =
+			crate::metrics::start_call(&self.metrics, "eth_getBlockTransactionCountByNumber");
 		self.block_transaction_count_by_number(number)
 	}
 
 	fn block_uncles_count_by_hash(&self, hash: H256) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getUncleCountByBlockHash");
 		self.block_uncles_count_by_hash(hash)
 	}
 
 	fn block_uncles_count_by_number(&self, number: BlockNumber) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getUncleCountByBlockNumber");
 		self.block_uncles_count_by_number(number)
 	}
 
 	fn uncle_by_block_hash_and_index(&self, hash: H256, index: Index) -> Result<Option<RichBlock>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getUncleByBlockHashAndIndex");
 		self.uncle_by_block_hash_and_index(hash, index)
 	}
 
@@ -233,6 +300,7 @@ where
 		number: BlockNumber,
 		index: Index,
 	) -> Result<Option<RichBlock>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getUncleByBlockNumberAndIndex");
 		self.uncle_by_block_number_and_index(number, index)
 	}
 
@@ -241,6 +309,7 @@ where
 	// ########################################################################
 
 	async fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getTransactionByHash");
 		self.transaction_by_hash(hash).await
 	}
 
@@ -249,6 +318,8 @@ where
 		hash: H256,
 		index: Index,
 	) -> Result<Option<Transaction>> {
+		let _timer =
+			crate::metrics::start_call(&self.metrics, "eth_getTransactionByBlockHashAndIndex");
 		self.transaction_by_block_hash_and_index(hash, index).await
 	}
 
@@ -257,48 +328,87 @@ where
 		number: BlockNumber,
 		index: Index,
 	) -> Result<Option<Transaction>> {
+		let _timer =
+			crate::metrics::start_call(&self.metrics, "eth_getTransactionByBlockNumberAndIndex");
 		self.transaction_by_block_number_and_index(number, index)
 			.await
 	}
 
 	async fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getTransactionReceipt");
 		self.transaction_receipt(hash).await
 	}
 
+	async fn block_receipts(&self, number: BlockNumber) -> Result<Option<Vec<Receipt>>> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getBlockReceipts");
+		self.block_receipts(number).await
+	}
+
 	// ########################################################################
 	// State
 	// ########################################################################
 
 	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getBalance");
 		self.balance(address, number)
 	}
 
 	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getStorageAt");
 		self.storage_at(address, index, number)
 	}
 
 	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getTransactionCount");
 		self.transaction_count(address, number)
 	}
 
 	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getCode");
 		self.code_at(address, number)
 	}
 
+	fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> Result<EthAccount> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getProof");
+		self.proof(address, storage_keys, number)
+	}
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
 
-	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes> {
-		self.call(request, number)
+	fn call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<Bytes> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_call");
+		self.call(request, number, state_overrides)
 	}
 
 	async fn estimate_gas(
 		&self,
 		request: CallRequest,
 		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
 	) -> Result<U256> {
-		self.estimate_gas(request, number).await
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_estimateGas");
+		self.estimate_gas(request, number, state_overrides).await
+	}
+
+	fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> Result<AccessListResult> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_createAccessList");
+		self.create_access_list(request, number)
 	}
 
 	// ########################################################################
@@ -306,6 +416,7 @@ where
 	// ########################################################################
 
 	fn gas_price(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_gasPrice");
 		self.gas_price()
 	}
 
@@ -315,10 +426,12 @@ where
 		newest_block: BlockNumber,
 		reward_percentiles: Option<Vec<f64>>,
 	) -> Result<FeeHistory> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_feeHistory");
 		self.fee_history(block_count, newest_block, reward_percentiles)
 	}
 
 	fn max_priority_fee_per_gas(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_maxPriorityFeePerGas");
 		self.max_priority_fee_per_gas()
 	}
 
@@ -327,22 +440,27 @@ where
 	// ########################################################################
 
 	fn is_mining(&self) -> Result<bool> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_mining");
 		self.is_mining()
 	}
 
 	fn hashrate(&self) -> Result<U256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_hashrate");
 		self.hashrate()
 	}
 
 	fn work(&self) -> Result<Work> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_getWork");
 		self.work()
 	}
 
 	fn submit_hashrate(&self, hashrate: U256, id: H256) -> Result<bool> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_submitHashrate");
 		self.submit_hashrate(hashrate, id)
 	}
 
 	fn submit_work(&self, nonce: H64, pow_hash: H256, mix_digest: H256) -> Result<bool> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_submitWork");
 		self.submit_work(nonce, pow_hash, mix_digest)
 	}
 
@@ -351,12 +469,28 @@ where
 	// ########################################################################
 
 	async fn send_transaction(&self, request: TransactionRequest) -> Result<H256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_sendTransaction");
 		self.send_transaction(request).await
 	}
 
 	async fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_sendRawTransaction");
 		self.send_raw_transaction(bytes).await
 	}
+
+	// ########################################################################
+	// Sign
+	// ########################################################################
+
+	fn sign(&self, address: H160, data: Bytes) -> Result<Bytes> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_sign");
+		self.sign(address, data)
+	}
+
+	fn sign_typed_data(&self, address: H160, typed_data: TypedData) -> Result<Bytes> {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_signTypedData_v4");
+		self.sign_typed_data(address, typed_data)
+	}
 }
 
 fn rich_block_build(
@@ -425,7 +559,7 @@ fn rich_block_build(
 	}
 }
 
-fn transaction_build(
+pub(crate) fn transaction_build(
 	ethereum_transaction: EthereumTransaction,
 	block: Option<EthereumBlock>,
 	status: Option<TransactionStatus>,
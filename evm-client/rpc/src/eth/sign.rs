@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use ethereum_types::H160;
+use jsonrpsee::core::RpcResult as Result;
+// Substrate
+use sc_network_common::ExHashT;
+use sc_transaction_pool::ChainApi;
+use sp_runtime::traits::Block as BlockT;
+// Frontier
+use fc_rpc_core::types::*;
+
+use crate::{eip712, eth::Eth, internal_err, signer};
+
+impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE, H, A, EGA> {
+	pub fn sign(&self, address: H160, data: Bytes) -> Result<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		signer::sign_hash(&self.signers, signer::message_hash(&data), &address)
+	}
+
+	pub fn sign_typed_data(&self, address: H160, typed_data: TypedData) -> Result<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		let hash = eip712::typed_data_hash(&typed_data)
+			.map_err(|err| internal_err(format!("invalid typed data: {}", err)))?;
+		signer::sign_hash(&self.signers, hash, &address)
+	}
+}
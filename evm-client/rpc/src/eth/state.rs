@@ -20,20 +20,24 @@ use ethereum_types::{H160, H256, U256};
 use jsonrpsee::core::RpcResult as Result;
 use scale_codec::Encode;
 // Substrate
-use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sc_client_api::{
+	backend::{Backend, StateBackend, StorageProvider},
+	ProofProvider,
+};
 use sc_network_common::ExHashT;
 use sc_transaction_pool::ChainApi;
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
+use sp_core::hashing::keccak_256;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{BlakeTwo256, Block as BlockT},
+	traits::{BlakeTwo256, Block as BlockT, Header as HeaderT},
 };
 // Frontier
 use fc_rpc_core::types::*;
-use fp_rpc::EthereumRuntimeRPCApi;
+use fp_rpc::{EthereumProofRuntimeApi, EthereumRuntimeRPCApi};
 
 use crate::{
 	eth::{pending_runtime_api, Eth},
@@ -63,6 +67,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		) {
 			Ok(self
 				.client
@@ -91,6 +96,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		) {
 			let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
 				self.client.as_ref(),
@@ -139,6 +145,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			number,
+			self.safe_depth,
 		)? {
 			Some(id) => id,
 			None => return Ok(U256::zero()),
@@ -164,6 +171,7 @@ where
 			self.client.as_ref(),
 			self.backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		) {
 			let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
 				self.client.as_ref(),
@@ -183,3 +191,102 @@ where
 		}
 	}
 }
+
+impl<B, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE, H, A, EGA>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + ProofProvider<B>,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + EthereumProofRuntimeApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	pub fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> Result<EthAccount> {
+		if let Some(BlockNumber::Pending) = number {
+			return Err(internal_err(
+				"proofs are not available for the pending block",
+			));
+		}
+		let id = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+			self.safe_depth,
+		)? {
+			Some(id) => id,
+			None => return Err(internal_err("header not found")),
+		};
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+		let state_root = match self.client.header(id) {
+			Ok(Some(header)) => *header.state_root(),
+			_ => {
+				return Err(internal_err(format!(
+					"Cannot get header for block {:?}",
+					id
+				)))
+			}
+		};
+
+		let api = self.client.runtime_api();
+		if !api
+			.has_api::<dyn EthereumProofRuntimeApi<B>>(&id)
+			.unwrap_or(false)
+		{
+			return Err(internal_err("runtime does not support account proofs"));
+		}
+		let read_proof = |keys: Vec<Vec<u8>>| -> Result<Vec<Bytes>> {
+			Ok(self
+				.client
+				.read_proof(substrate_hash, &mut keys.iter().map(|key| key.as_slice()))
+				.map_err(|err| internal_err(format!("fetch read proof failed: {:?}", err)))?
+				.into_iter_nodes()
+				.map(Bytes)
+				.collect())
+		};
+
+		let account = api.account_basic(&id, address).map_err(|err| {
+			internal_err(format!("fetch runtime account basic failed: {:?}", err))
+		})?;
+		let code = api
+			.account_code_at(&id, address)
+			.map_err(|err| internal_err(format!("fetch runtime account code failed: {:?}", err)))?;
+		let account_keys = api
+			.account_proof_keys(&id, address)
+			.map_err(|err| internal_err(format!("fetch runtime proof keys failed: {:?}", err)))?;
+
+		let mut storage_proof = Vec::with_capacity(storage_keys.len());
+		for index in storage_keys {
+			let key = api.storage_proof_key(&id, address, index).map_err(|err| {
+				internal_err(format!("fetch runtime proof keys failed: {:?}", err))
+			})?;
+			let value = api
+				.storage_at(&id, address, U256::from_big_endian(index.as_bytes()))
+				.map_err(|err| internal_err(format!("fetch runtime storage failed: {:?}", err)))?;
+			storage_proof.push(StorageProof {
+				key: U256::from_big_endian(index.as_bytes()),
+				value: U256::from_big_endian(value.as_bytes()),
+				proof: read_proof(vec![key])?,
+			});
+		}
+
+		Ok(EthAccount {
+			address,
+			balance: account.balance,
+			nonce: account.nonce,
+			code_hash: H256::from(keccak_256(&code)),
+			// Proofs are checked against the Substrate state root, see
+			// `fp_rpc::verify_storage_proof`.
+			storage_hash: state_root,
+			account_proof: read_proof(account_keys)?,
+			storage_proof,
+		})
+	}
+}
@@ -37,8 +37,9 @@ use fc_rpc_core::types::*;
 use fp_rpc::{ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
 
 use crate::{
-	eth::{format, Eth},
+	eth::{execute::JSON_RPC_ERROR_DEFAULT, format, Eth},
 	internal_err,
+	signer::EthSigner,
 };
 
 impl<B, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Eth<B, C, P, CT, BE, H, A, EGA>
@@ -54,19 +55,22 @@ where
 	A: ChainApi<Block = B> + 'static,
 {
 	pub async fn send_transaction(&self, request: TransactionRequest) -> Result<H256> {
+		self.send_transaction_with_signers(request, &self.signers)
+			.await
+	}
+
+	/// Sign a transaction with the given signers and submit it to the pool.
+	pub(crate) async fn send_transaction_with_signers(
+		&self,
+		request: TransactionRequest,
+		signers: &[Box<dyn EthSigner>],
+	) -> Result<H256> {
 		let from = match request.from {
 			Some(from) => from,
-			None => {
-				let accounts = match self.accounts() {
-					Ok(accounts) => accounts,
-					Err(e) => return Err(e),
-				};
-
-				match accounts.get(0) {
-					Some(account) => *account,
-					None => return Err(internal_err("no signer available")),
-				}
-			}
+			None => match signers.iter().flat_map(|signer| signer.accounts()).next() {
+				Some(account) => account,
+				None => return Err(internal_err("no signer available")),
+			},
 		};
 
 		let nonce = match request.nonce {
@@ -135,7 +139,7 @@ where
 
 		let mut transaction = None;
 
-		for signer in &self.signers {
+		for signer in signers {
 			if signer.accounts().contains(&from) {
 				match signer.sign(message, &from) {
 					Ok(t) => transaction = Some(t),
@@ -205,7 +209,7 @@ where
 		self.pool
 			.submit_one(&block_hash, TransactionSource::Local, extrinsic)
 			.map_ok(move |_| transaction_hash)
-			.map_err(|err| internal_err(format::Geth::pool_error(err)))
+			.map_err(|err| crate::err(JSON_RPC_ERROR_DEFAULT, format::Geth::pool_error(err), None))
 			.await
 	}
 
@@ -278,7 +282,7 @@ where
 		self.pool
 			.submit_one(&block_hash, TransactionSource::Local, extrinsic)
 			.map_ok(move |_| transaction_hash)
-			.map_err(|err| internal_err(format::Geth::pool_error(err)))
+			.map_err(|err| crate::err(JSON_RPC_ERROR_DEFAULT, format::Geth::pool_error(err), None))
 			.await
 	}
 }
@@ -18,7 +18,7 @@
 
 use std::sync::Arc;
 
-use ethereum::TransactionV2 as EthereumTransaction;
+use ethereum::{BlockV2 as EthereumBlock, TransactionV2 as EthereumTransaction};
 use ethereum_types::{H256, U256, U64};
 use jsonrpsee::core::RpcResult as Result;
 // Substrate
@@ -35,7 +35,7 @@ use sp_runtime::{
 };
 // Frontier
 use fc_rpc_core::types::*;
-use fp_rpc::EthereumRuntimeRPCApi;
+use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
 
 use crate::{
 	eth::{transaction_build, Eth},
@@ -228,6 +228,7 @@ where
 			client.as_ref(),
 			backend.as_ref(),
 			Some(number),
+			self.safe_depth,
 		)? {
 			Some(id) => id,
 			None => return Ok(None),
@@ -312,141 +313,289 @@ where
 		let receipts = handler.current_receipts(&id);
 		let is_eip1559 = handler.is_eip1559(&id);
 
-		match (block, statuses, receipts) {
-			(Some(block), Some(statuses), Some(receipts)) => {
-				let block_hash = H256::from(keccak_256(&rlp::encode(&block.header)));
-				let receipt = receipts[index].clone();
+		let (block, statuses, receipts) = match (block, statuses, receipts) {
+			(Some(block), Some(statuses), Some(receipts)) => (block, statuses, receipts),
+			_ => return Ok(None),
+		};
+		let base_fee = client.runtime_api().gas_price(&id).unwrap_or_default();
 
-				let (logs, logs_bloom, status_code, cumulative_gas_used, gas_used) = if !is_eip1559
-				{
-					// Pre-london frontier update stored receipts require cumulative gas calculation.
-					match receipt {
-						ethereum::ReceiptV3::Legacy(ref d) => {
-							let index = core::cmp::min(receipts.len(), index + 1);
-							let cumulative_gas: u32 = receipts[..index]
-								.iter()
-								.map(|r| match r {
-									ethereum::ReceiptV3::Legacy(d) => Ok(d.used_gas.as_u32()),
-									_ => Err(internal_err(format!(
-										"Unknown receipt for request {}",
-										hash
-									))),
-								})
-								.sum::<Result<u32>>()?;
-							(
-								d.logs.clone(),
-								d.logs_bloom,
-								d.status_code,
-								U256::from(cumulative_gas),
-								d.used_gas,
-							)
-						}
-						_ => {
-							return Err(internal_err(format!(
-								"Unknown receipt for request {}",
-								hash
-							)))
-						}
-					}
-				} else {
-					match receipt {
-						ethereum::ReceiptV3::Legacy(ref d)
-						| ethereum::ReceiptV3::EIP2930(ref d)
-						| ethereum::ReceiptV3::EIP1559(ref d) => {
-							let cumulative_gas = d.used_gas;
-							let gas_used = if index > 0 {
-								let previous_receipt = receipts[index - 1].clone();
-								let previous_gas_used = match previous_receipt {
-									ethereum::ReceiptV3::Legacy(d)
-									| ethereum::ReceiptV3::EIP2930(d)
-									| ethereum::ReceiptV3::EIP1559(d) => d.used_gas,
-								};
-								cumulative_gas.saturating_sub(previous_gas_used)
-							} else {
-								cumulative_gas
-							};
-							(
-								d.logs.clone(),
-								d.logs_bloom,
-								d.status_code,
-								cumulative_gas,
-								gas_used,
-							)
-						}
-					}
-				};
+		let mut receipts = receipts_build(
+			&block,
+			statuses,
+			receipts,
+			is_eip1559,
+			base_fee,
+			Some(index),
+		)?;
+		Ok(receipts.pop())
+	}
 
-				let status = statuses[index].clone();
-				let mut cumulative_receipts = receipts;
-				cumulative_receipts.truncate((status.transaction_index + 1) as usize);
-				let transaction = block.transactions[index].clone();
-				let effective_gas_price = match transaction {
-					EthereumTransaction::Legacy(t) => t.gas_price,
-					EthereumTransaction::EIP2930(t) => t.gas_price,
-					EthereumTransaction::EIP1559(t) => client
-						.runtime_api()
-						.gas_price(&id)
-						.unwrap_or_default()
-						.checked_add(t.max_priority_fee_per_gas)
-						.unwrap_or_else(U256::max_value)
-						.min(t.max_fee_per_gas),
-				};
+	pub async fn block_receipts(&self, number: BlockNumber) -> Result<Option<Vec<Receipt>>> {
+		let client = Arc::clone(&self.client);
+		let overrides = Arc::clone(&self.overrides);
+		let block_data_cache = Arc::clone(&self.block_data_cache);
+		let backend = Arc::clone(&self.backend);
 
-				return Ok(Some(Receipt {
-					transaction_hash: Some(status.transaction_hash),
-					transaction_index: Some(status.transaction_index.into()),
+		let id = match frontier_backend_client::native_block_id::<B, C>(
+			client.as_ref(),
+			backend.as_ref(),
+			Some(number),
+			self.safe_depth,
+		)? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+		let substrate_hash = client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		let schema =
+			frontier_backend_client::onchain_storage_schema::<B, C, BE>(client.as_ref(), id);
+		let handler = overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&overrides.fallback);
+
+		let block = block_data_cache.current_block(schema, substrate_hash).await;
+		let statuses = block_data_cache
+			.current_transaction_statuses(schema, substrate_hash)
+			.await;
+		let receipts = handler.current_receipts(&id);
+		let is_eip1559 = handler.is_eip1559(&id);
+
+		let (block, statuses, receipts) = match (block, statuses, receipts) {
+			(Some(block), Some(statuses), Some(receipts)) => (block, statuses, receipts),
+			_ => return Ok(None),
+		};
+		let base_fee = client.runtime_api().gas_price(&id).unwrap_or_default();
+
+		receipts_build(&block, statuses, receipts, is_eip1559, base_fee, None).map(Some)
+	}
+}
+
+/// Builds the receipts of every transaction of a block, or only of the one at `only`: the
+/// transactions before it are then read for the cumulative gas and log index alone, and the
+/// ones after it not at all.
+///
+/// Pre-london frontier update stored receipts hold the gas used by each transaction, later
+/// ones the cumulative gas used.
+fn receipts_build(
+	block: &EthereumBlock,
+	statuses: Vec<TransactionStatus>,
+	receipts: Vec<ethereum::ReceiptV3>,
+	is_eip1559: bool,
+	base_fee: U256,
+	only: Option<usize>,
+) -> Result<Vec<Receipt>> {
+	let block_hash = H256::from(keccak_256(&rlp::encode(&block.header)));
+	if statuses.len() != receipts.len() || block.transactions.len() != receipts.len() {
+		return Err(internal_err(format!(
+			"Inconsistent transaction data for block {}",
+			block_hash
+		)));
+	}
+
+	let mut block_receipts = Vec::with_capacity(only.map_or(receipts.len(), |_| 1));
+	let mut cumulative_gas_used = U256::zero();
+	let mut log_index = 0u32;
+	for (index, ((receipt, status), transaction)) in receipts
+		.into_iter()
+		.zip(statuses.into_iter())
+		.zip(block.transactions.iter())
+		.enumerate()
+		.take(only.map_or(usize::MAX, |only| only.saturating_add(1)))
+	{
+		let (transaction_type, d) = match receipt {
+			ethereum::ReceiptV3::Legacy(d) => (0, d),
+			ethereum::ReceiptV3::EIP2930(d) => (1, d),
+			ethereum::ReceiptV3::EIP1559(d) => (2, d),
+		};
+		let gas_used = if is_eip1559 {
+			let gas_used = d.used_gas.saturating_sub(cumulative_gas_used);
+			cumulative_gas_used = d.used_gas;
+			gas_used
+		} else {
+			cumulative_gas_used = cumulative_gas_used.saturating_add(d.used_gas);
+			d.used_gas
+		};
+		if matches!(only, Some(only) if index < only) {
+			log_index += d.logs.len() as u32;
+			continue;
+		}
+
+		let effective_gas_price = match transaction {
+			EthereumTransaction::Legacy(t) => t.gas_price,
+			EthereumTransaction::EIP2930(t) => t.gas_price,
+			EthereumTransaction::EIP1559(t) => base_fee
+				.checked_add(t.max_priority_fee_per_gas)
+				.unwrap_or_else(U256::max_value)
+				.min(t.max_fee_per_gas),
+		};
+
+		let logs = d
+			.logs
+			.into_iter()
+			.enumerate()
+			.map(|(i, log)| {
+				let log = Log {
+					address: log.address,
+					topics: log.topics,
+					data: Bytes(log.data),
 					block_hash: Some(block_hash),
-					from: Some(status.from),
-					to: status.to,
 					block_number: Some(block.header.number),
-					cumulative_gas_used,
-					gas_used: Some(gas_used),
-					contract_address: status.contract_address,
-					logs: {
-						let mut pre_receipts_log_index = None;
-						if cumulative_receipts.len() > 0 {
-							cumulative_receipts.truncate(cumulative_receipts.len() - 1);
-							pre_receipts_log_index = Some(
-								cumulative_receipts
-									.iter()
-									.map(|r| match r {
-										ethereum::ReceiptV3::Legacy(d)
-										| ethereum::ReceiptV3::EIP2930(d)
-										| ethereum::ReceiptV3::EIP1559(d) => d.logs.len() as u32,
-									})
-									.sum::<u32>(),
-							);
-						}
-						logs.iter()
-							.enumerate()
-							.map(|(i, log)| Log {
-								address: log.address,
-								topics: log.topics.clone(),
-								data: Bytes(log.data.clone()),
-								block_hash: Some(block_hash),
-								block_number: Some(block.header.number),
-								transaction_hash: Some(status.transaction_hash),
-								transaction_index: Some(status.transaction_index.into()),
-								log_index: Some(U256::from(
-									(pre_receipts_log_index.unwrap_or(0)) + i as u32,
-								)),
-								transaction_log_index: Some(U256::from(i)),
-								removed: false,
-							})
-							.collect()
-					},
-					status_code: Some(U64::from(status_code)),
-					logs_bloom,
-					state_root: None,
-					effective_gas_price,
-					transaction_type: match receipt {
-						ethereum::ReceiptV3::Legacy(_) => U256::from(0),
-						ethereum::ReceiptV3::EIP2930(_) => U256::from(1),
-						ethereum::ReceiptV3::EIP1559(_) => U256::from(2),
-					},
-				}));
-			}
-			_ => Ok(None),
+					transaction_hash: Some(status.transaction_hash),
+					transaction_index: Some(status.transaction_index.into()),
+					log_index: Some(U256::from(log_index)),
+					transaction_log_index: Some(U256::from(i)),
+					removed: false,
+				};
+				log_index += 1;
+				log
+			})
+			.collect();
+
+		block_receipts.push(Receipt {
+			transaction_hash: Some(status.transaction_hash),
+			transaction_index: Some(status.transaction_index.into()),
+			block_hash: Some(block_hash),
+			from: Some(status.from),
+			to: status.to,
+			block_number: Some(block.header.number),
+			cumulative_gas_used,
+			gas_used: Some(gas_used),
+			contract_address: status.contract_address,
+			logs,
+			status_code: Some(U64::from(d.status_code)),
+			logs_bloom: d.logs_bloom,
+			state_root: None,
+			effective_gas_price,
+			transaction_type: U256::from(transaction_type),
+		});
+	}
+
+	Ok(block_receipts)
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum::{
+		EIP658ReceiptData, LegacyTransaction, PartialHeader, TransactionAction,
+		TransactionSignature,
+	};
+	use ethereum_types::{Bloom, H160, H64};
+
+	use super::*;
+
+	/// Block of three transactions, emitting one, two and one logs.
+	fn block() -> (
+		EthereumBlock,
+		Vec<TransactionStatus>,
+		Vec<ethereum::ReceiptV3>,
+	) {
+		let mut transactions = Vec::new();
+		let mut statuses = Vec::new();
+		let mut receipts = Vec::new();
+		for (index, (used_gas, logs)) in [(21_000u64, 1), (50_000, 2), (71_000, 1)]
+			.into_iter()
+			.enumerate()
+		{
+			transactions.push(EthereumTransaction::Legacy(LegacyTransaction {
+				nonce: index.into(),
+				gas_price: U256::one(),
+				gas_limit: U256::from(100_000),
+				action: TransactionAction::Call(H160::repeat_byte(1)),
+				value: U256::zero(),
+				input: Vec::new(),
+				signature: TransactionSignature::new(
+					27,
+					H256::repeat_byte(1),
+					H256::repeat_byte(1),
+				)
+				.unwrap(),
+			}));
+			statuses.push(TransactionStatus {
+				transaction_hash: H256::from_low_u64_be(index as u64),
+				transaction_index: index as u32,
+				..Default::default()
+			});
+			receipts.push(ethereum::ReceiptV3::Legacy(EIP658ReceiptData {
+				status_code: 1,
+				used_gas: used_gas.into(),
+				logs_bloom: Bloom::default(),
+				logs: vec![
+					ethereum::Log {
+						address: H160::repeat_byte(2),
+						topics: Vec::new(),
+						data: Vec::new(),
+					};
+					logs
+				],
+			}));
 		}
+		let header = PartialHeader {
+			parent_hash: H256::zero(),
+			beneficiary: H160::zero(),
+			state_root: H256::zero(),
+			receipts_root: H256::zero(),
+			logs_bloom: Bloom::default(),
+			difficulty: U256::zero(),
+			number: U256::one(),
+			gas_limit: U256::from(1_000_000),
+			gas_used: U256::from(71_000),
+			timestamp: 0,
+			extra_data: Vec::new(),
+			mix_hash: H256::zero(),
+			nonce: H64::zero(),
+		};
+		(
+			ethereum::Block::new(header, transactions, Vec::new()),
+			statuses,
+			receipts,
+		)
+	}
+
+	fn log_indexes(receipt: &Receipt) -> Vec<U256> {
+		receipt
+			.logs
+			.iter()
+			.filter_map(|log| log.log_index)
+			.collect()
+	}
+
+	#[test]
+	fn single_receipt_carries_gas_and_log_index_forward() {
+		let (block, statuses, receipts) = block();
+		let all = receipts_build(
+			&block,
+			statuses.clone(),
+			receipts.clone(),
+			true,
+			U256::zero(),
+			None,
+		)
+		.unwrap();
+		assert_eq!(all.len(), 3);
+		assert_eq!(log_indexes(&all[2]), vec![U256::from(3)]);
+
+		for (index, expected) in all.iter().enumerate() {
+			let only = receipts_build(
+				&block,
+				statuses.clone(),
+				receipts.clone(),
+				true,
+				U256::zero(),
+				Some(index),
+			)
+			.unwrap();
+			assert_eq!(only.len(), 1);
+			assert_eq!(only[0].transaction_hash, expected.transaction_hash);
+			assert_eq!(only[0].cumulative_gas_used, expected.cumulative_gas_used);
+			assert_eq!(only[0].gas_used, expected.gas_used);
+			assert_eq!(log_indexes(&only[0]), log_indexes(expected));
+		}
+
+		let past_the_end =
+			receipts_build(&block, statuses, receipts, true, U256::zero(), Some(3)).unwrap();
+		assert!(past_the_end.is_empty());
 	}
 }
@@ -32,7 +32,7 @@ use sc_network_common::ExHashT;
 use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::{ApiExt, BlockId, ProvideRuntimeApi};
-use sp_blockchain::HeaderBackend;
+use sp_blockchain::{HeaderBackend, TreeRoute};
 use sp_consensus::SyncOracle;
 use sp_core::hashing::keccak_256;
 use sp_runtime::traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto};
@@ -46,7 +46,12 @@ use fc_rpc_core::{
 };
 use fp_rpc::EthereumRuntimeRPCApi;
 
-use crate::{frontier_backend_client, overrides::OverrideHandle};
+use crate::{
+	eth::transaction_build,
+	frontier_backend_client,
+	metrics::{ActiveSubscription, EthMetrics},
+	overrides::OverrideHandle,
+};
 
 #[derive(Debug)]
 pub struct EthereumSubIdProvider;
@@ -65,6 +70,7 @@ pub struct EthPubSub<B: BlockT, P, C, BE, H: ExHashT> {
 	subscriptions: SubscriptionTaskExecutor,
 	overrides: Arc<OverrideHandle<B>>,
 	starting_block: u64,
+	metrics: Option<Arc<EthMetrics>>,
 	_marker: PhantomData<BE>,
 }
 
@@ -89,9 +95,15 @@ where
 			subscriptions,
 			overrides,
 			starting_block,
+			metrics: None,
 			_marker: PhantomData,
 		}
 	}
+
+	pub fn with_metrics(mut self, metrics: Option<Arc<EthMetrics>>) -> Self {
+		self.metrics = metrics;
+		self
+	}
 }
 
 struct EthSubscriptionResult;
@@ -120,10 +132,61 @@ impl EthSubscriptionResult {
 			extra_info: BTreeMap::new(),
 		}))
 	}
+
+	/// Logs of a block matching the subscription filter, read from the block state.
+	pub fn block_logs<B, C, BE>(
+		client: &C,
+		overrides: &OverrideHandle<B>,
+		hash: H256,
+		params: &FilteredParams,
+		removed: bool,
+	) -> Vec<Log>
+	where
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+	{
+		let id = BlockId::Hash(hash);
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+		let handler = overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&overrides.fallback);
+
+		match (handler.current_block(&id), handler.current_receipts(&id)) {
+			(Some(block), Some(receipts)) => Self::logs(block, receipts, params, removed),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Logs sent for a new best block. On a re-org, the logs of the retracted blocks are
+	/// re-sent as removed, newest block first, then the logs of the enacted blocks which were
+	/// imported before becoming part of the best chain.
+	fn best_block_logs<B: BlockT<Hash = H256>>(
+		tree_route: Option<&TreeRoute<B>>,
+		hash: H256,
+		block_logs: impl Fn(H256, bool) -> Vec<Log>,
+	) -> Vec<Log> {
+		let mut logs = Vec::new();
+		if let Some(tree_route) = tree_route {
+			for retracted in tree_route.retracted() {
+				logs.extend(block_logs(retracted.hash, true));
+			}
+			for enacted in tree_route.enacted() {
+				logs.extend(block_logs(enacted.hash, false));
+			}
+		}
+		logs.extend(block_logs(hash, false));
+		logs
+	}
+
 	pub fn logs(
 		block: EthereumBlock,
 		receipts: Vec<ethereum::ReceiptV3>,
 		params: &FilteredParams,
+		removed: bool,
 	) -> Vec<Log> {
 		let block_hash = Some(H256::from(keccak_256(&rlp::encode(&block.header))));
 		let mut logs: Vec<Log> = vec![];
@@ -152,7 +215,7 @@ impl EthSubscriptionResult {
 						transaction_index: Some(U256::from(receipt_index)),
 						log_index: Some(U256::from(log_index)),
 						transaction_log_index: Some(U256::from(transaction_log_index)),
-						removed: false,
+						removed,
 					});
 				}
 				log_index += 1;
@@ -210,8 +273,11 @@ where
 		kind: Kind,
 		params: Option<Params>,
 	) -> SubscriptionResult {
+		let _timer = crate::metrics::start_call(&self.metrics, "eth_subscribe");
 		sink.accept()?;
 
+		// Whether `newPendingTransactions` returns full transactions instead of hashes.
+		let full_transactions = matches!(params, Some(Params::Bool(true)));
 		let filtered_params = match params {
 			Some(Params::Logs(filter)) => FilteredParams::new(Some(filter)),
 			_ => FilteredParams::default(),
@@ -222,45 +288,34 @@ where
 		let network = self.network.clone();
 		let overrides = self.overrides.clone();
 		let starting_block = self.starting_block;
+		// Keeps the subscription counted as active until its task ends.
+		let active = self.metrics.clone().map(ActiveSubscription::new);
 		let fut = async move {
 			match kind {
 				Kind::Logs => {
 					let stream = client
 						.import_notification_stream()
 						.filter_map(move |notification| {
-							if notification.is_new_best {
-								let id = BlockId::Hash(notification.hash);
-
-								let schema = frontier_backend_client::onchain_storage_schema::<
-									B,
-									C,
-									BE,
-								>(client.as_ref(), id);
-								let handler = overrides
-									.schemas
-									.get(&schema)
-									.unwrap_or(&overrides.fallback);
-
-								let block = handler.current_block(&id);
-								let receipts = handler.current_receipts(&id);
-
-								match (receipts, block) {
-									(Some(receipts), Some(block)) => {
-										futures::future::ready(Some((block, receipts)))
-									}
-									_ => futures::future::ready(None),
-								}
-							} else {
-								futures::future::ready(None)
+							if !notification.is_new_best {
+								return futures::future::ready(None);
 							}
+
+							let block_logs = |hash: H256, removed: bool| {
+								EthSubscriptionResult::block_logs::<B, C, BE>(
+									client.as_ref(),
+									&overrides,
+									hash,
+									&filtered_params,
+									removed,
+								)
+							};
+							futures::future::ready(Some(EthSubscriptionResult::best_block_logs(
+								notification.tree_route.as_deref(),
+								notification.hash,
+								block_logs,
+							)))
 						})
-						.flat_map(move |(block, receipts)| {
-							futures::stream::iter(EthSubscriptionResult::logs(
-								block,
-								receipts,
-								&filtered_params,
-							))
-						})
+						.flat_map(futures::stream::iter)
 						.map(|x| PubSubResult::Log(Box::new(x)));
 					sink.pipe_from_stream(stream).await;
 				}
@@ -290,6 +345,38 @@ where
 						.map(EthSubscriptionResult::new_heads);
 					sink.pipe_from_stream(stream).await;
 				}
+				Kind::FinalizedHeads => {
+					let stream = client
+						.finality_notification_stream()
+						.filter_map(move |notification| {
+							// A finality notification can cover several blocks, which are all
+							// sent in ascending order, ending with the newly finalized block.
+							let blocks = notification
+								.tree_route
+								.iter()
+								.chain(std::iter::once(&notification.hash))
+								.filter_map(|hash| {
+									let id = BlockId::Hash(*hash);
+
+									let schema = frontier_backend_client::onchain_storage_schema::<
+										B,
+										C,
+										BE,
+									>(client.as_ref(), id);
+									let handler = overrides
+										.schemas
+										.get(&schema)
+										.unwrap_or(&overrides.fallback);
+
+									handler.current_block(&id)
+								})
+								.collect::<Vec<_>>();
+							futures::future::ready(Some(blocks))
+						})
+						.flat_map(futures::stream::iter)
+						.map(EthSubscriptionResult::new_heads);
+					sink.pipe_from_stream(stream).await;
+				}
 				Kind::NewPendingTransactions => {
 					use sc_transaction_pool_api::InPoolTransaction;
 
@@ -339,7 +426,18 @@ where
 								futures::future::ready(None)
 							}
 						})
-						.map(|transaction| PubSubResult::TransactionHash(transaction.hash()));
+						.map(move |transaction| {
+							if full_transactions {
+								PubSubResult::Transaction(Box::new(transaction_build(
+									transaction,
+									None,
+									None,
+									None,
+								)))
+							} else {
+								PubSubResult::TransactionHash(transaction.hash())
+							}
+						});
 					sink.pipe_from_stream(stream).await;
 				}
 				Kind::Syncing => {
@@ -408,8 +506,80 @@ where
 		self.subscriptions.spawn(
 			"frontier-rpc-subscription",
 			Some("rpc"),
-			fut.map(drop).boxed(),
+			fut.map(move |_| drop(active)).boxed(),
 		);
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor, StreamExt};
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::BlockOrigin;
+	use substrate_test_runtime_client::{
+		prelude::*, DefaultTestClientBuilderExt, TestClient, TestClientBuilder,
+	};
+
+	use super::*;
+
+	fn log(hash: H256, removed: bool) -> Log {
+		Log {
+			address: Default::default(),
+			topics: Vec::new(),
+			data: Bytes(Vec::new()),
+			block_hash: Some(hash),
+			block_number: None,
+			transaction_hash: None,
+			transaction_index: None,
+			log_index: None,
+			transaction_log_index: None,
+			removed,
+		}
+	}
+
+	fn import(client: &mut Arc<TestClient>, parent: H256, key: u8) -> H256 {
+		let mut builder = client
+			.new_block_at(&BlockId::Hash(parent), Default::default(), false)
+			.unwrap();
+		builder.push_storage_change(vec![key], None).unwrap();
+		let block = builder.build().unwrap().block;
+		let hash = block.header.hash();
+		executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		hash
+	}
+
+	#[test]
+	fn best_block_logs_retract_then_enact_on_reorg() {
+		let mut client = Arc::new(TestClientBuilder::new().build());
+
+		// G -> A1 -> A2 is the best chain, then G -> B1 -> B2 -> B3 takes over.
+		let genesis = client.info().genesis_hash;
+		let a1 = import(&mut client, genesis, 1);
+		let a2 = import(&mut client, a1, 1);
+		let b1 = import(&mut client, genesis, 2);
+		let b2 = import(&mut client, b1, 2);
+		let mut notifications = client.import_notification_stream();
+		let b3 = import(&mut client, b2, 2);
+
+		let notification = executor::block_on(notifications.next()).unwrap();
+		assert_eq!(notification.hash, b3);
+		assert!(notification.is_new_best);
+
+		let logs = EthSubscriptionResult::best_block_logs(
+			notification.tree_route.as_deref(),
+			notification.hash,
+			|hash, removed| vec![log(hash, removed)],
+		);
+		assert_eq!(
+			logs,
+			vec![
+				log(a2, true),
+				log(a1, true),
+				log(b1, false),
+				log(b2, false),
+				log(b3, false),
+			]
+		);
+	}
+}
@@ -25,27 +25,41 @@
 	clippy::new_without_default
 )]
 
+mod account_store;
+mod debug;
+mod eip712;
 mod eth;
 mod eth_pubsub;
+mod metrics;
 mod net;
 mod overrides;
+mod personal;
 mod signer;
+mod trace;
+mod txpool;
 mod web3;
 
 pub use self::{
+	account_store::EthAccountStore,
+	debug::Debug,
 	eth::{format, EstimateGasAdapter, Eth, EthBlockDataCacheTask, EthFilter, EthTask},
 	eth_pubsub::{EthPubSub, EthereumSubIdProvider},
+	metrics::EthMetrics,
 	net::Net,
 	overrides::{
 		OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override, SchemaV2Override,
 		SchemaV3Override, StorageOverride,
 	},
-	signer::{EthDevSigner, EthSigner},
+	personal::Personal,
+	signer::{EthDevSigner, EthKeystoreSigner, EthSigner, ETH_KEY_TYPE},
+	trace::Trace,
+	txpool::TxPool,
 	web3::Web3,
 };
 pub use ethereum::TransactionV2 as EthereumTransaction;
 pub use fc_rpc_core::{
-	EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer, Web3ApiServer,
+	DebugApiServer, EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer,
+	PersonalApiServer, TraceApiServer, TxPoolApiServer, Web3ApiServer,
 };
 
 pub mod frontier_backend_client {
@@ -59,17 +73,24 @@ pub mod frontier_backend_client {
 	use sp_blockchain::HeaderBackend;
 	use sp_runtime::{
 		generic::BlockId,
-		traits::{BlakeTwo256, Block as BlockT, Header as HeaderT, UniqueSaturatedInto, Zero},
+		traits::{
+			BlakeTwo256, Block as BlockT, Header as HeaderT, Saturating, UniqueSaturatedInto, Zero,
+		},
 	};
 	use sp_storage::StorageKey;
 	// Frontier
 	use fc_rpc_core::types::BlockNumber;
 	use fp_storage::{EthereumStorageSchema, PALLET_ETHEREUM_SCHEMA};
 
+	/// Resolve a block number to a Substrate block.
+	///
+	/// `safe_depth` is the number of confirmations of the `safe` block, which otherwise
+	/// is the finalized block. It never resolves below the finalized block.
 	pub fn native_block_id<B: BlockT, C>(
 		client: &C,
 		backend: &fc_db::Backend<B>,
 		number: Option<BlockNumber>,
+		safe_depth: Option<u32>,
 	) -> RpcResult<Option<BlockId<B>>>
 	where
 		B: BlockT<Hash = H256> + Send + Sync + 'static,
@@ -83,7 +104,15 @@ pub mod frontier_backend_client {
 			BlockNumber::Latest => Some(BlockId::Hash(client.info().best_hash)),
 			BlockNumber::Earliest => Some(BlockId::Number(Zero::zero())),
 			BlockNumber::Pending => None,
-			BlockNumber::Safe => Some(BlockId::Hash(client.info().finalized_hash)),
+			BlockNumber::Safe => {
+				let info = client.info();
+				match safe_depth.map(|depth| info.best_number.saturating_sub(depth.into())) {
+					Some(safe_number) if safe_number > info.finalized_number => {
+						Some(BlockId::Number(safe_number))
+					}
+					_ => Some(BlockId::Hash(info.finalized_hash)),
+				}
+			}
 			BlockNumber::Finalized => Some(BlockId::Hash(client.info().finalized_hash)),
 		})
 	}
@@ -235,6 +264,58 @@ pub fn internal_err_with_data<T: ToString>(message: T, data: &[u8]) -> jsonrpsee
 	)
 }
 
+/// Decodes the reason of a reverted execution, either the message of an `Error(string)`
+/// revert or the description of a `Panic(uint256)` code. Custom errors are not decoded.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+	// `Error(string)` selector.
+	const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+	// `Panic(uint256)` selector.
+	const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+	let word = |start: usize| {
+		data.get(start..start.checked_add(32)?)
+			.map(ethereum_types::U256::from_big_endian)
+	};
+	let to_usize = |value: ethereum_types::U256| {
+		if value > ethereum_types::U256::from(usize::MAX) {
+			None
+		} else {
+			Some(value.as_usize())
+		}
+	};
+
+	let selector = data.get(0..4)?;
+	if selector == ERROR_SELECTOR {
+		let offset = to_usize(word(4)?)?.checked_add(4)?;
+		let len = to_usize(word(offset)?)?;
+		let start = offset.checked_add(32)?;
+		let body = data.get(start..start.checked_add(len)?)?;
+		std::str::from_utf8(body)
+			.ok()
+			.map(|reason| reason.to_string())
+	} else if selector == PANIC_SELECTOR {
+		// Panic codes emitted by solc, see
+		// https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require
+		let code = word(4)?;
+		let reason = match to_usize(code) {
+			Some(0x00) => "generic panic",
+			Some(0x01) => "assert(false)",
+			Some(0x11) => "arithmetic underflow or overflow",
+			Some(0x12) => "division or modulo by zero",
+			Some(0x21) => "enum overflow",
+			Some(0x22) => "invalid encoded storage byte array accessed",
+			Some(0x31) => "out-of-bounds array access; popping on an empty array",
+			Some(0x32) => "out-of-bounds access of an array or bytesslice",
+			Some(0x41) => "out of memory",
+			Some(0x51) => "uninitialized function",
+			_ => return Some(format!("unknown panic code: {:#x}", code)),
+		};
+		Some(reason.to_string())
+	} else {
+		None
+	}
+}
+
 pub fn public_key(transaction: &EthereumTransaction) -> Result<[u8; 64], sp_io::EcdsaVerifyError> {
 	let mut sig = [0u8; 65];
 	let mut msg = [0u8; 32];
@@ -399,4 +480,95 @@ mod tests {
 			BlockId::Hash(b2_hash),
 		);
 	}
+
+	#[test]
+	fn safe_block_resolves_from_safe_depth() {
+		use fc_rpc_core::types::BlockNumber;
+
+		let tmp = tempdir().expect("create a temporary directory");
+		let (client, _) = TestClientBuilder::new()
+			.build_with_native_executor::<substrate_test_runtime_client::runtime::RuntimeApi, _>(
+			None,
+		);
+		let mut client = Arc::new(client);
+		let frontier_backend = open_frontier_backend(client.clone(), tmp.into_path()).unwrap();
+
+		// G -> A1 -> A2 -> A3 -> A4, A1 being finalized.
+		let mut hashes = vec![];
+		for _ in 0..4 {
+			let block = client
+				.new_block(Default::default())
+				.unwrap()
+				.build()
+				.unwrap()
+				.block;
+			hashes.push(block.header.hash());
+			executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		}
+		client.finalize_block(hashes[0], None).unwrap();
+
+		let safe = |safe_depth: Option<u32>| {
+			super::frontier_backend_client::native_block_id(
+				client.as_ref(),
+				frontier_backend.as_ref(),
+				Some(BlockNumber::Safe),
+				safe_depth,
+			)
+			.unwrap()
+			.unwrap()
+		};
+		// Without a depth, the safe block is the finalized one.
+		assert_eq!(safe(None), BlockId::Hash(hashes[0]));
+		assert_eq!(safe(Some(0)), BlockId::Number(4));
+		assert_eq!(safe(Some(1)), BlockId::Number(3));
+		// It never resolves below the finalized block.
+		assert_eq!(safe(Some(3)), BlockId::Hash(hashes[0]));
+		assert_eq!(safe(Some(10)), BlockId::Hash(hashes[0]));
+	}
+
+	#[test]
+	fn decode_revert_reason_works() {
+		use super::decode_revert_reason;
+		use ethereum_types::U256;
+
+		let word = |value: usize| {
+			let mut word = [0u8; 32];
+			U256::from(value).to_big_endian(&mut word);
+			word.to_vec()
+		};
+
+		// Error(string) with message "Not enough balance".
+		let message = b"Not enough balance";
+		let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+		data.extend(word(32));
+		data.extend(word(message.len()));
+		data.extend(message);
+		data.extend(vec![0u8; 32 - message.len()]);
+		assert_eq!(
+			decode_revert_reason(&data),
+			Some("Not enough balance".to_string())
+		);
+		// Truncated message.
+		assert_eq!(decode_revert_reason(&data[..80]), None);
+
+		// Panic(uint256) with arithmetic overflow code.
+		let mut data = vec![0x4e, 0x48, 0x7b, 0x71];
+		data.extend(word(0x11));
+		assert_eq!(
+			decode_revert_reason(&data),
+			Some("arithmetic underflow or overflow".to_string())
+		);
+		let mut data = vec![0x4e, 0x48, 0x7b, 0x71];
+		data.extend(word(0x99));
+		assert_eq!(
+			decode_revert_reason(&data),
+			Some("unknown panic code: 0x99".to_string())
+		);
+
+		// Custom error and empty revert.
+		let mut data = vec![0xde, 0xad, 0xbe, 0xef];
+		data.extend(word(1));
+		assert_eq!(decode_revert_reason(&data), None);
+		assert_eq!(decode_revert_reason(&[]), None);
+	}
 }
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use prometheus_endpoint::{
+	prometheus::HistogramTimer, register, Counter, CounterVec, Gauge, Histogram, HistogramOpts,
+	HistogramVec, Opts, PrometheusError, Registry, U64,
+};
+
+/// Prometheus metrics of the Ethereum RPC layer, shared by `Eth`, `EthFilter` and `EthPubSub`.
+pub struct EthMetrics {
+	calls: CounterVec<U64>,
+	call_time: HistogramVec,
+	pub(crate) estimate_gas_probes: Histogram,
+	pub(crate) active_filters: Gauge<U64>,
+	active_subscriptions: Gauge<U64>,
+	pub(crate) mapping_sync_lag: Gauge<U64>,
+}
+
+impl EthMetrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			calls: register(
+				CounterVec::new(
+					Opts::new(
+						"frontier_eth_rpc_calls_total",
+						"Number of calls of each Ethereum RPC method.",
+					),
+					&["method"],
+				)?,
+				registry,
+			)?,
+			call_time: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"frontier_eth_rpc_call_duration_seconds",
+						"Time spent answering each Ethereum RPC method.",
+					)
+					.buckets(vec![
+						0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+					]),
+					&["method"],
+				)?,
+				registry,
+			)?,
+			estimate_gas_probes: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"frontier_eth_estimate_gas_probes",
+						"Number of executions needed by an eth_estimateGas request.",
+					)
+					.buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0]),
+				)?,
+				registry,
+			)?,
+			active_filters: register(
+				Gauge::new(
+					"frontier_eth_active_filters",
+					"Number of filters installed in the filter pool.",
+				)?,
+				registry,
+			)?,
+			active_subscriptions: register(
+				Gauge::new(
+					"frontier_eth_active_subscriptions",
+					"Number of active eth_subscribe subscriptions.",
+				)?,
+				registry,
+			)?,
+			mapping_sync_lag: register(
+				Gauge::new(
+					"frontier_mapping_sync_lag",
+					"Number of blocks between the best block and the first canonical block missing from the mapping database.",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Count a call of `method` and time it until the returned timer is dropped.
+	pub(crate) fn start_call(&self, method: &str) -> HistogramTimer {
+		self.calls.with_label_values(&[method]).inc();
+		self.call_time.with_label_values(&[method]).start_timer()
+	}
+}
+
+/// Start timing a call of `method` if metrics are enabled.
+pub(crate) fn start_call(
+	metrics: &Option<Arc<EthMetrics>>,
+	method: &str,
+) -> Option<HistogramTimer> {
+	metrics.as_ref().map(|metrics| metrics.start_call(method))
+}
+
+/// Counts a subscription as active for as long as it is alive.
+pub(crate) struct ActiveSubscription(Arc<EthMetrics>);
+
+impl ActiveSubscription {
+	pub(crate) fn new(metrics: Arc<EthMetrics>) -> Self {
+		metrics.active_subscriptions.inc();
+		Self(metrics)
+	}
+}
+
+impl Drop for ActiveSubscription {
+	fn drop(&mut self) {
+		self.0.active_subscriptions.dec();
+	}
+}
+
+/// Metrics of a `LRUCacheByteLimited`.
+pub(crate) struct CacheMetrics {
+	pub(crate) hits: Counter<U64>,
+	pub(crate) miss: Counter<U64>,
+	pub(crate) size: Gauge<U64>,
+}
+
+impl CacheMetrics {
+	pub(crate) fn register(
+		cache_name: &'static str,
+		registry: &Registry,
+	) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			hits: register(
+				Counter::new(
+					format!("frontier_eth_{}_hits", cache_name),
+					format!("Hits of eth {} cache.", cache_name),
+				)?,
+				registry,
+			)?,
+			miss: register(
+				Counter::new(
+					format!("frontier_eth_{}_miss", cache_name),
+					format!("Misses of eth {} cache.", cache_name),
+				)?,
+				registry,
+			)?,
+			size: register(
+				Gauge::new(
+					format!("frontier_eth_{}_size", cache_name),
+					format!("Size of eth {} data cache.", cache_name),
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use ethereum_types::{H160, H256};
+use jsonrpsee::core::{async_trait, RpcResult as Result};
+// Substrate
+use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sc_network_common::ExHashT;
+use sc_rpc::DenyUnsafe;
+use sc_transaction_pool::ChainApi;
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT};
+// Frontier
+use fc_rpc_core::{
+	types::{Bytes, TransactionRequest},
+	PersonalApiServer,
+};
+use fp_rpc::{ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
+
+use crate::{
+	account_store::EthAccountStore,
+	eth::Eth,
+	internal_err,
+	signer::{self, EthSigner, SecretKeySigner},
+};
+
+/// Number of seconds an account stays unlocked when no duration is given.
+const DEFAULT_UNLOCK_DURATION: u64 = 300;
+
+/// Personal API implementation.
+///
+/// Every method is unsafe, transactions are signed and submitted through `Eth`.
+pub struct Personal<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA = ()> {
+	eth: Eth<B, C, P, CT, BE, H, A, EGA>,
+	accounts: EthAccountStore,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi, EGA> Personal<B, C, P, CT, BE, H, A, EGA> {
+	pub fn new(
+		eth: Eth<B, C, P, CT, BE, H, A, EGA>,
+		accounts: EthAccountStore,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self {
+			eth,
+			accounts,
+			deny_unsafe,
+		}
+	}
+
+	async fn signer(&self, address: H160, password: String) -> Result<Box<dyn EthSigner>> {
+		let accounts = self.accounts.clone();
+		let secret = derive_blocking(move || accounts.secret(&address, &password)).await?;
+		Ok(Box::new(SecretKeySigner(secret)))
+	}
+}
+
+/// Run a key file operation on the blocking thread pool, as deriving the encryption key
+/// of a key file takes hundreds of thousands of PBKDF2 rounds.
+async fn derive_blocking<T, F>(f: F) -> Result<T>
+where
+	T: Send + 'static,
+	F: FnOnce() -> std::result::Result<T, String> + Send + 'static,
+{
+	tokio::task::spawn_blocking(f)
+		.await
+		.map_err(|err| internal_err(format!("key file task failed: {:?}", err)))?
+		.map_err(internal_err)
+}
+
+#[async_trait]
+impl<B, C, P, CT, BE, H: ExHashT, A, EGA> PersonalApiServer for Personal<B, C, P, CT, BE, H, A, EGA>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: BlockBuilderApi<B> + ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	P: TransactionPool<Block = B> + Send + Sync + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+	A: ChainApi<Block = B> + 'static,
+	EGA: Send + Sync + 'static,
+{
+	async fn new_account(&self, password: String) -> Result<H160> {
+		self.deny_unsafe.check_if_safe()?;
+		let accounts = self.accounts.clone();
+		derive_blocking(move || accounts.new_account(&password)).await
+	}
+
+	async fn import_raw_key(&self, key: String, password: String) -> Result<H160> {
+		self.deny_unsafe.check_if_safe()?;
+		let key = hex::decode(key.strip_prefix("0x").unwrap_or(&key))
+			.map_err(|_| internal_err("invalid private key"))?;
+		let accounts = self.accounts.clone();
+		derive_blocking(move || accounts.import_raw_key(&key, &password)).await
+	}
+
+	fn list_accounts(&self) -> Result<Vec<H160>> {
+		self.deny_unsafe.check_if_safe()?;
+		self.accounts.addresses().map_err(internal_err)
+	}
+
+	async fn unlock_account(
+		&self,
+		address: H160,
+		password: String,
+		duration: Option<u64>,
+	) -> Result<bool> {
+		self.deny_unsafe.check_if_safe()?;
+		let duration = match duration.unwrap_or(DEFAULT_UNLOCK_DURATION) {
+			0 => None,
+			seconds => Some(Duration::from_secs(seconds)),
+		};
+		let accounts = self.accounts.clone();
+		derive_blocking(move || accounts.unlock(&address, &password, duration)).await?;
+		Ok(true)
+	}
+
+	async fn send_transaction(
+		&self,
+		request: TransactionRequest,
+		password: String,
+	) -> Result<H256> {
+		self.deny_unsafe.check_if_safe()?;
+		let from = request
+			.from
+			.ok_or_else(|| internal_err("missing from address"))?;
+		let signers = [self.signer(from, password).await?];
+		self.eth
+			.send_transaction_with_signers(request, &signers)
+			.await
+	}
+
+	async fn sign(&self, data: Bytes, address: H160, password: Option<String>) -> Result<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		let hash = signer::message_hash(&data);
+		match password {
+			Some(password) => {
+				signer::sign_hash(&[self.signer(address, password).await?], hash, &address)
+			}
+			None => self.eth.sign(address, data),
+		}
+	}
+}
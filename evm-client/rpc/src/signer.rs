@@ -20,22 +20,37 @@ use ethereum::TransactionV2 as EthereumTransaction;
 use ethereum_types::{H160, H256};
 use jsonrpsee::core::Error;
 // Substrate
-use sp_core::hashing::keccak_256;
+use sp_core::{crypto::KeyTypeId, hashing::keccak_256};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
 // Frontier
-use fc_rpc_core::types::TransactionMessage;
+use fc_rpc_core::types::{Bytes, TransactionMessage};
 
 use crate::internal_err;
 
+/// Key type of the Ethereum accounts held by the node keystore.
+pub const ETH_KEY_TYPE: KeyTypeId = KeyTypeId(*b"eths");
+
 /// A generic Ethereum signer.
 pub trait EthSigner: Send + Sync {
 	/// Available accounts from this signer.
 	fn accounts(&self) -> Vec<H160>;
+	/// Sign a 32 bytes hash using the given account, returning the `r`, `s` and
+	/// recovery id of the signature.
+	fn sign_hash(&self, hash: H256, address: &H160) -> Result<[u8; 65], Error>;
 	/// Sign a transaction message using the given account in message.
 	fn sign(
 		&self,
 		message: TransactionMessage,
 		address: &H160,
-	) -> Result<EthereumTransaction, Error>;
+	) -> Result<EthereumTransaction, Error> {
+		let hash = match &message {
+			TransactionMessage::Legacy(m) => m.hash(),
+			TransactionMessage::EIP2930(m) => m.hash(),
+			TransactionMessage::EIP1559(m) => m.hash(),
+		};
+		let signature = self.sign_hash(hash, address)?;
+		signed_transaction(message, signature)
+	}
 }
 
 pub struct EthDevSigner {
@@ -55,7 +70,7 @@ impl EthDevSigner {
 	}
 }
 
-fn secret_key_address(secret: &libsecp256k1::SecretKey) -> H160 {
+pub(crate) fn secret_key_address(secret: &libsecp256k1::SecretKey) -> H160 {
 	let public = libsecp256k1::PublicKey::from_secret_key(secret);
 	public_key_address(&public)
 }
@@ -71,93 +86,242 @@ impl EthSigner for EthDevSigner {
 		self.keys.iter().map(secret_key_address).collect()
 	}
 
-	fn sign(
-		&self,
-		message: TransactionMessage,
-		address: &H160,
-	) -> Result<EthereumTransaction, Error> {
-		let mut transaction = None;
-
-		for secret in &self.keys {
-			let key_address = secret_key_address(secret);
-
-			if &key_address == address {
-				match message {
-					TransactionMessage::Legacy(m) => {
-						let signing_message = libsecp256k1::Message::parse_slice(&m.hash()[..])
-							.map_err(|_| internal_err("invalid signing message"))?;
-						let (signature, recid) = libsecp256k1::sign(&signing_message, secret);
-						let v = match m.chain_id {
-							None => 27 + recid.serialize() as u64,
-							Some(chain_id) => 2 * chain_id + 35 + recid.serialize() as u64,
-						};
-						let rs = signature.serialize();
-						let r = H256::from_slice(&rs[0..32]);
-						let s = H256::from_slice(&rs[32..64]);
-						transaction =
-							Some(EthereumTransaction::Legacy(ethereum::LegacyTransaction {
-								nonce: m.nonce,
-								gas_price: m.gas_price,
-								gas_limit: m.gas_limit,
-								action: m.action,
-								value: m.value,
-								input: m.input,
-								signature: ethereum::TransactionSignature::new(v, r, s)
-									.ok_or_else(|| {
-										internal_err("signer generated invalid signature")
-									})?,
-							}));
-					}
-					TransactionMessage::EIP2930(m) => {
-						let signing_message = libsecp256k1::Message::parse_slice(&m.hash()[..])
-							.map_err(|_| internal_err("invalid signing message"))?;
-						let (signature, recid) = libsecp256k1::sign(&signing_message, secret);
-						let rs = signature.serialize();
-						let r = H256::from_slice(&rs[0..32]);
-						let s = H256::from_slice(&rs[32..64]);
-						transaction =
-							Some(EthereumTransaction::EIP2930(ethereum::EIP2930Transaction {
-								chain_id: m.chain_id,
-								nonce: m.nonce,
-								gas_price: m.gas_price,
-								gas_limit: m.gas_limit,
-								action: m.action,
-								value: m.value,
-								input: m.input.clone(),
-								access_list: m.access_list,
-								odd_y_parity: recid.serialize() != 0,
-								r,
-								s,
-							}));
-					}
-					TransactionMessage::EIP1559(m) => {
-						let signing_message = libsecp256k1::Message::parse_slice(&m.hash()[..])
-							.map_err(|_| internal_err("invalid signing message"))?;
-						let (signature, recid) = libsecp256k1::sign(&signing_message, secret);
-						let rs = signature.serialize();
-						let r = H256::from_slice(&rs[0..32]);
-						let s = H256::from_slice(&rs[32..64]);
-						transaction =
-							Some(EthereumTransaction::EIP1559(ethereum::EIP1559Transaction {
-								chain_id: m.chain_id,
-								nonce: m.nonce,
-								max_priority_fee_per_gas: m.max_priority_fee_per_gas,
-								max_fee_per_gas: m.max_fee_per_gas,
-								gas_limit: m.gas_limit,
-								action: m.action,
-								value: m.value,
-								input: m.input.clone(),
-								access_list: m.access_list,
-								odd_y_parity: recid.serialize() != 0,
-								r,
-								s,
-							}));
-					}
-				}
-				break;
-			}
+	fn sign_hash(&self, hash: H256, address: &H160) -> Result<[u8; 65], Error> {
+		let secret = self
+			.keys
+			.iter()
+			.find(|secret| &secret_key_address(secret) == address)
+			.ok_or_else(|| internal_err("signer not available"))?;
+		sign_with_secret(secret, hash)
+	}
+}
+
+/// A signer holding a single decrypted key, for one-off signatures.
+pub(crate) struct SecretKeySigner(pub libsecp256k1::SecretKey);
+
+impl EthSigner for SecretKeySigner {
+	fn accounts(&self) -> Vec<H160> {
+		vec![secret_key_address(&self.0)]
+	}
+
+	fn sign_hash(&self, hash: H256, address: &H160) -> Result<[u8; 65], Error> {
+		if &secret_key_address(&self.0) != address {
+			return Err(internal_err("signer not available"));
+		}
+		sign_with_secret(&self.0, hash)
+	}
+}
+
+/// Sign a 32 bytes hash with a secret key, returning the `r`, `s` and recovery id of
+/// the signature.
+pub(crate) fn sign_with_secret(
+	secret: &libsecp256k1::SecretKey,
+	hash: H256,
+) -> Result<[u8; 65], Error> {
+	let signing_message = libsecp256k1::Message::parse_slice(&hash[..])
+		.map_err(|_| internal_err("invalid signing message"))?;
+	let (signature, recid) = libsecp256k1::sign(&signing_message, secret);
+	let mut res = [0u8; 65];
+	res[0..64].copy_from_slice(&signature.serialize()[..]);
+	res[64] = recid.serialize();
+	Ok(res)
+}
+
+/// A signer using the secp256k1 keys of type [`ETH_KEY_TYPE`] held by the node keystore.
+///
+/// Keys are added to the keystore with `author_insertKey`, using the `eths` key type
+/// and the hex encoded private key as secret URI.
+pub struct EthKeystoreSigner {
+	keystore: SyncCryptoStorePtr,
+}
+
+impl EthKeystoreSigner {
+	pub fn new(keystore: SyncCryptoStorePtr) -> Self {
+		Self { keystore }
+	}
+
+	fn public_keys(&self) -> Vec<(H160, sp_core::ecdsa::Public)> {
+		SyncCryptoStore::ecdsa_public_keys(&*self.keystore, ETH_KEY_TYPE)
+			.into_iter()
+			.filter_map(|public| {
+				let key = libsecp256k1::PublicKey::parse_compressed(&public.0).ok()?;
+				Some((public_key_address(&key), public))
+			})
+			.collect()
+	}
+}
+
+impl EthSigner for EthKeystoreSigner {
+	fn accounts(&self) -> Vec<H160> {
+		self.public_keys()
+			.into_iter()
+			.map(|(address, _)| address)
+			.collect()
+	}
+
+	fn sign_hash(&self, hash: H256, address: &H160) -> Result<[u8; 65], Error> {
+		let public = self
+			.public_keys()
+			.into_iter()
+			.find_map(|(key_address, public)| (&key_address == address).then_some(public))
+			.ok_or_else(|| internal_err("signer not available"))?;
+
+		let signature = SyncCryptoStore::ecdsa_sign_prehashed(
+			&*self.keystore,
+			ETH_KEY_TYPE,
+			&public,
+			hash.as_fixed_bytes(),
+		)
+		.map_err(|err| internal_err(format!("keystore signing failed: {:?}", err)))?
+		.ok_or_else(|| internal_err("signer not available"))?;
+		Ok(signature.0)
+	}
+}
+
+/// Hash of data prefixed as an EIP-191 personal message, as signed by `eth_sign`.
+pub(crate) fn message_hash(data: &[u8]) -> H256 {
+	let mut message = format!("\x19Ethereum Signed Message:\n{}", data.len()).into_bytes();
+	message.extend_from_slice(data);
+	H256::from(keccak_256(&message))
+}
+
+/// Sign a hash with the signer holding the given account, returning the signature
+/// in the `r`, `s`, `v` layout of `eth_sign`.
+pub(crate) fn sign_hash(
+	signers: &[Box<dyn EthSigner>],
+	hash: H256,
+	address: &H160,
+) -> Result<Bytes, Error> {
+	let signer = signers
+		.iter()
+		.find(|signer| signer.accounts().contains(address))
+		.ok_or_else(|| internal_err("no signer available"))?;
+
+	let mut signature = signer.sign_hash(hash, address)?;
+	signature[64] += 27;
+	Ok(Bytes::new(signature.to_vec()))
+}
+
+/// Build a signed transaction from its message and the `r`, `s` and recovery id of
+/// its signature.
+fn signed_transaction(
+	message: TransactionMessage,
+	signature: [u8; 65],
+) -> Result<EthereumTransaction, Error> {
+	let r = H256::from_slice(&signature[0..32]);
+	let s = H256::from_slice(&signature[32..64]);
+	let recid = signature[64];
+
+	Ok(match message {
+		TransactionMessage::Legacy(m) => {
+			let v = match m.chain_id {
+				None => 27 + recid as u64,
+				Some(chain_id) => 2 * chain_id + 35 + recid as u64,
+			};
+			EthereumTransaction::Legacy(ethereum::LegacyTransaction {
+				nonce: m.nonce,
+				gas_price: m.gas_price,
+				gas_limit: m.gas_limit,
+				action: m.action,
+				value: m.value,
+				input: m.input,
+				signature: ethereum::TransactionSignature::new(v, r, s)
+					.ok_or_else(|| internal_err("signer generated invalid signature"))?,
+			})
+		}
+		TransactionMessage::EIP2930(m) => {
+			EthereumTransaction::EIP2930(ethereum::EIP2930Transaction {
+				chain_id: m.chain_id,
+				nonce: m.nonce,
+				gas_price: m.gas_price,
+				gas_limit: m.gas_limit,
+				action: m.action,
+				value: m.value,
+				input: m.input,
+				access_list: m.access_list,
+				odd_y_parity: recid != 0,
+				r,
+				s,
+			})
 		}
+		TransactionMessage::EIP1559(m) => {
+			EthereumTransaction::EIP1559(ethereum::EIP1559Transaction {
+				chain_id: m.chain_id,
+				nonce: m.nonce,
+				max_priority_fee_per_gas: m.max_priority_fee_per_gas,
+				max_fee_per_gas: m.max_fee_per_gas,
+				gas_limit: m.gas_limit,
+				action: m.action,
+				value: m.value,
+				input: m.input,
+				access_list: m.access_list,
+				odd_y_parity: recid != 0,
+				r,
+				s,
+			})
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use sc_keystore::LocalKeystore;
+
+	use super::*;
+
+	const DEV_SEED: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+	fn recover(hash: H256, signature: &[u8]) -> H160 {
+		let message = libsecp256k1::Message::parse_slice(&hash[..]).unwrap();
+		let rs = libsecp256k1::Signature::parse_standard_slice(&signature[..64]).unwrap();
+		// Both the raw recovery id and its `eth_sign` form, offset by 27, are accepted.
+		let recid = libsecp256k1::RecoveryId::parse(signature[64] % 27).unwrap();
+		public_key_address(&libsecp256k1::recover(&message, &rs, &recid).unwrap())
+	}
+
+	#[test]
+	fn message_hash_prefixes_eip191_message() {
+		assert_eq!(
+			message_hash(b"Hello World"),
+			"0xa1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+				.parse::<H256>()
+				.unwrap()
+		);
+	}
+
+	#[test]
+	fn keystore_signer_signs_with_keystore_keys() {
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		let signer = EthKeystoreSigner::new(keystore.clone());
+		assert!(signer.accounts().is_empty());
+
+		SyncCryptoStore::ecdsa_generate_new(&*keystore, ETH_KEY_TYPE, Some(DEV_SEED)).unwrap();
+		// Keys of other types are not Ethereum accounts.
+		SyncCryptoStore::ecdsa_generate_new(&*keystore, KeyTypeId(*b"test"), None).unwrap();
+
+		let accounts = signer.accounts();
+		assert_eq!(accounts, EthDevSigner::new().accounts());
+
+		let hash = message_hash(b"keystore");
+		let signature = signer.sign_hash(hash, &accounts[0]).unwrap();
+		assert_eq!(recover(hash, &signature), accounts[0]);
+
+		assert!(signer.sign_hash(hash, &H160::repeat_byte(1)).is_err());
+	}
+
+	#[test]
+	fn sign_hash_returns_eth_sign_signature() {
+		let signers: Vec<Box<dyn EthSigner>> = vec![Box::new(EthDevSigner::new())];
+		let address = signers[0].accounts()[0];
+		let hash = message_hash(b"Hello World");
+
+		let signature = sign_hash(&signers, hash, &address).unwrap().into_vec();
+		assert_eq!(signature.len(), 65);
+		assert!(signature[64] == 27 || signature[64] == 28);
+		assert_eq!(recover(hash, &signature), address);
 
-		transaction.ok_or_else(|| internal_err("signer not available"))
+		assert!(sign_hash(&signers, hash, &H160::repeat_byte(1)).is_err());
 	}
 }
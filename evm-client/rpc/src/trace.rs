@@ -0,0 +1,569 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H256, U256};
+use evm::{ExitError, ExitReason};
+use jsonrpsee::core::{async_trait, RpcResult as Result};
+// Substrate
+use sc_client_api::{
+	backend::{Backend, StateBackend, StorageProvider},
+	BlockBackend,
+};
+use sc_rpc::DenyUnsafe;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto},
+};
+// Frontier
+use fc_rpc_core::{types::*, TraceApiServer};
+use fp_evm::tracing::{CallTrace, CallType as TracedCallType};
+use fp_rpc::{DebugRuntimeApi, EthereumRuntimeRPCApi};
+
+use crate::{
+	debug::{replay_api, transaction_block},
+	frontier_backend_client, internal_err, EthBlockDataCacheTask,
+};
+
+/// Trace API implementation.
+///
+/// Every method is unsafe, as replaying blocks is expensive.
+pub struct Trace<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	block_data_cache: Arc<EthBlockDataCacheTask<B>>,
+	max_block_range: u32,
+	deny_unsafe: DenyUnsafe,
+	safe_depth: Option<u32>,
+	_marker: PhantomData<BE>,
+}
+
+impl<B: BlockT, C, BE> Trace<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		block_data_cache: Arc<EthBlockDataCacheTask<B>>,
+		max_block_range: u32,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			block_data_cache,
+			max_block_range,
+			deny_unsafe,
+			safe_depth: None,
+			_marker: PhantomData,
+		}
+	}
+
+	pub fn with_safe_depth(mut self, safe_depth: Option<u32>) -> Self {
+		self.safe_depth = safe_depth;
+		self
+	}
+}
+
+impl<B, C, BE> Trace<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + BlockBackend<B> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: DebugRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	/// Returns the Ethereum hash and number of a block.
+	async fn ethereum_block(&self, id: BlockId<B>) -> Result<Option<(H256, U256)>> {
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+		let schema =
+			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+
+		Ok(self
+			.block_data_cache
+			.current_block(schema, substrate_hash)
+			.await
+			.map(|block| (block.header.hash(), block.header.number)))
+	}
+
+	async fn block_traces(&self, id: BlockId<B>) -> Result<Option<Vec<LocalizedTrace>>> {
+		let (block_hash, block_number) = match self.ethereum_block(id).await? {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+
+		let (api, parent_id, extrinsics) = replay_api::<B, C>(self.client.as_ref(), id)?;
+		let call_trees = api
+			.trace_block(&parent_id, extrinsics)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		let mut traces = Vec::new();
+		for (transaction_position, (transaction_hash, root)) in call_trees.into_iter().enumerate() {
+			let location = Location {
+				transaction_hash,
+				transaction_position,
+				block_hash,
+				block_number,
+			};
+			flatten(root, Vec::new(), &location, &mut traces);
+		}
+		Ok(Some(traces))
+	}
+
+	async fn transaction_traces(
+		&self,
+		transaction_hash: H256,
+	) -> Result<Option<Vec<LocalizedTrace>>> {
+		let (id, transaction_position) = match transaction_block::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			transaction_hash,
+		)? {
+			Some(found) => found,
+			None => return Ok(None),
+		};
+		let (block_hash, block_number) = match self.ethereum_block(id).await? {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+
+		let (api, parent_id, extrinsics) = replay_api::<B, C>(self.client.as_ref(), id)?;
+		let root = match api
+			.trace_transaction(
+				&parent_id,
+				extrinsics,
+				transaction_hash,
+				fp_evm::tracing::TraceType::CallTree,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?
+		{
+			fp_evm::tracing::TransactionTrace::CallTree(root) => root,
+			_ => return Err(internal_err("unexpected trace type")),
+		};
+
+		let location = Location {
+			transaction_hash,
+			transaction_position,
+			block_hash,
+			block_number,
+		};
+		let mut traces = Vec::new();
+		flatten(root, Vec::new(), &location, &mut traces);
+		Ok(Some(traces))
+	}
+}
+
+#[async_trait]
+impl<B, C, BE> TraceApiServer for Trace<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + BlockBackend<B> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: DebugRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	async fn trace_block(&self, number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+		self.deny_unsafe.check_if_safe()?;
+		let id = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+			self.safe_depth,
+		)? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+		self.block_traces(id).await
+	}
+
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+	) -> Result<Option<Vec<LocalizedTrace>>> {
+		self.deny_unsafe.check_if_safe()?;
+		self.transaction_traces(transaction_hash).await
+	}
+
+	async fn trace_filter(&self, filter: TraceFilter) -> Result<Vec<LocalizedTrace>> {
+		self.deny_unsafe.check_if_safe()?;
+		let best_number: u64 = self.client.info().best_number.unique_saturated_into();
+		let block_number = |number: Option<BlockNumber>| -> Result<u64> {
+			let id = frontier_backend_client::native_block_id::<B, C>(
+				self.client.as_ref(),
+				self.backend.as_ref(),
+				number,
+				self.safe_depth,
+			)?;
+			Ok(match id {
+				Some(id) => self
+					.client
+					.block_number_from_id(&id)
+					.map_err(|err| internal_err(format!("{:?}", err)))?
+					.map(UniqueSaturatedInto::unique_saturated_into)
+					.unwrap_or(best_number),
+				None => best_number,
+			})
+		};
+		let from_number = block_number(filter.from_block)?;
+		let to_number = block_number(filter.to_block)?.min(best_number);
+
+		if from_number > to_number {
+			return Err(internal_err("fromBlock cannot be greater than toBlock"));
+		}
+		if to_number - from_number >= self.max_block_range as u64 {
+			return Err(internal_err(format!(
+				"block range is too wide (maximum {})",
+				self.max_block_range
+			)));
+		}
+
+		let mut skip = filter.after.unwrap_or(0);
+		let count = filter.count.unwrap_or(usize::MAX);
+		let mut ret = Vec::new();
+		for number in from_number..=to_number {
+			let substrate_hash = match self
+				.client
+				.hash(number.unique_saturated_into())
+				.map_err(|err| internal_err(format!("{:?}", err)))?
+			{
+				Some(hash) => hash,
+				None => break,
+			};
+			// Blocks not yet indexed by the mapping sync worker carry no Ethereum data.
+			if !self
+				.backend
+				.mapping()
+				.is_synced(&substrate_hash)
+				.map_err(internal_err)?
+			{
+				continue;
+			}
+
+			let traces = self
+				.block_traces(BlockId::Hash(substrate_hash))
+				.await?
+				.unwrap_or_default();
+			for trace in traces {
+				let (from, to) = match &trace.action {
+					Action::Call(action) => (action.from, action.to),
+					Action::Create(action) => (
+						action.from,
+						match &trace.result {
+							Some(ActionResult::Create { address, .. }) => *address,
+							_ => Default::default(),
+						},
+					),
+					Action::Suicide(action) => (action.address, action.refund_address),
+				};
+				if !filter.matches(&from, &to) {
+					continue;
+				}
+				if skip > 0 {
+					skip -= 1;
+					continue;
+				}
+				ret.push(trace);
+				if ret.len() >= count {
+					return Ok(ret);
+				}
+			}
+		}
+		Ok(ret)
+	}
+}
+
+struct Location {
+	transaction_hash: H256,
+	transaction_position: usize,
+	block_hash: H256,
+	block_number: U256,
+}
+
+/// Flattens a call tree in depth-first order.
+fn flatten(
+	trace: CallTrace,
+	trace_address: Vec<usize>,
+	location: &Location,
+	traces: &mut Vec<LocalizedTrace>,
+) {
+	let CallTrace {
+		call_type,
+		from,
+		to,
+		value,
+		gas,
+		gas_used,
+		input,
+		output,
+		exit_reason,
+		calls,
+	} = trace;
+
+	let error = match &exit_reason {
+		Some(ExitReason::Revert(_)) => Some("Reverted".to_string()),
+		Some(ExitReason::Error(ExitError::OutOfGas)) => Some("Out of gas".to_string()),
+		Some(ExitReason::Error(err)) => Some(format!("{:?}", err)),
+		Some(ExitReason::Fatal(err)) => Some(format!("{:?}", err)),
+		Some(ExitReason::Succeed(_)) | None => None,
+	};
+	let call_action = |call_type| {
+		Action::Call(CallAction {
+			call_type,
+			from,
+			to,
+			value,
+			gas,
+			input: Bytes(input.clone()),
+		})
+	};
+	let (action, result) = match call_type {
+		TracedCallType::Call => (
+			call_action(CallType::Call),
+			ActionResult::Call {
+				gas_used,
+				output: Bytes(output),
+			},
+		),
+		TracedCallType::CallCode => (
+			call_action(CallType::CallCode),
+			ActionResult::Call {
+				gas_used,
+				output: Bytes(output),
+			},
+		),
+		TracedCallType::DelegateCall => (
+			call_action(CallType::DelegateCall),
+			ActionResult::Call {
+				gas_used,
+				output: Bytes(output),
+			},
+		),
+		TracedCallType::StaticCall => (
+			call_action(CallType::StaticCall),
+			ActionResult::Call {
+				gas_used,
+				output: Bytes(output),
+			},
+		),
+		TracedCallType::Create | TracedCallType::Create2 => (
+			Action::Create(CreateAction {
+				from,
+				value,
+				gas,
+				init: Bytes(input.clone()),
+			}),
+			ActionResult::Create {
+				gas_used,
+				code: Bytes(output),
+				address: to,
+			},
+		),
+		TracedCallType::SelfDestruct => {
+			traces.push(LocalizedTrace {
+				action: Action::Suicide(SuicideAction {
+					address: from,
+					refund_address: to,
+					balance: value,
+				}),
+				result: None,
+				error: None,
+				subtraces: 0,
+				trace_address,
+				transaction_hash: location.transaction_hash,
+				transaction_position: location.transaction_position,
+				block_hash: location.block_hash,
+				block_number: location.block_number,
+				action_type: ActionType::Suicide,
+			});
+			return;
+		}
+	};
+
+	traces.push(LocalizedTrace {
+		action_type: action.action_type(),
+		action,
+		result: match error {
+			Some(_) => None,
+			None => Some(result),
+		},
+		error,
+		subtraces: calls.len(),
+		trace_address: trace_address.clone(),
+		transaction_hash: location.transaction_hash,
+		transaction_position: location.transaction_position,
+		block_hash: location.block_hash,
+		block_number: location.block_number,
+	});
+	for (index, call) in calls.into_iter().enumerate() {
+		let mut sub_address = trace_address.clone();
+		sub_address.push(index);
+		flatten(call, sub_address, location, traces);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H160;
+	use evm::{ExitRevert, ExitSucceed};
+
+	use super::*;
+
+	fn location() -> Location {
+		Location {
+			transaction_hash: H256::repeat_byte(1),
+			transaction_position: 2,
+			block_hash: H256::repeat_byte(3),
+			block_number: U256::from(4),
+		}
+	}
+
+	fn call(
+		call_type: TracedCallType,
+		exit_reason: ExitReason,
+		calls: Vec<CallTrace>,
+	) -> CallTrace {
+		CallTrace {
+			call_type,
+			from: H160::repeat_byte(0xaa),
+			to: H160::repeat_byte(0xbb),
+			value: U256::from(5),
+			gas: U256::from(100_000),
+			gas_used: U256::from(21_000),
+			input: vec![1, 2],
+			output: vec![3, 4],
+			exit_reason: Some(exit_reason),
+			calls,
+		}
+	}
+
+	fn flattened(trace: CallTrace) -> Vec<LocalizedTrace> {
+		let mut traces = Vec::new();
+		flatten(trace, Vec::new(), &location(), &mut traces);
+		traces
+	}
+
+	#[test]
+	fn flatten_addresses_calls_depth_first() {
+		let succeed = ExitReason::Succeed(ExitSucceed::Returned);
+		let nested = call(TracedCallType::StaticCall, succeed.clone(), Vec::new());
+		let first = call(TracedCallType::Call, succeed.clone(), vec![nested]);
+		let second = call(TracedCallType::DelegateCall, succeed.clone(), Vec::new());
+		let traces = flattened(call(TracedCallType::Call, succeed, vec![first, second]));
+
+		let addresses: Vec<_> = traces
+			.iter()
+			.map(|trace| (trace.trace_address.clone(), trace.subtraces))
+			.collect();
+		assert_eq!(
+			addresses,
+			vec![(vec![], 2), (vec![0], 1), (vec![0, 0], 0), (vec![1], 0)]
+		);
+		assert!(matches!(
+			&traces[2].action,
+			Action::Call(CallAction {
+				call_type: CallType::StaticCall,
+				..
+			})
+		));
+		assert!(traces.iter().all(|trace| trace.transaction_position == 2
+			&& trace.block_number == U256::from(4)
+			&& trace.error.is_none()));
+		assert_eq!(
+			traces[0].result,
+			Some(ActionResult::Call {
+				gas_used: U256::from(21_000),
+				output: Bytes(vec![3, 4]),
+			})
+		);
+	}
+
+	#[test]
+	fn flatten_reports_failed_calls_without_result() {
+		let reverted = call(
+			TracedCallType::Call,
+			ExitReason::Revert(ExitRevert::Reverted),
+			Vec::new(),
+		);
+		let out_of_gas = call(
+			TracedCallType::Call,
+			ExitReason::Error(ExitError::OutOfGas),
+			Vec::new(),
+		);
+		let traces = flattened(call(
+			TracedCallType::Call,
+			ExitReason::Succeed(ExitSucceed::Stopped),
+			vec![reverted, out_of_gas],
+		));
+
+		assert_eq!(traces[0].error, None);
+		assert_eq!(traces[1].error.as_deref(), Some("Reverted"));
+		assert_eq!(traces[1].result, None);
+		assert_eq!(traces[2].error.as_deref(), Some("Out of gas"));
+		assert_eq!(traces[2].result, None);
+	}
+
+	#[test]
+	fn flatten_maps_creates_and_self_destructs() {
+		let self_destruct = call(
+			TracedCallType::SelfDestruct,
+			ExitReason::Succeed(ExitSucceed::Suicided),
+			Vec::new(),
+		);
+		let traces = flattened(call(
+			TracedCallType::Create2,
+			ExitReason::Succeed(ExitSucceed::Returned),
+			vec![self_destruct],
+		));
+
+		assert_eq!(
+			traces[0].action,
+			Action::Create(CreateAction {
+				from: H160::repeat_byte(0xaa),
+				value: U256::from(5),
+				gas: U256::from(100_000),
+				init: Bytes(vec![1, 2]),
+			})
+		);
+		assert_eq!(
+			traces[0].result,
+			Some(ActionResult::Create {
+				gas_used: U256::from(21_000),
+				code: Bytes(vec![3, 4]),
+				address: H160::repeat_byte(0xbb),
+			})
+		);
+		assert_eq!(
+			traces[1].action,
+			Action::Suicide(SuicideAction {
+				address: H160::repeat_byte(0xaa),
+				refund_address: H160::repeat_byte(0xbb),
+				balance: U256::from(5),
+			})
+		);
+		assert_eq!(traces[1].trace_address, vec![0]);
+		assert_eq!(traces[1].result, None);
+	}
+}
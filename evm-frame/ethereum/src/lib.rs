@@ -891,9 +891,9 @@ impl From<InvalidEvmTransactionError> for InvalidTransactionWrapper {
 			InvalidEvmTransactionError::GasLimitTooHigh => InvalidTransactionWrapper(
 				InvalidTransaction::Custom(TransactionValidationError::GasLimitTooHigh as u8),
 			),
-			InvalidEvmTransactionError::GasPriceTooLow => {
-				InvalidTransactionWrapper(InvalidTransaction::Payment)
-			}
+			InvalidEvmTransactionError::GasPriceTooLow => InvalidTransactionWrapper(
+				InvalidTransaction::Custom(TransactionValidationError::GasPriceTooLow as u8),
+			),
 			InvalidEvmTransactionError::PriorityFeeTooHigh => InvalidTransactionWrapper(
 				InvalidTransaction::Custom(TransactionValidationError::MaxFeePerGasTooLow as u8),
 			),
@@ -906,12 +906,15 @@ impl From<InvalidEvmTransactionError> for InvalidTransactionWrapper {
 			InvalidEvmTransactionError::TxNonceTooHigh => {
 				InvalidTransactionWrapper(InvalidTransaction::Future)
 			}
-			InvalidEvmTransactionError::InvalidPaymentInput => {
-				InvalidTransactionWrapper(InvalidTransaction::Payment)
-			}
+			InvalidEvmTransactionError::InvalidPaymentInput => InvalidTransactionWrapper(
+				InvalidTransaction::Custom(TransactionValidationError::InvalidFeeInput as u8),
+			),
 			InvalidEvmTransactionError::InvalidChainId => InvalidTransactionWrapper(
 				InvalidTransaction::Custom(TransactionValidationError::InvalidChainId as u8),
 			),
+			InvalidEvmTransactionError::InitcodeTooLarge => InvalidTransactionWrapper(
+				InvalidTransaction::Custom(TransactionValidationError::InitcodeTooLarge as u8),
+			),
 		}
 	}
 }
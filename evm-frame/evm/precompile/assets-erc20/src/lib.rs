@@ -0,0 +1,469 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ERC-20 precompiles over the assets of `pallet_assets`.
+//!
+//! Every asset is exposed at an address derived from its id through [`AddressToAssetId`],
+//! implementing the ERC-20 interface, its metadata extension and the EIP-2612 `permit`.
+//! Allowances are the approvals of `pallet_assets`; an `approve` replaces the previous one.
+//! The permit nonces live in the storage of this crate's pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use alloc::format;
+use core::marker::PhantomData;
+use fp_evm::{PrecompileFailure, PrecompileHandle, PrecompileResult, PrecompileSet};
+use frame_support::traits::{
+	fungibles::{approvals, metadata, Inspect, Transfer},
+	Get,
+};
+use frame_system::RawOrigin;
+use hex_literal::hex;
+use pallet_assets::WeightInfo as _;
+use pallet_evm::AddressMapping;
+use precompile_utils::{
+	log3, revert, Bytes, EvmDataWriter, EvmResult, LogExt, PrecompileHandleExt,
+};
+use sp_core::{H160, H256, U256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{
+	traits::{Bounded, StaticLookup, UniqueSaturatedInto, Zero},
+	DispatchError,
+};
+
+pub use pallet::*;
+
+/// Topic of the `Transfer(address,address,uint256)` event.
+pub const SELECTOR_LOG_TRANSFER: [u8; 32] =
+	hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Topic of the `Approval(address,address,uint256)` event.
+pub const SELECTOR_LOG_APPROVAL: [u8; 32] =
+	hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+/// `keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)")`
+pub const PERMIT_TYPEHASH: [u8; 32] =
+	hex!("6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9");
+
+/// `keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")`
+pub const EIP712_DOMAIN_TYPEHASH: [u8; 32] =
+	hex!("8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f");
+
+/// `keccak256("1")`, the version of the permit domain.
+pub const PERMIT_VERSION_HASH: [u8; 32] =
+	hex!("c89efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bc6");
+
+/// Mapping between precompile addresses and asset ids.
+pub trait AddressToAssetId<AssetId> {
+	/// Asset exposed at `address`, if it is an asset precompile address.
+	fn address_to_asset_id(address: H160) -> Option<AssetId>;
+
+	/// Address of the precompile of `asset_id`.
+	fn asset_id_to_address(asset_id: AssetId) -> H160;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use sp_core::{H160, U256};
+
+	/// Holds the permit nonces of [`super::Erc20AssetsPrecompileSet`], which has no calls.
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: frame_system::Config + pallet_assets::Config<I> {}
+
+	/// EIP-2612 nonces of the permit signers, by asset.
+	#[pallet::storage]
+	pub type Nonces<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		<T as pallet_assets::Config<I>>::AssetId,
+		Blake2_128Concat,
+		H160,
+		U256,
+		ValueQuery,
+	>;
+}
+
+type Assets<Runtime, Instance> = pallet_assets::Pallet<Runtime, Instance>;
+type AssetIdOf<Runtime, Instance> = <Runtime as pallet_assets::Config<Instance>>::AssetId;
+type BalanceOf<Runtime, Instance> = <Runtime as pallet_assets::Config<Instance>>::Balance;
+
+/// ERC-20 precompiles of the assets of a `pallet_assets` instance.
+pub struct Erc20AssetsPrecompileSet<Runtime, Instance: 'static = ()>(
+	PhantomData<(Runtime, Instance)>,
+);
+
+impl<Runtime, Instance> Erc20AssetsPrecompileSet<Runtime, Instance> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<Runtime, Instance> Default for Erc20AssetsPrecompileSet<Runtime, Instance> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Runtime, Instance> PrecompileSet for Erc20AssetsPrecompileSet<Runtime, Instance>
+where
+	Runtime: Config<Instance>
+		+ pallet_evm::Config
+		+ pallet_timestamp::Config
+		+ AddressToAssetId<AssetIdOf<Runtime, Instance>>,
+	Instance: 'static,
+{
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		let asset_id = Runtime::address_to_asset_id(handle.code_address())?;
+		if !Assets::<Runtime, Instance>::asset_exists(asset_id) {
+			return None;
+		}
+		Some(Self::dispatch(handle))
+	}
+
+	fn is_precompile(&self, address: H160) -> bool {
+		Runtime::address_to_asset_id(address)
+			.map_or(false, Assets::<Runtime, Instance>::asset_exists)
+	}
+}
+
+#[precompile_utils::precompile(precompile_set)]
+impl<Runtime, Instance> Erc20AssetsPrecompileSet<Runtime, Instance>
+where
+	Runtime: Config<Instance>
+		+ pallet_evm::Config
+		+ pallet_timestamp::Config
+		+ AddressToAssetId<AssetIdOf<Runtime, Instance>>,
+	Instance: 'static,
+{
+	#[precompile::public("totalSupply()")]
+	#[precompile::view]
+	fn total_supply(handle: &mut impl PrecompileHandle) -> EvmResult<U256> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		let supply = Assets::<Runtime, Instance>::total_issuance(asset_id);
+		Ok(Self::balance_to_u256(supply))
+	}
+
+	#[precompile::public("balanceOf(address)")]
+	#[precompile::view]
+	fn balance_of(handle: &mut impl PrecompileHandle, owner: H160) -> EvmResult<U256> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		let owner = Runtime::AddressMapping::into_account_id(owner);
+		let balance = Assets::<Runtime, Instance>::balance(asset_id, &owner);
+		Ok(Self::balance_to_u256(balance))
+	}
+
+	#[precompile::public("allowance(address,address)")]
+	#[precompile::view]
+	fn allowance(
+		handle: &mut impl PrecompileHandle,
+		owner: H160,
+		spender: H160,
+	) -> EvmResult<U256> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		let owner = Runtime::AddressMapping::into_account_id(owner);
+		let spender = Runtime::AddressMapping::into_account_id(spender);
+		let allowance = <Assets<Runtime, Instance> as approvals::Inspect<_>>::allowance(
+			asset_id, &owner, &spender,
+		);
+		Ok(Self::balance_to_u256(allowance))
+	}
+
+	#[precompile::public("transfer(address,uint256)")]
+	fn transfer(handle: &mut impl PrecompileHandle, to: H160, value: U256) -> EvmResult<bool> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_weight::<Runtime>(
+			<Runtime as pallet_assets::Config<Instance>>::WeightInfo::transfer(),
+		)?;
+
+		let from = handle.context().caller;
+		<Assets<Runtime, Instance> as Transfer<_>>::transfer(
+			asset_id,
+			&Runtime::AddressMapping::into_account_id(from),
+			&Runtime::AddressMapping::into_account_id(to),
+			Self::balance_from_u256(value)?,
+			false,
+		)
+		.map_err(dispatch_revert)?;
+
+		Self::log(handle, SELECTOR_LOG_TRANSFER, from, to, value)?;
+		Ok(true)
+	}
+
+	#[precompile::public("approve(address,uint256)")]
+	fn approve(handle: &mut impl PrecompileHandle, spender: H160, value: U256) -> EvmResult<bool> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_weight::<Runtime>(
+			<Runtime as pallet_assets::Config<Instance>>::WeightInfo::cancel_approval()
+				.saturating_add(
+					<Runtime as pallet_assets::Config<Instance>>::WeightInfo::approve_transfer(),
+				),
+		)?;
+
+		let owner = handle.context().caller;
+		Self::set_allowance(asset_id, owner, spender, value)?;
+
+		Self::log(handle, SELECTOR_LOG_APPROVAL, owner, spender, value)?;
+		Ok(true)
+	}
+
+	#[precompile::public("transferFrom(address,address,uint256)")]
+	fn transfer_from(
+		handle: &mut impl PrecompileHandle,
+		from: H160,
+		to: H160,
+		value: U256,
+	) -> EvmResult<bool> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_weight::<Runtime>(
+			<Runtime as pallet_assets::Config<Instance>>::WeightInfo::transfer_approved(),
+		)?;
+
+		let caller = handle.context().caller;
+		let amount = Self::balance_from_u256(value)?;
+		let owner = Runtime::AddressMapping::into_account_id(from);
+		let dest = Runtime::AddressMapping::into_account_id(to);
+		// Moving one's own tokens needs no allowance.
+		if caller == from {
+			<Assets<Runtime, Instance> as Transfer<_>>::transfer(
+				asset_id, &owner, &dest, amount, false,
+			)
+			.map(|_| ())
+		} else {
+			<Assets<Runtime, Instance> as approvals::Mutate<_>>::transfer_from(
+				asset_id,
+				&owner,
+				&Runtime::AddressMapping::into_account_id(caller),
+				&dest,
+				amount,
+			)
+		}
+		.map_err(dispatch_revert)?;
+
+		Self::log(handle, SELECTOR_LOG_TRANSFER, from, to, value)?;
+		Ok(true)
+	}
+
+	#[precompile::public("name()")]
+	#[precompile::view]
+	fn name(handle: &mut impl PrecompileHandle) -> EvmResult<Bytes> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(<Assets<Runtime, Instance> as metadata::Inspect<_>>::name(asset_id).into())
+	}
+
+	#[precompile::public("symbol()")]
+	#[precompile::view]
+	fn symbol(handle: &mut impl PrecompileHandle) -> EvmResult<Bytes> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(<Assets<Runtime, Instance> as metadata::Inspect<_>>::symbol(asset_id).into())
+	}
+
+	#[precompile::public("decimals()")]
+	#[precompile::view]
+	fn decimals(handle: &mut impl PrecompileHandle) -> EvmResult<u8> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(<Assets<Runtime, Instance> as metadata::Inspect<_>>::decimals(asset_id))
+	}
+
+	#[precompile::public("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)")]
+	#[allow(clippy::too_many_arguments)]
+	fn permit(
+		handle: &mut impl PrecompileHandle,
+		owner: H160,
+		spender: H160,
+		value: U256,
+		deadline: U256,
+		v: u8,
+		r: H256,
+		s: H256,
+	) -> EvmResult {
+		let asset_id = Self::asset_id(handle)?;
+		// Timestamp, metadata and nonce reads, nonce write and the approval.
+		handle.record_weight::<Runtime>(
+			<Runtime as frame_system::Config>::DbWeight::get()
+				.reads_writes(3, 1)
+				.saturating_add(
+					<Runtime as pallet_assets::Config<Instance>>::WeightInfo::cancel_approval(),
+				)
+				.saturating_add(
+					<Runtime as pallet_assets::Config<Instance>>::WeightInfo::approve_transfer(),
+				),
+		)?;
+
+		let now: u64 = pallet_timestamp::Pallet::<Runtime>::get().unique_saturated_into();
+		if deadline < U256::from(now / 1000) {
+			return Err(revert("permit expired"));
+		}
+
+		let nonce = Nonces::<Runtime, Instance>::get(asset_id, owner);
+		let permit = keccak_256(
+			&EvmDataWriter::new()
+				.write(H256(PERMIT_TYPEHASH))
+				.write(owner)
+				.write(spender)
+				.write(value)
+				.write(nonce)
+				.write(deadline)
+				.build(),
+		);
+		let domain_separator = Self::compute_domain_separator(asset_id, handle.code_address());
+		let digest = keccak_256(&[&[0x19, 0x01][..], &domain_separator, &permit].concat());
+
+		let mut signature = [0u8; 65];
+		signature[..32].copy_from_slice(r.as_bytes());
+		signature[32..64].copy_from_slice(s.as_bytes());
+		signature[64] = v.wrapping_sub(27);
+		let signer = sp_io::crypto::secp256k1_ecdsa_recover(&signature, &digest)
+			.map(|public| H160::from(H256::from(keccak_256(&public))))
+			.map_err(|_| revert("invalid permit"))?;
+		if signer != owner {
+			return Err(revert("invalid permit"));
+		}
+
+		Nonces::<Runtime, Instance>::insert(asset_id, owner, nonce.saturating_add(U256::one()));
+		Self::set_allowance(asset_id, owner, spender, value)?;
+
+		Self::log(handle, SELECTOR_LOG_APPROVAL, owner, spender, value)
+	}
+
+	#[precompile::public("nonces(address)")]
+	#[precompile::view]
+	fn nonces(handle: &mut impl PrecompileHandle, owner: H160) -> EvmResult<U256> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(Nonces::<Runtime, Instance>::get(asset_id, owner))
+	}
+
+	#[precompile::public("DOMAIN_SEPARATOR()")]
+	#[precompile::view]
+	fn domain_separator(handle: &mut impl PrecompileHandle) -> EvmResult<H256> {
+		let asset_id = Self::asset_id(handle)?;
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(Self::compute_domain_separator(asset_id, handle.code_address()).into())
+	}
+
+	/// EIP-712 domain of the permits of an asset, named after its metadata.
+	pub fn compute_domain_separator(
+		asset_id: AssetIdOf<Runtime, Instance>,
+		address: H160,
+	) -> [u8; 32] {
+		let name = <Assets<Runtime, Instance> as metadata::Inspect<_>>::name(asset_id);
+		keccak_256(
+			&[
+				EIP712_DOMAIN_TYPEHASH,
+				keccak_256(&name),
+				PERMIT_VERSION_HASH,
+				H256::from_low_u64_be(Runtime::ChainId::get()).0,
+				H256::from(address).0,
+			]
+			.concat(),
+		)
+	}
+
+	/// Replace the allowance of `spender` over the tokens of `owner`. Values above the
+	/// asset balance type are capped, so that "infinite" approvals keep working.
+	fn set_allowance(
+		asset_id: AssetIdOf<Runtime, Instance>,
+		owner: H160,
+		spender: H160,
+		value: U256,
+	) -> EvmResult {
+		let owner = Runtime::AddressMapping::into_account_id(owner);
+		let spender = Runtime::AddressMapping::into_account_id(spender);
+		let amount = u128::try_from(value)
+			.ok()
+			.and_then(|value| BalanceOf::<Runtime, Instance>::try_from(value).ok())
+			.unwrap_or_else(Bounded::max_value);
+
+		let current = <Assets<Runtime, Instance> as approvals::Inspect<_>>::allowance(
+			asset_id, &owner, &spender,
+		);
+		if !current.is_zero() {
+			Assets::<Runtime, Instance>::cancel_approval(
+				RawOrigin::Signed(owner.clone()).into(),
+				asset_id.into(),
+				Runtime::Lookup::unlookup(spender.clone()),
+			)
+			.map_err(dispatch_revert)?;
+		}
+		if !amount.is_zero() {
+			<Assets<Runtime, Instance> as approvals::Mutate<_>>::approve(
+				asset_id, &owner, &spender, amount,
+			)
+			.map_err(dispatch_revert)?;
+		}
+		Ok(())
+	}
+
+	fn balance_to_u256(balance: BalanceOf<Runtime, Instance>) -> U256 {
+		let balance: u128 = balance.try_into().unwrap_or(u128::MAX);
+		balance.into()
+	}
+
+	fn balance_from_u256(value: U256) -> EvmResult<BalanceOf<Runtime, Instance>> {
+		u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value out of bounds"))
+	}
+
+	/// Asset of the precompile being called, which `execute` checked to exist.
+	fn asset_id(handle: &impl PrecompileHandle) -> EvmResult<AssetIdOf<Runtime, Instance>> {
+		Runtime::address_to_asset_id(handle.code_address()).ok_or_else(|| revert("unknown asset"))
+	}
+
+	fn log(
+		handle: &mut impl PrecompileHandle,
+		topic: [u8; 32],
+		from: H160,
+		to: H160,
+		value: U256,
+	) -> EvmResult {
+		log3(
+			handle.code_address(),
+			topic,
+			from,
+			to,
+			EvmDataWriter::new().write(value).build(),
+		)
+		.record(handle)
+	}
+}
+
+fn dispatch_revert(error: DispatchError) -> PrecompileFailure {
+	revert(format!(
+		"dispatch execution failed: {}",
+		<&'static str>::from(error)
+	))
+}
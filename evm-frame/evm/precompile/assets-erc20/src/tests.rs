@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::Log;
+use frame_support::assert_ok;
+use precompile_utils::testing::{alice, bob, charlie, input, output, reverted_with, MockHandle};
+use sp_core::{ecdsa, Pair};
+
+const ASSET_ID: u32 = 1;
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		assert_ok!(Assets::force_create(
+			RuntimeOrigin::root(),
+			ASSET_ID,
+			alice(),
+			true,
+			1
+		));
+		assert_ok!(Assets::set_metadata(
+			RuntimeOrigin::signed(alice()),
+			ASSET_ID,
+			b"Test Token".to_vec(),
+			b"TST".to_vec(),
+			12
+		));
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(alice()),
+			ASSET_ID,
+			alice(),
+			1000
+		));
+	});
+	ext
+}
+
+fn asset_address() -> H160 {
+	Test::asset_id_to_address(ASSET_ID)
+}
+
+fn call(caller: H160, input: EvmDataWriter) -> (PrecompileResult, MockHandle) {
+	let mut handle = MockHandle::new(asset_address(), caller, input.build());
+	let result = Erc20AssetsPrecompileSet::<Test>::new()
+		.execute(&mut handle)
+		.expect("asset precompile exists");
+	(result, handle)
+}
+
+fn balance_of(owner: H160) -> U256 {
+	output(call(owner, input("balanceOf(address)").write(owner)).0)
+}
+
+fn allowance(owner: H160, spender: H160) -> U256 {
+	let input = input("allowance(address,address)")
+		.write(owner)
+		.write(spender);
+	output(call(owner, input).0)
+}
+
+fn approve_input(spender: H160, value: U256) -> EvmDataWriter {
+	input("approve(address,uint256)")
+		.write(spender)
+		.write(value)
+}
+
+fn permit_input(
+	owner: H160,
+	spender: H160,
+	value: U256,
+	deadline: U256,
+	signature: [u8; 65],
+) -> EvmDataWriter {
+	input("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)")
+		.write(owner)
+		.write(spender)
+		.write(value)
+		.write(deadline)
+		.write(signature[64] + 27)
+		.write(H256::from_slice(&signature[..32]))
+		.write(H256::from_slice(&signature[32..64]))
+}
+
+#[test]
+fn constants_match_their_preimages() {
+	assert_eq!(
+		SELECTOR_LOG_TRANSFER,
+		keccak_256(b"Transfer(address,address,uint256)")
+	);
+	assert_eq!(
+		SELECTOR_LOG_APPROVAL,
+		keccak_256(b"Approval(address,address,uint256)")
+	);
+	assert_eq!(
+		PERMIT_TYPEHASH,
+		keccak_256(
+			b"Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"
+		)
+	);
+	assert_eq!(
+		EIP712_DOMAIN_TYPEHASH,
+		keccak_256(
+			b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"
+		)
+	);
+	assert_eq!(PERMIT_VERSION_HASH, keccak_256(b"1"));
+}
+
+#[test]
+fn only_existing_assets_are_precompiles() {
+	new_test_ext().execute_with(|| {
+		let precompiles = Erc20AssetsPrecompileSet::<Test>::new();
+		assert!(precompiles.is_precompile(asset_address()));
+		assert!(!precompiles.is_precompile(Test::asset_id_to_address(2)));
+		assert!(!precompiles.is_precompile(alice()));
+
+		let mut handle = MockHandle::new(
+			Test::asset_id_to_address(2),
+			alice(),
+			input("totalSupply()").build(),
+		);
+		assert!(precompiles.execute(&mut handle).is_none());
+	});
+}
+
+#[test]
+fn metadata_getters_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			output::<Bytes>(call(alice(), input("name()")).0),
+			Bytes::from("Test Token")
+		);
+		assert_eq!(
+			output::<Bytes>(call(alice(), input("symbol()")).0),
+			Bytes::from("TST")
+		);
+		assert_eq!(output::<u8>(call(alice(), input("decimals()")).0), 12);
+		assert_eq!(
+			output::<U256>(call(alice(), input("totalSupply()")).0),
+			U256::from(1000)
+		);
+	});
+}
+
+#[test]
+fn transfer_moves_balance_and_logs() {
+	new_test_ext().execute_with(|| {
+		let input = input("transfer(address,uint256)")
+			.write(bob())
+			.write(U256::from(400));
+		let (result, handle) = call(alice(), input);
+		assert!(output::<bool>(result));
+
+		assert_eq!(balance_of(alice()), U256::from(600));
+		assert_eq!(balance_of(bob()), U256::from(400));
+		assert_eq!(
+			handle.logs,
+			vec![Log {
+				address: asset_address(),
+				topics: vec![H256(SELECTOR_LOG_TRANSFER), alice().into(), bob().into()],
+				data: EvmDataWriter::new().write(U256::from(400)).build(),
+			}]
+		);
+	});
+}
+
+#[test]
+fn transfer_above_balance_reverts() {
+	new_test_ext().execute_with(|| {
+		let input = input("transfer(address,uint256)")
+			.write(bob())
+			.write(U256::from(1001));
+		assert!(matches!(
+			call(alice(), input).0,
+			Err(PrecompileFailure::Revert { .. })
+		));
+		assert_eq!(balance_of(alice()), U256::from(1000));
+	});
+}
+
+#[test]
+fn approve_replaces_allowance() {
+	new_test_ext().execute_with(|| {
+		let (result, handle) = call(alice(), approve_input(bob(), U256::from(100)));
+		assert!(output::<bool>(result));
+		assert_eq!(allowance(alice(), bob()), U256::from(100));
+		assert_eq!(handle.logs[0].topics[0], H256(SELECTOR_LOG_APPROVAL));
+
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::from(30))).0
+		));
+		assert_eq!(allowance(alice(), bob()), U256::from(30));
+
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::zero())).0
+		));
+		assert_eq!(allowance(alice(), bob()), U256::zero());
+
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::max_value())).0
+		));
+		assert_eq!(allowance(alice(), bob()), U256::from(u128::MAX));
+	});
+}
+
+#[test]
+fn transfer_from_spends_allowance() {
+	new_test_ext().execute_with(|| {
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::from(100))).0
+		));
+
+		let transfer_from = |value: u64| {
+			input("transferFrom(address,address,uint256)")
+				.write(alice())
+				.write(charlie())
+				.write(U256::from(value))
+		};
+		let (result, handle) = call(bob(), transfer_from(60));
+		assert!(output::<bool>(result));
+		assert_eq!(handle.logs[0].topics[1], alice().into());
+		assert_eq!(handle.logs[0].topics[2], charlie().into());
+
+		assert_eq!(balance_of(charlie()), U256::from(60));
+		assert_eq!(allowance(alice(), bob()), U256::from(40));
+
+		assert!(call(bob(), transfer_from(50)).0.is_err());
+		assert_eq!(balance_of(charlie()), U256::from(60));
+	});
+}
+
+#[test]
+fn permit_sets_allowance_once() {
+	new_test_ext().execute_with(|| {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let probe = [0u8; 32];
+		let owner = H160::from(H256::from(keccak_256(
+			&sp_io::crypto::secp256k1_ecdsa_recover(&pair.sign_prehashed(&probe).0, &probe)
+				.unwrap(),
+		)));
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(alice()),
+			ASSET_ID,
+			owner,
+			500
+		));
+
+		let value = U256::from(250);
+		let deadline = U256::from(1_000);
+		let permit = keccak_256(
+			&EvmDataWriter::new()
+				.write(H256(PERMIT_TYPEHASH))
+				.write(owner)
+				.write(bob())
+				.write(value)
+				.write(U256::zero())
+				.write(deadline)
+				.build(),
+		);
+		let domain_separator =
+			Erc20AssetsPrecompileSet::<Test>::compute_domain_separator(ASSET_ID, asset_address());
+		assert_eq!(
+			output::<H256>(call(alice(), input("DOMAIN_SEPARATOR()")).0),
+			H256(domain_separator)
+		);
+		let digest = keccak_256(&[&[0x19, 0x01][..], &domain_separator, &permit].concat());
+		let signature = pair.sign_prehashed(&digest).0;
+
+		let input_permit = || permit_input(owner, bob(), value, deadline, signature);
+		let (result, handle) = call(charlie(), input_permit());
+		assert!(result.unwrap().output.is_empty());
+		assert_eq!(handle.logs[0].topics[1], owner.into());
+		assert_eq!(allowance(owner, bob()), value);
+		assert_eq!(
+			output::<U256>(call(charlie(), input("nonces(address)").write(owner)).0),
+			U256::one()
+		);
+
+		// The nonce moved on, so the same permit no longer matches its signer.
+		assert!(call(charlie(), input_permit()).0.is_err());
+	});
+}
+
+#[test]
+fn expired_permit_reverts() {
+	new_test_ext().execute_with(|| {
+		Timestamp::set_timestamp(20_000);
+		let input = permit_input(alice(), bob(), U256::one(), U256::from(19), [1u8; 65]);
+		assert!(reverted_with(call(alice(), input).0, "permit expired"));
+	});
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ERC-20 precompile over the native currency of the EVM pallet.
+//!
+//! Balances are those of `pallet_evm::Config::Currency`; allowances live in the storage of
+//! this crate's pallet, and an allowance of `type(uint256).max` is never spent.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use alloc::format;
+use core::marker::PhantomData;
+use fp_evm::{PrecompileFailure, PrecompileHandle};
+use frame_support::traits::{Currency, ExistenceRequirement, Get};
+use hex_literal::hex;
+use pallet_balances::WeightInfo as _;
+use pallet_evm::{AddressMapping, BalanceOf};
+use precompile_utils::{
+	log3, revert, Bytes, EvmDataWriter, EvmResult, LogExt, PrecompileHandleExt,
+};
+use sp_core::{H160, U256};
+use sp_runtime::DispatchError;
+
+pub use pallet::*;
+
+/// Topic of the `Transfer(address,address,uint256)` event.
+pub const SELECTOR_LOG_TRANSFER: [u8; 32] =
+	hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Topic of the `Approval(address,address,uint256)` event.
+pub const SELECTOR_LOG_APPROVAL: [u8; 32] =
+	hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+/// Metadata of the native currency, as returned by the ERC-20 getters.
+pub trait Erc20Metadata {
+	fn name() -> &'static str;
+	fn symbol() -> &'static str;
+	fn decimals() -> u8;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use sp_core::{H160, U256};
+
+	/// Holds the allowances of [`super::Erc20BalancesPrecompile`], which has no calls.
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {}
+
+	/// Allowances granted by owners to spenders.
+	#[pallet::storage]
+	pub type Approves<T> =
+		StorageDoubleMap<_, Blake2_128Concat, H160, Blake2_128Concat, H160, U256, ValueQuery>;
+}
+
+/// ERC-20 precompile of the native currency.
+pub struct Erc20BalancesPrecompile<Runtime, Metadata>(PhantomData<(Runtime, Metadata)>);
+
+#[precompile_utils::precompile]
+impl<Runtime, Metadata> Erc20BalancesPrecompile<Runtime, Metadata>
+where
+	Runtime: Config + pallet_evm::Config + pallet_balances::Config,
+	BalanceOf<Runtime>: TryFrom<U256> + Into<U256>,
+	Metadata: Erc20Metadata,
+{
+	#[precompile::public("totalSupply()")]
+	#[precompile::view]
+	fn total_supply(handle: &mut impl PrecompileHandle) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(<Runtime::Currency as Currency<Runtime::AccountId>>::total_issuance().into())
+	}
+
+	#[precompile::public("balanceOf(address)")]
+	#[precompile::view]
+	fn balance_of(handle: &mut impl PrecompileHandle, owner: H160) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		let owner = Runtime::AddressMapping::into_account_id(owner);
+		Ok(<Runtime::Currency as Currency<Runtime::AccountId>>::free_balance(&owner).into())
+	}
+
+	#[precompile::public("allowance(address,address)")]
+	#[precompile::view]
+	fn allowance(
+		handle: &mut impl PrecompileHandle,
+		owner: H160,
+		spender: H160,
+	) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(Approves::<Runtime>::get(owner, spender))
+	}
+
+	#[precompile::public("transfer(address,uint256)")]
+	fn transfer(handle: &mut impl PrecompileHandle, to: H160, value: U256) -> EvmResult<bool> {
+		handle.record_weight::<Runtime>(
+			<Runtime as pallet_balances::Config>::WeightInfo::transfer(),
+		)?;
+
+		let from = handle.context().caller;
+		Self::do_transfer(from, to, value)?;
+
+		Self::log_transfer(handle, from, to, value)?;
+		Ok(true)
+	}
+
+	#[precompile::public("approve(address,uint256)")]
+	fn approve(handle: &mut impl PrecompileHandle, spender: H160, value: U256) -> EvmResult<bool> {
+		handle.record_db_write::<Runtime>(1)?;
+
+		let owner = handle.context().caller;
+		if value.is_zero() {
+			Approves::<Runtime>::remove(owner, spender);
+		} else {
+			Approves::<Runtime>::insert(owner, spender, value);
+		}
+
+		log3(
+			handle.code_address(),
+			SELECTOR_LOG_APPROVAL,
+			owner,
+			spender,
+			EvmDataWriter::new().write(value).build(),
+		)
+		.record(handle)?;
+		Ok(true)
+	}
+
+	#[precompile::public("transferFrom(address,address,uint256)")]
+	fn transfer_from(
+		handle: &mut impl PrecompileHandle,
+		from: H160,
+		to: H160,
+		value: U256,
+	) -> EvmResult<bool> {
+		handle.record_weight::<Runtime>(
+			<Runtime as pallet_balances::Config>::WeightInfo::transfer()
+				.saturating_add(Runtime::DbWeight::get().reads_writes(1, 1)),
+		)?;
+
+		// Moving one's own tokens needs no allowance.
+		let caller = handle.context().caller;
+		if caller != from {
+			let allowance = Approves::<Runtime>::get(from, caller);
+			if allowance < value {
+				return Err(revert("allowance exceeded"));
+			}
+			if allowance != U256::max_value() {
+				Approves::<Runtime>::insert(from, caller, allowance - value);
+			}
+		}
+		Self::do_transfer(from, to, value)?;
+
+		Self::log_transfer(handle, from, to, value)?;
+		Ok(true)
+	}
+
+	#[precompile::public("name()")]
+	#[precompile::view]
+	fn name(_handle: &mut impl PrecompileHandle) -> EvmResult<Bytes> {
+		Ok(Metadata::name().into())
+	}
+
+	#[precompile::public("symbol()")]
+	#[precompile::view]
+	fn symbol(_handle: &mut impl PrecompileHandle) -> EvmResult<Bytes> {
+		Ok(Metadata::symbol().into())
+	}
+
+	#[precompile::public("decimals()")]
+	#[precompile::view]
+	fn decimals(_handle: &mut impl PrecompileHandle) -> EvmResult<u8> {
+		Ok(Metadata::decimals())
+	}
+
+	fn do_transfer(from: H160, to: H160, value: U256) -> EvmResult {
+		let amount =
+			BalanceOf::<Runtime>::try_from(value).map_err(|_| revert("value out of bounds"))?;
+		<Runtime::Currency as Currency<Runtime::AccountId>>::transfer(
+			&Runtime::AddressMapping::into_account_id(from),
+			&Runtime::AddressMapping::into_account_id(to),
+			amount,
+			ExistenceRequirement::AllowDeath,
+		)
+		.map_err(dispatch_revert)
+	}
+
+	fn log_transfer(
+		handle: &mut impl PrecompileHandle,
+		from: H160,
+		to: H160,
+		value: U256,
+	) -> EvmResult {
+		log3(
+			handle.code_address(),
+			SELECTOR_LOG_TRANSFER,
+			from,
+			to,
+			EvmDataWriter::new().write(value).build(),
+		)
+		.record(handle)
+	}
+}
+
+fn dispatch_revert(error: DispatchError) -> PrecompileFailure {
+	revert(format!(
+		"dispatch execution failed: {}",
+		<&'static str>::from(error)
+	))
+}
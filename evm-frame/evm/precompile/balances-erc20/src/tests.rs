@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{Log, Precompile, PrecompileResult};
+use precompile_utils::testing::{alice, bob, charlie, input, output, MockHandle};
+use sp_core::H256;
+use sp_io::hashing::keccak_256;
+
+type Erc20 = Erc20BalancesPrecompile<Test, NativeMetadata>;
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2050)
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(alice(), 1000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	t.into()
+}
+
+fn call(caller: H160, input: EvmDataWriter) -> (PrecompileResult, MockHandle) {
+	let mut handle = MockHandle::new(precompile_address(), caller, input.build());
+	let result = Erc20::execute(&mut handle);
+	(result, handle)
+}
+
+fn balance_of(owner: H160) -> U256 {
+	output(call(owner, input("balanceOf(address)").write(owner)).0)
+}
+
+fn allowance(owner: H160, spender: H160) -> U256 {
+	let input = input("allowance(address,address)")
+		.write(owner)
+		.write(spender);
+	output(call(owner, input).0)
+}
+
+fn approve_input(spender: H160, value: U256) -> EvmDataWriter {
+	input("approve(address,uint256)")
+		.write(spender)
+		.write(value)
+}
+
+fn transfer_from_input(from: H160, to: H160, value: U256) -> EvmDataWriter {
+	input("transferFrom(address,address,uint256)")
+		.write(from)
+		.write(to)
+		.write(value)
+}
+
+#[test]
+fn log_topics_match_events() {
+	assert_eq!(
+		SELECTOR_LOG_TRANSFER,
+		keccak_256(b"Transfer(address,address,uint256)")
+	);
+	assert_eq!(
+		SELECTOR_LOG_APPROVAL,
+		keccak_256(b"Approval(address,address,uint256)")
+	);
+}
+
+#[test]
+fn metadata_and_supply_getters_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			output::<Bytes>(call(alice(), input("name()")).0),
+			Bytes::from("Native Token")
+		);
+		assert_eq!(
+			output::<Bytes>(call(alice(), input("symbol()")).0),
+			Bytes::from("NAT")
+		);
+		assert_eq!(output::<u8>(call(alice(), input("decimals()")).0), 18);
+		assert_eq!(
+			output::<U256>(call(alice(), input("totalSupply()")).0),
+			U256::from(1000)
+		);
+		assert!(call(alice(), input("mint(address,uint256)")).0.is_err());
+	});
+}
+
+#[test]
+fn transfer_moves_native_balance_and_logs() {
+	new_test_ext().execute_with(|| {
+		let input = input("transfer(address,uint256)")
+			.write(bob())
+			.write(U256::from(400));
+		let (result, handle) = call(alice(), input);
+		assert!(output::<bool>(result));
+
+		assert_eq!(Balances::free_balance(alice()), 600);
+		assert_eq!(balance_of(bob()), U256::from(400));
+		assert_eq!(
+			handle.logs,
+			vec![Log {
+				address: precompile_address(),
+				topics: vec![H256(SELECTOR_LOG_TRANSFER), alice().into(), bob().into()],
+				data: EvmDataWriter::new().write(U256::from(400)).build(),
+			}]
+		);
+		assert!(handle.used_gas > 375 + 3 * 375 + 8 * 32);
+	});
+}
+
+#[test]
+fn transfer_from_spends_allowance() {
+	new_test_ext().execute_with(|| {
+		let (result, handle) = call(alice(), approve_input(bob(), U256::from(100)));
+		assert!(output::<bool>(result));
+		assert_eq!(handle.logs[0].topics[0], H256(SELECTOR_LOG_APPROVAL));
+		assert_eq!(allowance(alice(), bob()), U256::from(100));
+
+		let input = transfer_from_input(alice(), charlie(), U256::from(60));
+		assert!(output::<bool>(call(bob(), input).0));
+		assert_eq!(balance_of(charlie()), U256::from(60));
+		assert_eq!(allowance(alice(), bob()), U256::from(40));
+
+		let input = transfer_from_input(alice(), charlie(), U256::from(50));
+		assert!(call(bob(), input).0.is_err());
+		assert_eq!(balance_of(charlie()), U256::from(60));
+		assert_eq!(allowance(alice(), bob()), U256::from(40));
+	});
+}
+
+#[test]
+fn unlimited_allowance_is_not_spent() {
+	new_test_ext().execute_with(|| {
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::max_value())).0
+		));
+
+		let input = transfer_from_input(alice(), charlie(), U256::from(60));
+		assert!(output::<bool>(call(bob(), input).0));
+		assert_eq!(allowance(alice(), bob()), U256::max_value());
+
+		assert!(output::<bool>(
+			call(alice(), approve_input(bob(), U256::zero())).0
+		));
+		assert_eq!(allowance(alice(), bob()), U256::zero());
+	});
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batch precompile, making several subcalls in a single transaction.
+//!
+//! The subcalls are made on behalf of the caller of the precompile, as if it had sent them
+//! itself. Each function takes the targets of the subcalls along with their values, call data
+//! and gas limits; missing entries stand for no value, empty call data and all the remaining
+//! gas, and entries past the last target are ignored. The outcome of every subcall made is
+//! reported by a `SubcallSucceeded(uint256)` or `SubcallFailed(uint256)` event carrying its
+//! index, unless the whole batch reverts.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod tests;
+
+use alloc::vec::Vec;
+use fp_evm::{Context, ExitReason, ExitRevert, PrecompileFailure, PrecompileHandle, Transfer};
+use hex_literal::hex;
+use precompile_utils::{log1, revert, Bytes, EvmDataWriter, EvmResult, PrecompileHandleExt};
+use sp_core::{H160, U256};
+
+/// Topic of the `SubcallSucceeded(uint256)` event.
+pub const SELECTOR_LOG_SUBCALL_SUCCEEDED: [u8; 32] =
+	hex!("bf855484633929c3d6688eb3caf8eff910fb4bef030a8d7dbc9390d26759714d");
+
+/// Topic of the `SubcallFailed(uint256)` event.
+pub const SELECTOR_LOG_SUBCALL_FAILED: [u8; 32] =
+	hex!("dbc5d06f4f877f959b1ff12d2161cdd693fa8e442ee53f1790b2804b24881f05");
+
+/// What a batch does when one of its subcalls fails.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+	/// Go on with the next subcalls.
+	BatchSome,
+	/// Skip the remaining subcalls.
+	BatchSomeUntilFailure,
+	/// Revert the whole batch.
+	BatchAll,
+}
+
+/// Batch precompile.
+pub struct BatchPrecompile;
+
+#[precompile_utils::precompile]
+impl BatchPrecompile {
+	/// Makes every subcall, whether the previous ones failed or not.
+	#[precompile::public("batchSome(address[],uint256[],bytes[],uint64[])")]
+	fn batch_some(
+		handle: &mut impl PrecompileHandle,
+		to: Vec<H160>,
+		value: Vec<U256>,
+		call_data: Vec<Bytes>,
+		gas_limit: Vec<u64>,
+	) -> EvmResult {
+		Self::batch(handle, Mode::BatchSome, to, value, call_data, gas_limit)
+	}
+
+	/// Makes the subcalls up to the first one which fails.
+	#[precompile::public("batchSomeUntilFailure(address[],uint256[],bytes[],uint64[])")]
+	fn batch_some_until_failure(
+		handle: &mut impl PrecompileHandle,
+		to: Vec<H160>,
+		value: Vec<U256>,
+		call_data: Vec<Bytes>,
+		gas_limit: Vec<u64>,
+	) -> EvmResult {
+		Self::batch(
+			handle,
+			Mode::BatchSomeUntilFailure,
+			to,
+			value,
+			call_data,
+			gas_limit,
+		)
+	}
+
+	/// Makes all the subcalls, reverting with the first one which fails.
+	#[precompile::public("batchAll(address[],uint256[],bytes[],uint64[])")]
+	fn batch_all(
+		handle: &mut impl PrecompileHandle,
+		to: Vec<H160>,
+		value: Vec<U256>,
+		call_data: Vec<Bytes>,
+		gas_limit: Vec<u64>,
+	) -> EvmResult {
+		Self::batch(handle, Mode::BatchAll, to, value, call_data, gas_limit)
+	}
+
+	fn batch(
+		handle: &mut impl PrecompileHandle,
+		mode: Mode,
+		to: Vec<H160>,
+		value: Vec<U256>,
+		call_data: Vec<Bytes>,
+		gas_limit: Vec<u64>,
+	) -> EvmResult {
+		let caller = handle.context().caller;
+		let mut call_data = call_data.into_iter();
+		for (index, address) in to.into_iter().enumerate() {
+			let value = value.get(index).copied().unwrap_or_default();
+			let input = call_data.next().map_or_else(Vec::new, |data| data.0);
+
+			// The event reporting the subcall is paid upfront, so that the subcall cannot leave
+			// too little gas to emit it.
+			handle.record_log_costs_manual(1, 32)?;
+
+			let remaining_gas = handle.remaining_gas();
+			let target_gas = match gas_limit.get(index).copied() {
+				None | Some(0) => remaining_gas,
+				Some(target_gas) if target_gas <= remaining_gas => target_gas,
+				Some(_) => match mode {
+					Mode::BatchAll => {
+						return Err(revert("gas limit of a subcall exceeds the remaining gas"))
+					}
+					Mode::BatchSome => {
+						Self::log_subcall(handle, SELECTOR_LOG_SUBCALL_FAILED, index)?;
+						continue;
+					}
+					Mode::BatchSomeUntilFailure => {
+						Self::log_subcall(handle, SELECTOR_LOG_SUBCALL_FAILED, index)?;
+						break;
+					}
+				},
+			};
+
+			let transfer = (!value.is_zero()).then_some(Transfer {
+				source: caller,
+				target: address,
+				value,
+			});
+			let context = Context {
+				address,
+				caller,
+				apparent_value: value,
+			};
+			let (reason, output) =
+				handle.call(address, transfer, input, Some(target_gas), false, &context);
+
+			let failure = match reason {
+				ExitReason::Succeed(_) => {
+					Self::log_subcall(handle, SELECTOR_LOG_SUBCALL_SUCCEEDED, index)?;
+					continue;
+				}
+				ExitReason::Revert(_) => PrecompileFailure::Revert {
+					exit_status: ExitRevert::Reverted,
+					output,
+				},
+				ExitReason::Error(exit_status) => PrecompileFailure::Error { exit_status },
+				ExitReason::Fatal(exit_status) => {
+					return Err(PrecompileFailure::Fatal { exit_status })
+				}
+			};
+			match mode {
+				Mode::BatchAll => return Err(failure),
+				Mode::BatchSome => {
+					Self::log_subcall(handle, SELECTOR_LOG_SUBCALL_FAILED, index)?;
+				}
+				Mode::BatchSomeUntilFailure => {
+					Self::log_subcall(handle, SELECTOR_LOG_SUBCALL_FAILED, index)?;
+					break;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Emit the event of a subcall, whose cost was recorded before the subcall.
+	fn log_subcall(handle: &mut impl PrecompileHandle, topic: [u8; 32], index: usize) -> EvmResult {
+		let log = log1(
+			handle.context().address,
+			topic,
+			EvmDataWriter::new().write(U256::from(index)).build(),
+		);
+		handle.log(log.address, log.topics, log.data)?;
+		Ok(())
+	}
+}
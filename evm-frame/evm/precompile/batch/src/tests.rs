@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use fp_evm::{ExitError, ExitSucceed, Precompile, PrecompileResult};
+use precompile_utils::{
+	testing::{self, alice, MockHandle, Subcall, SubcallOutput},
+	EvmDataReader,
+};
+use sp_core::H256;
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2053)
+}
+
+/// Succeeds, emitting a log.
+fn succeeding() -> H160 {
+	H160::from_low_u64_be(10)
+}
+
+/// Reverts with `nope`.
+fn reverting() -> H160 {
+	H160::from_low_u64_be(11)
+}
+
+/// Runs out of gas.
+fn failing() -> H160 {
+	H160::from_low_u64_be(12)
+}
+
+fn input(
+	signature: &str,
+	to: Vec<H160>,
+	value: Vec<U256>,
+	call_data: Vec<Bytes>,
+	gas_limit: Vec<u64>,
+) -> Vec<u8> {
+	testing::input(signature)
+		.write(to)
+		.write(value)
+		.write(call_data)
+		.write(gas_limit)
+		.build()
+}
+
+/// Handle answering the subcalls of the batch, which are kept in `subcalls`.
+fn handle(input: Vec<u8>, subcalls: Rc<RefCell<Vec<Subcall>>>) -> MockHandle {
+	let mut handle =
+		MockHandle::new(precompile_address(), alice(), input).with_subcall_handle(move |subcall| {
+			let output = match subcall.address {
+				a if a == succeeding() => SubcallOutput {
+					reason: ExitReason::Succeed(ExitSucceed::Returned),
+					output: Vec::new(),
+					cost: 1_000,
+					logs: vec![log1(a, H256::repeat_byte(0xaa), Vec::new())],
+				},
+				a if a == reverting() => SubcallOutput {
+					reason: ExitReason::Revert(ExitRevert::Reverted),
+					output: b"nope".to_vec(),
+					cost: 1_000,
+					logs: Vec::new(),
+				},
+				_ => SubcallOutput {
+					reason: ExitReason::Error(ExitError::OutOfGas),
+					output: Vec::new(),
+					cost: 1_000,
+					logs: Vec::new(),
+				},
+			};
+			subcalls.borrow_mut().push(subcall);
+			output
+		});
+	handle.gas_limit = Some(100_000);
+	handle
+}
+
+fn call(input: Vec<u8>) -> (PrecompileResult, MockHandle, Vec<Subcall>) {
+	let subcalls = Rc::new(RefCell::new(Vec::new()));
+	let mut handle = handle(input, subcalls.clone());
+	let result = BatchPrecompile::execute(&mut handle);
+	let subcalls = subcalls.take();
+	(result, handle, subcalls)
+}
+
+/// Topics and indexes of the events of the batch, skipping the logs of its subcalls.
+fn events(handle: &MockHandle) -> Vec<(H256, U256)> {
+	handle
+		.logs
+		.iter()
+		.filter(|log| log.address == precompile_address())
+		.map(|log| {
+			(
+				log.topics[0],
+				EvmDataReader::new(&log.data).read::<U256>().unwrap(),
+			)
+		})
+		.collect()
+}
+
+fn succeeded(index: u64) -> (H256, U256) {
+	(H256(SELECTOR_LOG_SUBCALL_SUCCEEDED), index.into())
+}
+
+fn failed(index: u64) -> (H256, U256) {
+	(H256(SELECTOR_LOG_SUBCALL_FAILED), index.into())
+}
+
+fn targets() -> Vec<H160> {
+	vec![succeeding(), reverting(), succeeding()]
+}
+
+#[test]
+fn batch_some_makes_every_subcall() {
+	let (result, handle, subcalls) = call(input(
+		"batchSome(address[],uint256[],bytes[],uint64[])",
+		targets(),
+		vec![],
+		vec![],
+		vec![],
+	));
+	assert!(result.is_ok());
+	assert_eq!(subcalls.len(), 3);
+	assert_eq!(events(&handle), vec![succeeded(0), failed(1), succeeded(2)]);
+	// The logs of both succeeding subcalls are kept.
+	assert_eq!(handle.logs.len(), 5);
+}
+
+#[test]
+fn batch_some_until_failure_stops_at_the_first_failure() {
+	let (result, handle, subcalls) = call(input(
+		"batchSomeUntilFailure(address[],uint256[],bytes[],uint64[])",
+		targets(),
+		vec![],
+		vec![],
+		vec![],
+	));
+	assert!(result.is_ok());
+	assert_eq!(subcalls.len(), 2);
+	assert_eq!(events(&handle), vec![succeeded(0), failed(1)]);
+}
+
+#[test]
+fn batch_all_reverts_with_the_first_failure() {
+	let (result, _, subcalls) = call(input(
+		"batchAll(address[],uint256[],bytes[],uint64[])",
+		targets(),
+		vec![],
+		vec![],
+		vec![],
+	));
+	assert_eq!(subcalls.len(), 2);
+	assert_eq!(
+		result.err(),
+		Some(PrecompileFailure::Revert {
+			exit_status: ExitRevert::Reverted,
+			output: b"nope".to_vec(),
+		})
+	);
+
+	let (result, _, _) = call(input(
+		"batchAll(address[],uint256[],bytes[],uint64[])",
+		vec![succeeding(), failing()],
+		vec![],
+		vec![],
+		vec![],
+	));
+	assert_eq!(
+		result.err(),
+		Some(PrecompileFailure::Error {
+			exit_status: ExitError::OutOfGas
+		})
+	);
+}
+
+#[test]
+fn subcalls_are_made_as_the_caller() {
+	let (result, _, subcalls) = call(input(
+		"batchAll(address[],uint256[],bytes[],uint64[])",
+		vec![succeeding(), succeeding()],
+		vec![U256::from(5)],
+		vec![Bytes::from(&b"first"[..])],
+		vec![10_000],
+	));
+	assert!(result.is_ok());
+
+	let first = &subcalls[0];
+	assert_eq!(first.context.caller, alice());
+	assert_eq!(first.context.address, succeeding());
+	assert_eq!(first.context.apparent_value, U256::from(5));
+	let transfer = first.transfer.as_ref().unwrap();
+	assert_eq!(
+		(transfer.source, transfer.target, transfer.value),
+		(alice(), succeeding(), U256::from(5))
+	);
+	assert_eq!(first.input, b"first".to_vec());
+	assert_eq!(first.target_gas, Some(10_000));
+	assert!(!first.is_static);
+
+	// Missing entries stand for no value, no call data and all the remaining gas.
+	let second = &subcalls[1];
+	assert!(second.transfer.is_none());
+	assert!(second.input.is_empty());
+	assert!(matches!(second.target_gas, Some(gas) if gas > 10_000));
+}
+
+#[test]
+fn gas_limit_above_the_remaining_gas_fails_the_subcall() {
+	let (result, handle, subcalls) = call(input(
+		"batchSome(address[],uint256[],bytes[],uint64[])",
+		vec![succeeding(), succeeding()],
+		vec![],
+		vec![],
+		vec![1_000_000],
+	));
+	assert!(result.is_ok());
+	assert_eq!(subcalls.len(), 1);
+	assert_eq!(events(&handle), vec![failed(0), succeeded(1)]);
+}
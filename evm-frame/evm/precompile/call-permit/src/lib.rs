@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Call permit precompile, dispatching calls signed off-chain by their sender.
+//!
+//! A permit is an EIP-712 signature of a `CallPermit` by its `from` account, allowing anyone,
+//! such as a relayer paying the gas, to make the call on its behalf before `deadline`, given
+//! in seconds. Every permit consumes the current nonce of its signer, so that it cannot be
+//! replayed; a call which fails reverts the dispatch, leaving the nonce untouched.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{Context, ExitReason, ExitRevert, PrecompileFailure, PrecompileHandle, Transfer};
+use frame_support::traits::Get;
+use hex_literal::hex;
+use precompile_utils::{revert, Bytes, EvmDataWriter, EvmResult, PrecompileHandleExt};
+use sp_core::{H160, H256, U256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::traits::UniqueSaturatedInto;
+
+pub use pallet::*;
+
+/// `keccak256("CallPermit(address from,address to,uint256 value,bytes data,uint64 gaslimit,uint256 nonce,uint256 deadline)")`
+pub const PERMIT_TYPEHASH: [u8; 32] =
+	hex!("506804721d438a24e91e7d0d65a8105f208b3b12629be3a1c464ebf61c8df92d");
+
+/// `keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")`
+pub const EIP712_DOMAIN_TYPEHASH: [u8; 32] =
+	hex!("8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f");
+
+/// `keccak256("Call Permit Precompile")`, the name of the permit domain.
+pub const PERMIT_NAME_HASH: [u8; 32] =
+	hex!("f3208ef23ef67442fe01a790b35e0628052e724664f0281fea88e1bffcc5b096");
+
+/// `keccak256("1")`, the version of the permit domain.
+pub const PERMIT_VERSION_HASH: [u8; 32] =
+	hex!("c89efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bc6");
+
+/// Gas of recovering the signer of a permit, as charged by the `ecrecover` precompile.
+const ECRECOVER_COST: u64 = 3_000;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use sp_core::{H160, U256};
+
+	/// Holds the nonces of [`super::CallPermitPrecompile`], which has no calls.
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {}
+
+	/// Nonces of the permit signers.
+	#[pallet::storage]
+	pub type Nonces<T> = StorageMap<_, Blake2_128Concat, H160, U256, ValueQuery>;
+}
+
+/// Call permit precompile.
+pub struct CallPermitPrecompile<Runtime>(PhantomData<Runtime>);
+
+#[precompile_utils::precompile]
+impl<Runtime> CallPermitPrecompile<Runtime>
+where
+	Runtime: Config + pallet_evm::Config + pallet_timestamp::Config,
+{
+	/// Makes the call permitted by `from`, returning its output.
+	#[precompile::public(
+		"dispatch(address,address,uint256,bytes,uint64,uint256,uint8,bytes32,bytes32)"
+	)]
+	#[allow(clippy::too_many_arguments)]
+	fn dispatch_permit(
+		handle: &mut impl PrecompileHandle,
+		from: H160,
+		to: H160,
+		value: U256,
+		data: Bytes,
+		gas_limit: u64,
+		deadline: U256,
+		v: u8,
+		r: H256,
+		s: H256,
+	) -> EvmResult<Bytes> {
+		// Signature recovery, timestamp and nonce reads and the nonce write.
+		handle.record_cost(ECRECOVER_COST)?;
+		handle.record_db_read::<Runtime>(2)?;
+		handle.record_db_write::<Runtime>(1)?;
+
+		let now: u64 = pallet_timestamp::Pallet::<Runtime>::get().unique_saturated_into();
+		if deadline < U256::from(now / 1000) {
+			return Err(revert("permit expired"));
+		}
+
+		let nonce = Nonces::<Runtime>::get(from);
+		let digest = Self::compute_digest(
+			handle.code_address(),
+			from,
+			to,
+			value,
+			&data.0,
+			gas_limit,
+			nonce,
+			deadline,
+		);
+		let mut signature = [0u8; 65];
+		signature[..32].copy_from_slice(r.as_bytes());
+		signature[32..64].copy_from_slice(s.as_bytes());
+		signature[64] = v.wrapping_sub(27);
+		let signer = sp_io::crypto::secp256k1_ecdsa_recover(&signature, &digest)
+			.map(|public| H160::from(H256::from(keccak_256(&public))))
+			.map_err(|_| revert("invalid permit"))?;
+		if signer != from {
+			return Err(revert("invalid permit"));
+		}
+		Nonces::<Runtime>::insert(from, nonce.saturating_add(U256::one()));
+
+		// The subcall gets at most 63/64 of the remaining gas, which must cover the gas limit
+		// signed for, so that a relayer cannot make the call fail by short-changing it.
+		let remaining_gas = handle.remaining_gas();
+		if remaining_gas - remaining_gas / 64 < gas_limit {
+			return Err(revert("gas limit is too low to make the permitted call"));
+		}
+
+		let transfer = (!value.is_zero()).then_some(Transfer {
+			source: from,
+			target: to,
+			value,
+		});
+		let context = Context {
+			address: to,
+			caller: from,
+			apparent_value: value,
+		};
+		let (reason, output) = handle.call(to, transfer, data.0, Some(gas_limit), false, &context);
+		match reason {
+			ExitReason::Succeed(_) => Ok(Bytes(output)),
+			ExitReason::Revert(_) => Err(PrecompileFailure::Revert {
+				exit_status: ExitRevert::Reverted,
+				output,
+			}),
+			ExitReason::Error(exit_status) => Err(PrecompileFailure::Error { exit_status }),
+			ExitReason::Fatal(exit_status) => Err(PrecompileFailure::Fatal { exit_status }),
+		}
+	}
+
+	#[precompile::public("nonces(address)")]
+	#[precompile::view]
+	fn nonces(handle: &mut impl PrecompileHandle, owner: H160) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(Nonces::<Runtime>::get(owner))
+	}
+
+	#[precompile::public("DOMAIN_SEPARATOR()")]
+	#[precompile::view]
+	fn domain_separator(handle: &mut impl PrecompileHandle) -> EvmResult<H256> {
+		Ok(Self::compute_domain_separator(handle.code_address()).into())
+	}
+
+	/// EIP-712 domain of the permits of the precompile at `address`.
+	pub fn compute_domain_separator(address: H160) -> [u8; 32] {
+		keccak_256(
+			&[
+				EIP712_DOMAIN_TYPEHASH,
+				PERMIT_NAME_HASH,
+				PERMIT_VERSION_HASH,
+				H256::from_low_u64_be(Runtime::ChainId::get()).0,
+				H256::from(address).0,
+			]
+			.concat(),
+		)
+	}
+
+	/// Digest signed by `from` to permit a call.
+	#[allow(clippy::too_many_arguments)]
+	pub fn compute_digest(
+		address: H160,
+		from: H160,
+		to: H160,
+		value: U256,
+		data: &[u8],
+		gas_limit: u64,
+		nonce: U256,
+		deadline: U256,
+	) -> [u8; 32] {
+		let permit = keccak_256(
+			&EvmDataWriter::new()
+				.write(H256(PERMIT_TYPEHASH))
+				.write(from)
+				.write(to)
+				.write(value)
+				.write(H256(keccak_256(data)))
+				.write(gas_limit)
+				.write(nonce)
+				.write(deadline)
+				.build(),
+		);
+		let domain_separator = Self::compute_domain_separator(address);
+		keccak_256(&[&[0x19, 0x01][..], &domain_separator, &permit].concat())
+	}
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{ExitSucceed, Precompile, PrecompileResult};
+use precompile_utils::testing::{input, output, reverted_with, MockHandle, Subcall, SubcallOutput};
+use sp_core::{ecdsa, Pair};
+use std::{cell::RefCell, rc::Rc};
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2054)
+}
+
+fn relayer() -> H160 {
+	H160::from_low_u64_be(1)
+}
+
+fn target() -> H160 {
+	H160::from_low_u64_be(10)
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	sp_io::TestExternalities::new(t)
+}
+
+/// Key pair of the signer of the permits, with its address.
+fn signer() -> (ecdsa::Pair, H160) {
+	let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+	let probe = [0u8; 32];
+	let address = H160::from(H256::from(keccak_256(
+		&sp_io::crypto::secp256k1_ecdsa_recover(&pair.sign_prehashed(&probe).0, &probe).unwrap(),
+	)));
+	(pair, address)
+}
+
+/// Input of a permit of `from` to call `target` with `data`, signed with `pair`.
+fn permit_input(
+	pair: &ecdsa::Pair,
+	from: H160,
+	data: &[u8],
+	gas_limit: u64,
+	nonce: U256,
+) -> Vec<u8> {
+	let value = U256::from(5);
+	let deadline = U256::from(1_000);
+	let digest = CallPermitPrecompile::<Test>::compute_digest(
+		precompile_address(),
+		from,
+		target(),
+		value,
+		data,
+		gas_limit,
+		nonce,
+		deadline,
+	);
+	let signature = pair.sign_prehashed(&digest).0;
+	input("dispatch(address,address,uint256,bytes,uint64,uint256,uint8,bytes32,bytes32)")
+		.write(from)
+		.write(target())
+		.write(value)
+		.write(Bytes::from(data))
+		.write(gas_limit)
+		.write(deadline)
+		.write(signature[64] + 27)
+		.write(H256::from_slice(&signature[..32]))
+		.write(H256::from_slice(&signature[32..64]))
+		.build()
+}
+
+/// Call of the relayer, whose subcalls end with `reason` and are kept in `subcalls`.
+fn call(
+	input: Vec<u8>,
+	reason: ExitReason,
+	subcalls: Rc<RefCell<Vec<Subcall>>>,
+) -> PrecompileResult {
+	let mut handle = MockHandle::new(precompile_address(), relayer(), input).with_subcall_handle(
+		move |subcall| {
+			subcalls.borrow_mut().push(subcall);
+			SubcallOutput {
+				reason: reason.clone(),
+				output: b"done".to_vec(),
+				cost: 1_000,
+				logs: Vec::new(),
+			}
+		},
+	);
+	handle.gas_limit = Some(100_000);
+	CallPermitPrecompile::<Test>::execute(&mut handle)
+}
+
+fn succeed() -> ExitReason {
+	ExitReason::Succeed(ExitSucceed::Returned)
+}
+
+fn nonce(owner: H160) -> U256 {
+	output(call(
+		input("nonces(address)").write(owner).build(),
+		succeed(),
+		Default::default(),
+	))
+}
+
+#[test]
+fn permit_dispatches_the_call_as_its_signer() {
+	new_test_ext().execute_with(|| {
+		let (pair, from) = signer();
+		let subcalls = Rc::new(RefCell::new(Vec::new()));
+		let input = permit_input(&pair, from, b"call", 50_000, U256::zero());
+
+		let result = call(input.clone(), succeed(), subcalls.clone());
+		assert_eq!(output::<Bytes>(result), Bytes::from(&b"done"[..]));
+		assert_eq!(nonce(from), U256::one());
+
+		let subcalls = subcalls.take();
+		assert_eq!(subcalls.len(), 1);
+		let subcall = &subcalls[0];
+		assert_eq!(subcall.address, target());
+		assert_eq!(subcall.input, b"call".to_vec());
+		assert_eq!(subcall.target_gas, Some(50_000));
+		assert_eq!(subcall.context.caller, from);
+		assert_eq!(subcall.context.apparent_value, U256::from(5));
+		let transfer = subcall.transfer.as_ref().unwrap();
+		assert_eq!((transfer.source, transfer.target), (from, target()));
+
+		// The nonce moved on, so the same permit no longer matches its signer.
+		assert!(reverted_with(
+			call(input, succeed(), Default::default()),
+			"invalid permit"
+		));
+	});
+}
+
+#[test]
+fn permit_of_another_signer_reverts() {
+	new_test_ext().execute_with(|| {
+		let (pair, _) = signer();
+		let subcalls = Rc::new(RefCell::new(Vec::new()));
+		let input = permit_input(&pair, relayer(), b"call", 50_000, U256::zero());
+		assert!(reverted_with(
+			call(input, succeed(), subcalls.clone()),
+			"invalid permit"
+		));
+		assert!(subcalls.borrow().is_empty());
+	});
+}
+
+#[test]
+fn failed_call_reverts_with_its_output() {
+	new_test_ext().execute_with(|| {
+		let (pair, from) = signer();
+		let input = permit_input(&pair, from, b"call", 50_000, U256::zero());
+		assert_eq!(
+			call(
+				input,
+				ExitReason::Revert(ExitRevert::Reverted),
+				Default::default()
+			)
+			.err(),
+			Some(PrecompileFailure::Revert {
+				exit_status: ExitRevert::Reverted,
+				output: b"done".to_vec(),
+			})
+		);
+	});
+}
+
+#[test]
+fn permit_needs_gas_for_its_gas_limit() {
+	new_test_ext().execute_with(|| {
+		let (pair, from) = signer();
+		let input = permit_input(&pair, from, b"call", 99_000, U256::zero());
+		assert!(reverted_with(
+			call(input, succeed(), Default::default()),
+			"gas limit is too low to make the permitted call"
+		));
+	});
+}
+
+#[test]
+fn expired_permit_reverts() {
+	new_test_ext().execute_with(|| {
+		Timestamp::set_timestamp(2_000_000);
+		let (pair, from) = signer();
+		let input = permit_input(&pair, from, b"call", 50_000, U256::zero());
+		assert!(reverted_with(
+			call(input, succeed(), Default::default()),
+			"permit expired"
+		));
+	});
+}
+
+#[test]
+fn domain_separator_is_bound_to_the_precompile() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			output::<H256>(call(
+				input("DOMAIN_SEPARATOR()").build(),
+				succeed(),
+				Default::default()
+			)),
+			H256(CallPermitPrecompile::<Test>::compute_domain_separator(
+				precompile_address()
+			))
+		);
+	});
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conviction voting precompile over `pallet_conviction_voting`.
+//!
+//! Votes, delegations and unlocks are dispatched from the account the caller maps to through
+//! `AddressMapping`. Polls are the referenda of `pallet_referenda` in the runtime, addressed by
+//! their `uint32` index, and voting classes are its `uint16` track ids. Convictions range from
+//! `0` (no lock, a tenth of the votes) to `6` (32 lock periods, six times the votes). Delegation
+//! targets live outside the EVM and are passed as their raw 32 bytes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::PrecompileHandle;
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Currency, Polling},
+};
+use frame_system::RawOrigin;
+use pallet_conviction_voting::{AccountVote, Conviction, TallyOf, Vote};
+use pallet_evm::AddressMapping;
+use precompile_utils::{revert, EvmResult, PrecompileHandleExt, RuntimeHelper};
+use sp_core::{H256, U256};
+use sp_runtime::traits::StaticLookup;
+
+type BalanceOf<Runtime> = <<Runtime as pallet_conviction_voting::Config>::Currency as Currency<
+	<Runtime as frame_system::Config>::AccountId,
+>>::Balance;
+type IndexOf<Runtime> =
+	<<Runtime as pallet_conviction_voting::Config>::Polls as Polling<TallyOf<Runtime>>>::Index;
+type ClassOf<Runtime> =
+	<<Runtime as pallet_conviction_voting::Config>::Polls as Polling<TallyOf<Runtime>>>::Class;
+
+/// Conviction voting precompile, for the accounts mapped from EVM addresses.
+pub struct ConvictionVotingPrecompile<Runtime>(PhantomData<Runtime>);
+
+#[precompile_utils::precompile]
+impl<Runtime> ConvictionVotingPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config + pallet_conviction_voting::Config,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_conviction_voting::Call<Runtime>>,
+	Runtime::AccountId: From<[u8; 32]>,
+	IndexOf<Runtime>: TryFrom<u32>,
+	ClassOf<Runtime>: TryFrom<u16> + Into<u16>,
+	BalanceOf<Runtime>: TryFrom<U256> + Into<U256>,
+{
+	/// Whether a poll is ongoing, with its track and current tally. Polls which are over or
+	/// never existed report zeroes.
+	#[precompile::public("referendumStatus(uint32)")]
+	#[precompile::view]
+	fn referendum_status(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+	) -> EvmResult<(bool, u16, U256, U256, U256)> {
+		handle.record_db_read::<Runtime>(1)?;
+		let poll_index = Self::poll_index(poll_index)?;
+		Ok(match Runtime::Polls::as_ongoing(poll_index) {
+			Some((tally, class)) => (
+				true,
+				class.into(),
+				tally.ayes.into(),
+				tally.nays.into(),
+				tally.support.into(),
+			),
+			None => (false, 0, U256::zero(), U256::zero(), U256::zero()),
+		})
+	}
+
+	#[precompile::public("voteYes(uint32,uint256,uint8)")]
+	fn vote_yes(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		Self::vote_standard(handle, poll_index, true, amount, conviction)
+	}
+
+	#[precompile::public("voteNo(uint32,uint256,uint8)")]
+	fn vote_no(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		Self::vote_standard(handle, poll_index, false, amount, conviction)
+	}
+
+	/// Splits a vote between both sides, without conviction.
+	#[precompile::public("voteSplit(uint32,uint256,uint256)")]
+	fn vote_split(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		aye: U256,
+		nay: U256,
+	) -> EvmResult {
+		let vote = AccountVote::Split {
+			aye: Self::balance(aye)?,
+			nay: Self::balance(nay)?,
+		};
+		Self::vote(handle, poll_index, vote)
+	}
+
+	/// Splits a vote between both sides and abstention, without conviction.
+	#[precompile::public("voteSplitAbstain(uint32,uint256,uint256,uint256)")]
+	fn vote_split_abstain(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		aye: U256,
+		nay: U256,
+		abstain: U256,
+	) -> EvmResult {
+		let vote = AccountVote::SplitAbstain {
+			aye: Self::balance(aye)?,
+			nay: Self::balance(nay)?,
+			abstain: Self::balance(abstain)?,
+		};
+		Self::vote(handle, poll_index, vote)
+	}
+
+	#[precompile::public("removeVote(uint32)")]
+	fn remove_vote(handle: &mut impl PrecompileHandle, poll_index: u32) -> EvmResult {
+		let call = pallet_conviction_voting::Call::<Runtime>::remove_vote {
+			class: None,
+			index: Self::poll_index(poll_index)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	/// Removes a vote on a poll which may be over, given the track it was on.
+	#[precompile::public("removeVoteForTrack(uint32,uint16)")]
+	fn remove_vote_for_track(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		track: u16,
+	) -> EvmResult {
+		let call = pallet_conviction_voting::Call::<Runtime>::remove_vote {
+			class: Some(Self::track(track)?),
+			index: Self::poll_index(poll_index)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("delegate(uint16,bytes32,uint8,uint256)")]
+	fn delegate(
+		handle: &mut impl PrecompileHandle,
+		track: u16,
+		to: H256,
+		conviction: u8,
+		amount: U256,
+	) -> EvmResult {
+		let call = pallet_conviction_voting::Call::<Runtime>::delegate {
+			class: Self::track(track)?,
+			to: Runtime::Lookup::unlookup(to.0.into()),
+			conviction: Self::conviction(conviction)?,
+			balance: Self::balance(amount)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("undelegate(uint16)")]
+	fn undelegate(handle: &mut impl PrecompileHandle, track: u16) -> EvmResult {
+		let call = pallet_conviction_voting::Call::<Runtime>::undelegate {
+			class: Self::track(track)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	/// Releases the caller's expired voting locks on a track.
+	#[precompile::public("unlock(uint16)")]
+	fn unlock(handle: &mut impl PrecompileHandle, track: u16) -> EvmResult {
+		let target = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_conviction_voting::Call::<Runtime>::unlock {
+			class: Self::track(track)?,
+			target: Runtime::Lookup::unlookup(target),
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	fn vote_standard(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		aye: bool,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		let vote = AccountVote::Standard {
+			vote: Vote {
+				aye,
+				conviction: Self::conviction(conviction)?,
+			},
+			balance: Self::balance(amount)?,
+		};
+		Self::vote(handle, poll_index, vote)
+	}
+
+	fn vote(
+		handle: &mut impl PrecompileHandle,
+		poll_index: u32,
+		vote: AccountVote<BalanceOf<Runtime>>,
+	) -> EvmResult {
+		let call = pallet_conviction_voting::Call::<Runtime>::vote {
+			poll_index: Self::poll_index(poll_index)?,
+			vote,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	fn dispatch_call(
+		handle: &mut impl PrecompileHandle,
+		call: pallet_conviction_voting::Call<Runtime>,
+	) -> EvmResult {
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		RuntimeHelper::<Runtime>::try_dispatch(handle, RawOrigin::Signed(origin).into(), call)?;
+		Ok(())
+	}
+
+	fn poll_index(value: u32) -> EvmResult<IndexOf<Runtime>> {
+		value
+			.try_into()
+			.map_err(|_| revert("poll index out of bounds"))
+	}
+
+	fn track(value: u16) -> EvmResult<ClassOf<Runtime>> {
+		value.try_into().map_err(|_| revert("track out of bounds"))
+	}
+
+	fn conviction(value: u8) -> EvmResult<Conviction> {
+		value.try_into().map_err(|_| revert("invalid conviction"))
+	}
+
+	fn balance(value: U256) -> EvmResult<BalanceOf<Runtime>> {
+		value.try_into().map_err(|_| revert("value out of bounds"))
+	}
+}
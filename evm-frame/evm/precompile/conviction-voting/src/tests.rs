@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{Precompile, PrecompileResult};
+use pallet_conviction_voting::Voting;
+use pallet_evm::HashedAddressMapping;
+use precompile_utils::{
+	testing::{alice, input, output, reverted_with, MockHandle},
+	EvmDataWriter,
+};
+use sp_core::{crypto::AccountId32, H160};
+use sp_runtime::traits::BlakeTwo256;
+
+type Status = (bool, u16, U256, U256, U256);
+
+fn alice_account() -> AccountId32 {
+	HashedAddressMapping::<BlakeTwo256>::into_account_id(alice())
+}
+
+fn bob() -> AccountId32 {
+	AccountId32::from([2u8; 32])
+}
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2052)
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(alice_account(), 1_000), (bob(), 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn call(input: EvmDataWriter) -> PrecompileResult {
+	let mut handle = MockHandle::new(precompile_address(), alice(), input.build());
+	ConvictionVotingPrecompile::<Test>::execute(&mut handle)
+}
+
+fn status(poll_index: u32) -> Status {
+	output(call(input("referendumStatus(uint32)").write(poll_index)))
+}
+
+fn ongoing(ayes: u64, nays: u64, support: u64) -> Status {
+	(true, 7, ayes.into(), nays.into(), support.into())
+}
+
+#[test]
+fn referendum_status_reports_ongoing_polls_only() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(status(2), ongoing(0, 0, 0));
+
+		let over = (false, 0, U256::zero(), U256::zero(), U256::zero());
+		assert_eq!(status(1), over);
+		assert_eq!(status(9), over);
+	});
+}
+
+#[test]
+fn standard_votes_are_tallied_with_conviction() {
+	new_test_ext().execute_with(|| {
+		output::<()>(call(
+			input("voteYes(uint32,uint256,uint8)")
+				.write(2u32)
+				.write(U256::from(100))
+				.write(2u8),
+		));
+		assert_eq!(status(2), ongoing(200, 0, 100));
+
+		output::<()>(call(
+			input("voteNo(uint32,uint256,uint8)")
+				.write(2u32)
+				.write(U256::from(100))
+				.write(0u8),
+		));
+		assert_eq!(status(2), ongoing(0, 10, 0));
+
+		output::<()>(call(input("removeVote(uint32)").write(2u32)));
+		assert_eq!(status(2), ongoing(0, 0, 0));
+	});
+}
+
+#[test]
+fn split_votes_are_tallied_without_conviction() {
+	new_test_ext().execute_with(|| {
+		output::<()>(call(
+			input("voteSplit(uint32,uint256,uint256)")
+				.write(2u32)
+				.write(U256::from(60))
+				.write(U256::from(40)),
+		));
+		assert_eq!(status(2), ongoing(6, 4, 60));
+
+		output::<()>(call(
+			input("voteSplitAbstain(uint32,uint256,uint256,uint256)")
+				.write(2u32)
+				.write(U256::from(60))
+				.write(U256::from(40))
+				.write(U256::from(100)),
+		));
+		assert_eq!(status(2), ongoing(6, 4, 160));
+
+		output::<()>(call(
+			input("removeVoteForTrack(uint32,uint16)")
+				.write(2u32)
+				.write(7u16),
+		));
+		assert_eq!(status(2), ongoing(0, 0, 0));
+	});
+}
+
+#[test]
+fn invalid_votes_revert() {
+	new_test_ext().execute_with(|| {
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(2u32)
+					.write(U256::from(100))
+					.write(7u8)
+			),
+			"invalid conviction"
+		));
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(1u32)
+					.write(U256::from(100))
+					.write(1u8)
+			),
+			"dispatch execution failed: NotOngoing"
+		));
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(2u32)
+					.write(U256::MAX)
+					.write(1u8)
+			),
+			"value out of bounds"
+		));
+	});
+}
+
+#[test]
+fn delegate_and_undelegate() {
+	new_test_ext().execute_with(|| {
+		output::<()>(call(
+			input("delegate(uint16,bytes32,uint8,uint256)")
+				.write(7u16)
+				.write(H256(bob().into()))
+				.write(1u8)
+				.write(U256::from(500)),
+		));
+		match pallet_conviction_voting::VotingFor::<Test>::get(alice_account(), 7) {
+			Voting::Delegating(delegating) => {
+				assert_eq!(delegating.target, bob());
+				assert_eq!(delegating.balance, 500);
+			}
+			voting => panic!("expected a delegation, got {:?}", voting),
+		}
+
+		output::<()>(call(input("undelegate(uint16)").write(7u16)));
+		assert!(matches!(
+			pallet_conviction_voting::VotingFor::<Test>::get(alice_account(), 7),
+			Voting::Casting(_)
+		));
+
+		output::<()>(call(input("unlock(uint16)").write(7u16)));
+	});
+}
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Democracy precompile over `pallet_democracy`.
+//!
+//! Proposals, seconds, votes, delegations and unlocks are dispatched from the account the
+//! caller maps to through `AddressMapping`. Proposals are submitted by the hash of a call
+//! whose preimage was noted beforehand in the preimage pallet. Public proposals and
+//! referenda are addressed by their `uint32` index. Convictions range from `0` (no lock, a
+//! tenth of the votes) to `6` (32 lock periods, six times the votes). Delegation targets
+//! live outside the EVM and are passed as their raw 32 bytes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::PrecompileHandle;
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Bounded, Currency, QueryPreimage},
+};
+use frame_system::RawOrigin;
+use pallet_democracy::{AccountVote, Conviction, ReferendumInfo, Vote};
+use pallet_evm::AddressMapping;
+use precompile_utils::{revert, EvmResult, PrecompileHandleExt, RuntimeHelper};
+use sp_core::{H256, U256};
+use sp_runtime::traits::StaticLookup;
+
+type BalanceOf<Runtime> = <<Runtime as pallet_democracy::Config>::Currency as Currency<
+	<Runtime as frame_system::Config>::AccountId,
+>>::Balance;
+
+/// Democracy precompile, for the accounts mapped from EVM addresses.
+pub struct DemocracyPrecompile<Runtime>(PhantomData<Runtime>);
+
+#[precompile_utils::precompile]
+impl<Runtime> DemocracyPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config + pallet_democracy::Config,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_democracy::Call<Runtime>>,
+	Runtime::AccountId: From<[u8; 32]>,
+	BalanceOf<Runtime>: TryFrom<U256> + Into<U256>,
+{
+	/// Number of public proposals made so far, which is also the index of the next one.
+	#[precompile::public("publicPropCount()")]
+	#[precompile::view]
+	fn public_prop_count(handle: &mut impl PrecompileHandle) -> EvmResult<u32> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(pallet_democracy::Pallet::<Runtime>::public_prop_count())
+	}
+
+	/// Deposit locked by each backer of a public proposal, with the number of backers. Proposals
+	/// which were tabled or never existed report zeroes.
+	#[precompile::public("depositOf(uint32)")]
+	#[precompile::view]
+	fn deposit_of(handle: &mut impl PrecompileHandle, prop_index: u32) -> EvmResult<(U256, u32)> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(
+			match pallet_democracy::Pallet::<Runtime>::deposit_of(prop_index) {
+				Some((backers, deposit)) => (deposit.into(), backers.len() as u32),
+				None => (U256::zero(), 0),
+			},
+		)
+	}
+
+	/// Whether a referendum is ongoing, with its current tally. Referenda which are over or
+	/// never existed report zeroes.
+	#[precompile::public("referendumStatus(uint32)")]
+	#[precompile::view]
+	fn referendum_status(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+	) -> EvmResult<(bool, U256, U256, U256)> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(
+			match pallet_democracy::Pallet::<Runtime>::referendum_info(ref_index) {
+				Some(ReferendumInfo::Ongoing(status)) => (
+					true,
+					status.tally.ayes.into(),
+					status.tally.nays.into(),
+					status.tally.turnout.into(),
+				),
+				_ => (false, U256::zero(), U256::zero(), U256::zero()),
+			},
+		)
+	}
+
+	/// Proposes the call of a noted preimage, locking `value` as deposit.
+	#[precompile::public("propose(bytes32,uint256)")]
+	fn propose(handle: &mut impl PrecompileHandle, proposal_hash: H256, value: U256) -> EvmResult {
+		handle.record_db_read::<Runtime>(1)?;
+		let len = <Runtime as pallet_democracy::Config>::Preimages::len(&proposal_hash)
+			.ok_or_else(|| revert("preimage not found"))?;
+		let call = pallet_democracy::Call::<Runtime>::propose {
+			proposal: Bounded::Lookup {
+				hash: proposal_hash,
+				len,
+			},
+			value: Self::balance(value)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	/// Backs a public proposal, locking the same deposit as its proposer.
+	#[precompile::public("second(uint32)")]
+	fn second(handle: &mut impl PrecompileHandle, prop_index: u32) -> EvmResult {
+		let call = pallet_democracy::Call::<Runtime>::second {
+			proposal: prop_index,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("voteYes(uint32,uint256,uint8)")]
+	fn vote_yes(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		Self::vote_standard(handle, ref_index, true, amount, conviction)
+	}
+
+	#[precompile::public("voteNo(uint32,uint256,uint8)")]
+	fn vote_no(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		Self::vote_standard(handle, ref_index, false, amount, conviction)
+	}
+
+	/// Splits a vote between both sides, without conviction.
+	#[precompile::public("voteSplit(uint32,uint256,uint256)")]
+	fn vote_split(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+		aye: U256,
+		nay: U256,
+	) -> EvmResult {
+		let vote = AccountVote::Split {
+			aye: Self::balance(aye)?,
+			nay: Self::balance(nay)?,
+		};
+		Self::vote(handle, ref_index, vote)
+	}
+
+	#[precompile::public("removeVote(uint32)")]
+	fn remove_vote(handle: &mut impl PrecompileHandle, ref_index: u32) -> EvmResult {
+		let call = pallet_democracy::Call::<Runtime>::remove_vote { index: ref_index };
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("delegate(bytes32,uint8,uint256)")]
+	fn delegate(
+		handle: &mut impl PrecompileHandle,
+		to: H256,
+		conviction: u8,
+		amount: U256,
+	) -> EvmResult {
+		let call = pallet_democracy::Call::<Runtime>::delegate {
+			to: Runtime::Lookup::unlookup(to.0.into()),
+			conviction: Self::conviction(conviction)?,
+			balance: Self::balance(amount)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("undelegate()")]
+	fn undelegate(handle: &mut impl PrecompileHandle) -> EvmResult {
+		Self::dispatch_call(handle, pallet_democracy::Call::<Runtime>::undelegate {})
+	}
+
+	/// Releases the caller's expired voting locks.
+	#[precompile::public("unlock()")]
+	fn unlock(handle: &mut impl PrecompileHandle) -> EvmResult {
+		let target = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_democracy::Call::<Runtime>::unlock {
+			target: Runtime::Lookup::unlookup(target),
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	fn vote_standard(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+		aye: bool,
+		amount: U256,
+		conviction: u8,
+	) -> EvmResult {
+		let vote = AccountVote::Standard {
+			vote: Vote {
+				aye,
+				conviction: Self::conviction(conviction)?,
+			},
+			balance: Self::balance(amount)?,
+		};
+		Self::vote(handle, ref_index, vote)
+	}
+
+	fn vote(
+		handle: &mut impl PrecompileHandle,
+		ref_index: u32,
+		vote: AccountVote<BalanceOf<Runtime>>,
+	) -> EvmResult {
+		let call = pallet_democracy::Call::<Runtime>::vote { ref_index, vote };
+		Self::dispatch_call(handle, call)
+	}
+
+	fn dispatch_call(
+		handle: &mut impl PrecompileHandle,
+		call: pallet_democracy::Call<Runtime>,
+	) -> EvmResult {
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		RuntimeHelper::<Runtime>::try_dispatch(handle, RawOrigin::Signed(origin).into(), call)?;
+		Ok(())
+	}
+
+	fn conviction(value: u8) -> EvmResult<Conviction> {
+		value.try_into().map_err(|_| revert("invalid conviction"))
+	}
+
+	fn balance(value: U256) -> EvmResult<BalanceOf<Runtime>> {
+		value.try_into().map_err(|_| revert("value out of bounds"))
+	}
+}
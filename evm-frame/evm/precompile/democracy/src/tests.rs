@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{Precompile, PrecompileResult};
+use frame_support::traits::StorePreimage;
+use pallet_democracy::{VoteThreshold, Voting};
+use pallet_evm::HashedAddressMapping;
+use precompile_utils::{
+	testing::{alice, input, output, reverted_with, MockHandle},
+	EvmDataWriter,
+};
+use scale_codec::Encode;
+use sp_core::{crypto::AccountId32, H160};
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+type Status = (bool, U256, U256, U256);
+
+fn alice_account() -> AccountId32 {
+	HashedAddressMapping::<BlakeTwo256>::into_account_id(alice())
+}
+
+fn bob() -> AccountId32 {
+	AccountId32::from([2u8; 32])
+}
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2055)
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(alice_account(), 1_000), (bob(), 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn call(input: EvmDataWriter) -> PrecompileResult {
+	let mut handle = MockHandle::new(precompile_address(), alice(), input.build());
+	DemocracyPrecompile::<Test>::execute(&mut handle)
+}
+
+fn remark() -> RuntimeCall {
+	RuntimeCall::System(frame_system::Call::remark {
+		remark: b"proposal".to_vec(),
+	})
+}
+
+/// Note the preimage of a call on behalf of bob, returning its hash.
+fn note_preimage(call: RuntimeCall) -> H256 {
+	let encoded = call.encode();
+	Preimage::note_preimage(RuntimeOrigin::signed(bob()), encoded.clone()).unwrap();
+	BlakeTwo256::hash(&encoded)
+}
+
+fn start_referendum() -> u32 {
+	Democracy::internal_start_referendum(
+		Preimage::bound(remark()).unwrap(),
+		VoteThreshold::SuperMajorityApprove,
+		0,
+	)
+}
+
+fn deposit(prop_index: u32) -> (U256, u32) {
+	output(call(input("depositOf(uint32)").write(prop_index)))
+}
+
+fn status(ref_index: u32) -> Status {
+	output(call(input("referendumStatus(uint32)").write(ref_index)))
+}
+
+fn ongoing(ayes: u64, nays: u64, turnout: u64) -> Status {
+	(true, ayes.into(), nays.into(), turnout.into())
+}
+
+#[test]
+fn proposals_are_made_and_seconded() {
+	new_test_ext().execute_with(|| {
+		let proposal_hash = note_preimage(remark());
+		output::<()>(call(
+			input("propose(bytes32,uint256)")
+				.write(proposal_hash)
+				.write(U256::from(100)),
+		));
+		assert_eq!(output::<u32>(call(input("publicPropCount()"))), 1);
+		assert_eq!(deposit(0), (U256::from(100), 1));
+
+		output::<()>(call(input("second(uint32)").write(0u32)));
+		assert_eq!(deposit(0), (U256::from(100), 2));
+		assert_eq!(Balances::reserved_balance(alice_account()), 200);
+
+		assert_eq!(deposit(1), (U256::zero(), 0));
+	});
+}
+
+#[test]
+fn invalid_proposals_revert() {
+	new_test_ext().execute_with(|| {
+		assert!(reverted_with(
+			call(
+				input("propose(bytes32,uint256)")
+					.write(H256::repeat_byte(1))
+					.write(U256::from(100))
+			),
+			"preimage not found"
+		));
+
+		let proposal_hash = note_preimage(remark());
+		assert!(reverted_with(
+			call(
+				input("propose(bytes32,uint256)")
+					.write(proposal_hash)
+					.write(U256::from(1))
+			),
+			"dispatch execution failed: ValueLow"
+		));
+		assert!(reverted_with(
+			call(input("second(uint32)").write(0u32)),
+			"dispatch execution failed: ProposalMissing"
+		));
+	});
+}
+
+#[test]
+fn referendum_status_reports_ongoing_referenda_only() {
+	new_test_ext().execute_with(|| {
+		let ref_index = start_referendum();
+		assert_eq!(status(ref_index), ongoing(0, 0, 0));
+
+		let over = (false, U256::zero(), U256::zero(), U256::zero());
+		assert_eq!(status(ref_index + 1), over);
+	});
+}
+
+#[test]
+fn votes_are_tallied_with_conviction() {
+	new_test_ext().execute_with(|| {
+		let ref_index = start_referendum();
+
+		output::<()>(call(
+			input("voteYes(uint32,uint256,uint8)")
+				.write(ref_index)
+				.write(U256::from(100))
+				.write(2u8),
+		));
+		assert_eq!(status(ref_index), ongoing(200, 0, 100));
+
+		output::<()>(call(
+			input("voteNo(uint32,uint256,uint8)")
+				.write(ref_index)
+				.write(U256::from(100))
+				.write(0u8),
+		));
+		assert_eq!(status(ref_index), ongoing(0, 10, 100));
+
+		output::<()>(call(
+			input("voteSplit(uint32,uint256,uint256)")
+				.write(ref_index)
+				.write(U256::from(60))
+				.write(U256::from(40)),
+		));
+		assert_eq!(status(ref_index), ongoing(6, 4, 100));
+
+		output::<()>(call(input("removeVote(uint32)").write(ref_index)));
+		assert_eq!(status(ref_index), ongoing(0, 0, 0));
+	});
+}
+
+#[test]
+fn invalid_votes_revert() {
+	new_test_ext().execute_with(|| {
+		let ref_index = start_referendum();
+
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(ref_index)
+					.write(U256::from(100))
+					.write(7u8)
+			),
+			"invalid conviction"
+		));
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(ref_index + 1)
+					.write(U256::from(100))
+					.write(1u8)
+			),
+			"dispatch execution failed: ReferendumInvalid"
+		));
+		assert!(reverted_with(
+			call(
+				input("voteYes(uint32,uint256,uint8)")
+					.write(ref_index)
+					.write(U256::MAX)
+					.write(1u8)
+			),
+			"value out of bounds"
+		));
+	});
+}
+
+#[test]
+fn delegate_and_undelegate() {
+	new_test_ext().execute_with(|| {
+		output::<()>(call(
+			input("delegate(bytes32,uint8,uint256)")
+				.write(H256(bob().into()))
+				.write(1u8)
+				.write(U256::from(500)),
+		));
+		match pallet_democracy::VotingOf::<Test>::get(alice_account()) {
+			Voting::Delegating {
+				target, balance, ..
+			} => {
+				assert_eq!(target, bob());
+				assert_eq!(balance, 500);
+			}
+			voting => panic!("expected a delegation, got {:?}", voting),
+		}
+
+		output::<()>(call(input("undelegate()")));
+		assert!(matches!(
+			pallet_democracy::VotingOf::<Test>::get(alice_account()),
+			Voting::Direct { .. }
+		));
+
+		output::<()>(call(input("unlock()")));
+	});
+}
@@ -182,6 +182,17 @@ impl PrecompileHandle for MockHandle {
 		Ok(())
 	}
 
+	fn record_external_cost(
+		&mut self,
+		_: Option<u64>,
+		_: Option<u64>,
+		_: Option<u64>,
+	) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn refund_external_cost(&mut self, _: Option<u64>, _: Option<u64>) {}
+
 	fn remaining_gas(&self) -> u64 {
 		unimplemented!()
 	}
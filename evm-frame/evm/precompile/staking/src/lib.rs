@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Staking precompile over `pallet_staking`.
+//!
+//! Calls are dispatched from the account the caller maps to through `AddressMapping`, which
+//! bonds as its own controller. Accounts living outside the EVM, such as validators, are
+//! passed as their raw 32 bytes. Reward destinations are `0` to restake rewards, `1` to pay
+//! them to the stash and `2` to forgo them.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use fp_evm::PrecompileHandle;
+use frame_support::dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo};
+use frame_system::RawOrigin;
+use pallet_evm::AddressMapping;
+use pallet_staking::{BalanceOf, RewardDestination};
+use precompile_utils::{revert, EvmResult, PrecompileHandleExt, RuntimeHelper};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::StaticLookup;
+
+/// Staking precompile, for the accounts mapped from EVM addresses.
+pub struct StakingPrecompile<Runtime>(PhantomData<Runtime>);
+
+#[precompile_utils::precompile]
+impl<Runtime> StakingPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config + pallet_staking::Config,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_staking::Call<Runtime>>,
+	Runtime::AccountId: From<[u8; 32]> + Into<[u8; 32]>,
+	BalanceOf<Runtime>: TryFrom<U256> + Into<U256>,
+{
+	#[precompile::public("currentEra()")]
+	#[precompile::view]
+	fn current_era(handle: &mut impl PrecompileHandle) -> EvmResult<u32> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(pallet_staking::CurrentEra::<Runtime>::get().unwrap_or_default())
+	}
+
+	#[precompile::public("activeEra()")]
+	#[precompile::view]
+	fn active_era(handle: &mut impl PrecompileHandle) -> EvmResult<u32> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(pallet_staking::ActiveEra::<Runtime>::get().map_or(0, |era| era.index))
+	}
+
+	#[precompile::public("minNominatorBond()")]
+	#[precompile::view]
+	fn min_nominator_bond(handle: &mut impl PrecompileHandle) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(pallet_staking::MinNominatorBond::<Runtime>::get().into())
+	}
+
+	/// Total and active bonded amounts of a stash.
+	#[precompile::public("ledger(address)")]
+	#[precompile::view]
+	fn ledger(handle: &mut impl PrecompileHandle, stash: H160) -> EvmResult<(U256, U256)> {
+		handle.record_db_read::<Runtime>(2)?;
+		let stash = Runtime::AddressMapping::into_account_id(stash);
+		Ok(pallet_staking::Bonded::<Runtime>::get(&stash)
+			.and_then(pallet_staking::Ledger::<Runtime>::get)
+			.map_or((U256::zero(), U256::zero()), |ledger| {
+				(ledger.total.into(), ledger.active.into())
+			}))
+	}
+
+	#[precompile::public("nominations(address)")]
+	#[precompile::view]
+	fn nominations(handle: &mut impl PrecompileHandle, stash: H160) -> EvmResult<Vec<H256>> {
+		handle.record_db_read::<Runtime>(1)?;
+		let stash = Runtime::AddressMapping::into_account_id(stash);
+		Ok(pallet_staking::Nominators::<Runtime>::get(&stash)
+			.map(|nominations| {
+				nominations
+					.targets
+					.into_iter()
+					.map(|target| H256(target.into()))
+					.collect()
+			})
+			.unwrap_or_default())
+	}
+
+	#[precompile::public("erasValidatorReward(uint32)")]
+	#[precompile::view]
+	fn eras_validator_reward(handle: &mut impl PrecompileHandle, era: u32) -> EvmResult<U256> {
+		handle.record_db_read::<Runtime>(1)?;
+		Ok(pallet_staking::ErasValidatorReward::<Runtime>::get(era)
+			.map_or_else(U256::zero, Into::into))
+	}
+
+	#[precompile::public("erasRewardPoints(uint32,bytes32)")]
+	#[precompile::view]
+	fn eras_reward_points(
+		handle: &mut impl PrecompileHandle,
+		era: u32,
+		validator: H256,
+	) -> EvmResult<u32> {
+		handle.record_db_read::<Runtime>(1)?;
+		let validator = Runtime::AccountId::from(validator.0);
+		Ok(pallet_staking::ErasRewardPoints::<Runtime>::get(era)
+			.individual
+			.get(&validator)
+			.copied()
+			.unwrap_or_default())
+	}
+
+	/// Whether the rewards of `validator` and its nominators for `era` were paid out.
+	#[precompile::public("rewardsClaimed(bytes32,uint32)")]
+	#[precompile::view]
+	fn rewards_claimed(
+		handle: &mut impl PrecompileHandle,
+		validator: H256,
+		era: u32,
+	) -> EvmResult<bool> {
+		handle.record_db_read::<Runtime>(2)?;
+		let validator = Runtime::AccountId::from(validator.0);
+		Ok(pallet_staking::Bonded::<Runtime>::get(&validator)
+			.and_then(pallet_staking::Ledger::<Runtime>::get)
+			.map_or(false, |ledger| ledger.claimed_rewards.contains(&era)))
+	}
+
+	#[precompile::public("bond(uint256,uint8)")]
+	fn bond(handle: &mut impl PrecompileHandle, value: U256, reward_destination: u8) -> EvmResult {
+		let controller = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_staking::Call::<Runtime>::bond {
+			controller: Runtime::Lookup::unlookup(controller),
+			value: Self::balance(value)?,
+			payee: Self::reward_destination(reward_destination)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("bondExtra(uint256)")]
+	fn bond_extra(handle: &mut impl PrecompileHandle, value: U256) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::bond_extra {
+			max_additional: Self::balance(value)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("unbond(uint256)")]
+	fn unbond(handle: &mut impl PrecompileHandle, value: U256) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::unbond {
+			value: Self::balance(value)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("rebond(uint256)")]
+	fn rebond(handle: &mut impl PrecompileHandle, value: U256) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::rebond {
+			value: Self::balance(value)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("withdrawUnbonded(uint32)")]
+	fn withdraw_unbonded(handle: &mut impl PrecompileHandle, num_slashing_spans: u32) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::withdraw_unbonded { num_slashing_spans };
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("nominate(bytes32[])")]
+	fn nominate(handle: &mut impl PrecompileHandle, targets: Vec<H256>) -> EvmResult {
+		let targets = targets
+			.into_iter()
+			.map(|target| Runtime::Lookup::unlookup(target.0.into()))
+			.collect();
+		Self::dispatch_call(
+			handle,
+			pallet_staking::Call::<Runtime>::nominate { targets },
+		)
+	}
+
+	#[precompile::public("chill()")]
+	fn chill(handle: &mut impl PrecompileHandle) -> EvmResult {
+		Self::dispatch_call(handle, pallet_staking::Call::<Runtime>::chill {})
+	}
+
+	#[precompile::public("setPayee(uint8)")]
+	fn set_payee(handle: &mut impl PrecompileHandle, reward_destination: u8) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::set_payee {
+			payee: Self::reward_destination(reward_destination)?,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	#[precompile::public("payoutStakers(bytes32,uint32)")]
+	fn payout_stakers(handle: &mut impl PrecompileHandle, validator: H256, era: u32) -> EvmResult {
+		let call = pallet_staking::Call::<Runtime>::payout_stakers {
+			validator_stash: validator.0.into(),
+			era,
+		};
+		Self::dispatch_call(handle, call)
+	}
+
+	fn dispatch_call(
+		handle: &mut impl PrecompileHandle,
+		call: pallet_staking::Call<Runtime>,
+	) -> EvmResult {
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		RuntimeHelper::<Runtime>::try_dispatch(handle, RawOrigin::Signed(origin).into(), call)?;
+		Ok(())
+	}
+
+	fn balance(value: U256) -> EvmResult<BalanceOf<Runtime>> {
+		value.try_into().map_err(|_| revert("value out of bounds"))
+	}
+
+	fn reward_destination(value: u8) -> EvmResult<RewardDestination<Runtime::AccountId>> {
+		match value {
+			0 => Ok(RewardDestination::Staked),
+			1 => Ok(RewardDestination::Stash),
+			2 => Ok(RewardDestination::None),
+			_ => Err(revert("invalid reward destination")),
+		}
+	}
+}
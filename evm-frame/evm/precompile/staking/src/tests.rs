@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{ExitError, Precompile, PrecompileFailure, PrecompileResult};
+use frame_support::assert_ok;
+use pallet_evm::HashedAddressMapping;
+use pallet_staking::{EraRewardPoints, ValidatorPrefs};
+use precompile_utils::{
+	testing::{alice, input, output, reverted_with, MockHandle},
+	EvmDataWriter,
+};
+use sp_core::crypto::AccountId32;
+use sp_runtime::traits::BlakeTwo256;
+
+fn alice_account() -> AccountId32 {
+	HashedAddressMapping::<BlakeTwo256>::into_account_id(alice())
+}
+
+fn validator() -> AccountId32 {
+	AccountId32::from([7u8; 32])
+}
+
+fn precompile_address() -> H160 {
+	H160::from_low_u64_be(2051)
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(alice_account(), 10_000), (validator(), 10_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		assert_ok!(Staking::bond(
+			RuntimeOrigin::signed(validator()),
+			validator(),
+			1_000,
+			RewardDestination::Staked
+		));
+		assert_ok!(Staking::validate(
+			RuntimeOrigin::signed(validator()),
+			ValidatorPrefs::default()
+		));
+	});
+	ext
+}
+
+fn call(input: EvmDataWriter) -> PrecompileResult {
+	let mut handle = MockHandle::new(precompile_address(), alice(), input.build());
+	StakingPrecompile::<Test>::execute(&mut handle)
+}
+
+fn ledger() -> (U256, U256) {
+	output(call(input("ledger(address)").write(alice())))
+}
+
+#[test]
+fn bond_and_nominate_from_evm() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ledger(), (U256::zero(), U256::zero()));
+
+		output::<()>(call(
+			input("bond(uint256,uint8)")
+				.write(U256::from(500))
+				.write(0u8),
+		));
+		assert_eq!(ledger(), (U256::from(500), U256::from(500)));
+		assert_eq!(Staking::bonded(alice_account()), Some(alice_account()));
+
+		let targets = vec![H256(validator().into())];
+		output::<()>(call(input("nominate(bytes32[])").write(targets.clone())));
+		assert_eq!(
+			output::<Vec<H256>>(call(input("nominations(address)").write(alice()))),
+			targets
+		);
+
+		output::<()>(call(input("chill()")));
+		assert!(output::<Vec<H256>>(call(input("nominations(address)").write(alice()))).is_empty());
+	});
+}
+
+#[test]
+fn unbond_and_rebond_update_ledger() {
+	new_test_ext().execute_with(|| {
+		output::<()>(call(
+			input("bond(uint256,uint8)")
+				.write(U256::from(500))
+				.write(1u8),
+		));
+		output::<()>(call(input("bondExtra(uint256)").write(U256::from(100))));
+		assert_eq!(ledger(), (U256::from(600), U256::from(600)));
+
+		output::<()>(call(input("unbond(uint256)").write(U256::from(200))));
+		assert_eq!(ledger(), (U256::from(600), U256::from(400)));
+
+		output::<()>(call(input("rebond(uint256)").write(U256::from(50))));
+		assert_eq!(ledger(), (U256::from(600), U256::from(450)));
+	});
+}
+
+#[test]
+fn failed_dispatch_reverts_with_the_error() {
+	new_test_ext().execute_with(|| {
+		assert!(reverted_with(
+			call(input("unbond(uint256)").write(U256::from(1))),
+			"dispatch execution failed: NotController"
+		));
+		assert!(reverted_with(
+			call(
+				input("bond(uint256,uint8)")
+					.write(U256::from(500))
+					.write(3u8)
+			),
+			"invalid reward destination"
+		));
+	});
+}
+
+#[test]
+fn dispatch_needs_gas_for_the_call_weight() {
+	new_test_ext().execute_with(|| {
+		let input = input("bond(uint256,uint8)")
+			.write(U256::from(500))
+			.write(0u8)
+			.build();
+		let mut handle = MockHandle::new(precompile_address(), alice(), input);
+		handle.gas_limit = Some(1);
+		assert_eq!(
+			StakingPrecompile::<Test>::execute(&mut handle).err(),
+			Some(PrecompileFailure::Error {
+				exit_status: ExitError::OutOfGas
+			})
+		);
+		assert_eq!(Staking::bonded(alice_account()), None);
+	});
+}
+
+#[test]
+fn payout_queries_read_era_rewards() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(output::<u32>(call(input("currentEra()"))), 0);
+
+		pallet_staking::CurrentEra::<Test>::put(3);
+		pallet_staking::ErasValidatorReward::<Test>::insert(3, 1_000);
+		pallet_staking::ErasRewardPoints::<Test>::insert(
+			3,
+			EraRewardPoints {
+				total: 20,
+				individual: vec![(validator(), 20)].into_iter().collect(),
+			},
+		);
+		assert_eq!(output::<u32>(call(input("currentEra()"))), 3);
+		assert_eq!(
+			output::<U256>(call(input("erasValidatorReward(uint32)").write(3u32))),
+			U256::from(1_000)
+		);
+		assert_eq!(
+			output::<u32>(call(
+				input("erasRewardPoints(uint32,bytes32)")
+					.write(3u32)
+					.write(H256(validator().into()))
+			)),
+			20
+		);
+
+		let claimed = || {
+			output::<bool>(call(
+				input("rewardsClaimed(bytes32,uint32)")
+					.write(H256(validator().into()))
+					.write(3u32),
+			))
+		};
+		assert!(!claimed());
+		pallet_staking::Ledger::<Test>::mutate(validator(), |ledger| {
+			ledger
+				.as_mut()
+				.unwrap()
+				.claimed_rewards
+				.try_push(3)
+				.unwrap()
+		});
+		assert!(claimed());
+	});
+}
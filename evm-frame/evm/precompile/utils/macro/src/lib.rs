@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedural macros of `precompile-utils`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use sha3::{Digest, Keccak256};
+use std::collections::BTreeMap;
+use syn::{
+	parse::{Parse, ParseStream},
+	parse_macro_input,
+	spanned::Spanned,
+	Error, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, LitInt, LitStr, Pat, Result, Type,
+};
+
+/// Generate the selector based dispatch of a precompile.
+///
+/// Applied to an `impl` block, every function annotated with
+/// `#[precompile::public("name(type,...)")]` becomes callable through the 4 bytes selector of
+/// its Solidity signature. Such functions take the `PrecompileHandle` first, then one argument
+/// per parameter of the signature, each implementing `EvmData`, and return an `EvmResult` of an
+/// `EvmData` type. A function can have several signatures.
+///
+/// Functions are non payable and rejected in static calls unless annotated with
+/// `#[precompile::view]` (allowed in static calls) or `#[precompile::payable]`. They are also
+/// rejected when called through `DELEGATECALL` or `CALLCODE`, which would let another contract
+/// act as its own caller, unless annotated with `#[precompile::allow_delegatecall]`.
+///
+/// The macro adds an inherent `dispatch` function and implements `Precompile` with it, unless
+/// invoked as `#[precompile(precompile_set)]`, in which case the `PrecompileSet` is implemented
+/// by hand on top of `dispatch`.
+#[proc_macro_attribute]
+pub fn precompile(attr: TokenStream, item: TokenStream) -> TokenStream {
+	let args = parse_macro_input!(attr as PrecompileArgs);
+	let item = parse_macro_input!(item as ItemImpl);
+	match expand(args, item) {
+		Ok(tokens) => tokens.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+struct PrecompileArgs {
+	precompile_set: bool,
+}
+
+impl Parse for PrecompileArgs {
+	fn parse(input: ParseStream) -> Result<Self> {
+		if input.is_empty() {
+			return Ok(Self {
+				precompile_set: false,
+			});
+		}
+		let ident: Ident = input.parse()?;
+		if ident != "precompile_set" {
+			return Err(Error::new(ident.span(), "expected `precompile_set`"));
+		}
+		Ok(Self {
+			precompile_set: true,
+		})
+	}
+}
+
+enum Modifier {
+	View,
+	NonPayable,
+	Payable,
+}
+
+struct Function {
+	name: Ident,
+	signatures: Vec<LitStr>,
+	modifier: Modifier,
+	allow_delegatecall: bool,
+	args: Vec<(Ident, Type)>,
+}
+
+fn expand(args: PrecompileArgs, mut item: ItemImpl) -> Result<TokenStream2> {
+	let mut functions = Vec::new();
+	for impl_item in item.items.iter_mut() {
+		if let ImplItem::Method(method) = impl_item {
+			if let Some(function) = parse_function(method)? {
+				functions.push(function);
+			}
+		}
+	}
+	if functions.is_empty() {
+		return Err(Error::new(
+			item.span(),
+			"no function is annotated with `#[precompile::public]`",
+		));
+	}
+
+	let mut selectors = BTreeMap::new();
+	let mut arms = Vec::new();
+	for function in &functions {
+		let name = &function.name;
+		let modifier = match function.modifier {
+			Modifier::View => quote!(View),
+			Modifier::NonPayable => quote!(NonPayable),
+			Modifier::Payable => quote!(Payable),
+		};
+		let check_delegatecall = if function.allow_delegatecall {
+			quote!()
+		} else {
+			quote!(handle.check_no_delegatecall()?;)
+		};
+		let arg_names: Vec<_> = function.args.iter().map(|(name, _)| name).collect();
+		let arg_types: Vec<_> = function.args.iter().map(|(_, ty)| ty).collect();
+		let read_args = if arg_names.is_empty() {
+			quote!()
+		} else {
+			quote! {
+				let (#(#arg_names,)*) = {
+					let mut reader =
+						::precompile_utils::EvmDataReader::new_skip_selector(handle.input())?;
+					(#(reader.read::<#arg_types>()?,)*)
+				};
+			}
+		};
+
+		for signature in &function.signatures {
+			let selector = selector(&signature.value());
+			if let Some(other) = selectors.insert(selector, signature.value()) {
+				return Err(Error::new(
+					signature.span(),
+					format!("selector collides with the one of `{}`", other),
+				));
+			}
+			let selector = LitInt::new(&format!("0x{:08x}_u32", selector), Span::call_site());
+			arms.push(quote! {
+				#selector => {
+					#check_delegatecall
+					handle.check_function_modifier(
+						::precompile_utils::FunctionModifier::#modifier,
+					)?;
+					#read_args
+					let output = Self::#name(handle, #(#arg_names),*)?;
+					Ok(::precompile_utils::succeed(
+						::precompile_utils::EvmDataWriter::new().write(output).build(),
+					))
+				}
+			});
+		}
+	}
+
+	let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+	let self_ty = &item.self_ty;
+	let precompile_impl = if args.precompile_set {
+		quote!()
+	} else {
+		quote! {
+			impl #impl_generics ::precompile_utils::fp_evm::Precompile for #self_ty #where_clause {
+				fn execute(
+					handle: &mut impl ::precompile_utils::fp_evm::PrecompileHandle,
+				) -> ::precompile_utils::fp_evm::PrecompileResult {
+					Self::dispatch(handle)
+				}
+			}
+		}
+	};
+
+	Ok(quote! {
+		#item
+
+		impl #impl_generics #self_ty #where_clause {
+			/// Execute the function matching the selector of the input.
+			pub fn dispatch(
+				handle: &mut impl ::precompile_utils::fp_evm::PrecompileHandle,
+			) -> ::precompile_utils::fp_evm::PrecompileResult {
+				use ::precompile_utils::PrecompileHandleExt as _;
+
+				match handle.read_selector()? {
+					#(#arms)*
+					_ => Err(::precompile_utils::revert("unknown selector")),
+				}
+			}
+		}
+
+		#precompile_impl
+	})
+}
+
+/// Extract the precompile attributes of a function, leaving the other attributes untouched.
+fn parse_function(method: &mut ImplItemMethod) -> Result<Option<Function>> {
+	let mut signatures = Vec::new();
+	let mut modifier = None;
+	let mut allow_delegatecall = false;
+	let mut errors = Vec::new();
+	method.attrs.retain(|attr| {
+		let segments: Vec<_> = attr
+			.path
+			.segments
+			.iter()
+			.map(|segment| segment.ident.to_string())
+			.collect();
+		if segments.len() != 2 || segments[0] != "precompile" {
+			return true;
+		}
+		let attr_modifier = match segments[1].as_str() {
+			"public" => {
+				match attr.parse_args::<LitStr>() {
+					Ok(signature) => signatures.push(signature),
+					Err(e) => errors.push(e),
+				}
+				return false;
+			}
+			"allow_delegatecall" => {
+				allow_delegatecall = true;
+				return false;
+			}
+			"view" => Modifier::View,
+			"payable" => Modifier::Payable,
+			_ => {
+				errors.push(Error::new(attr.span(), "unknown precompile attribute"));
+				return false;
+			}
+		};
+		if modifier.replace(attr_modifier).is_some() {
+			errors.push(Error::new(
+				attr.span(),
+				"a function is either view, payable or neither",
+			));
+		}
+		false
+	});
+	if let Some(error) = errors.into_iter().next() {
+		return Err(error);
+	}
+	if signatures.is_empty() {
+		if modifier.is_some() || allow_delegatecall {
+			return Err(Error::new(
+				method.sig.span(),
+				"only `#[precompile::public]` functions can be view, payable or allow delegatecall",
+			));
+		}
+		return Ok(None);
+	}
+
+	let mut inputs = method.sig.inputs.iter();
+	match inputs.next() {
+		Some(FnArg::Typed(_)) => {}
+		_ => {
+			return Err(Error::new(
+				method.sig.span(),
+				"the first argument must be the precompile handle",
+			))
+		}
+	}
+	let args = inputs
+		.map(|input| match input {
+			FnArg::Typed(arg) => match &*arg.pat {
+				Pat::Ident(pat) => Ok((pat.ident.clone(), (*arg.ty).clone())),
+				pat => Err(Error::new(
+					pat.span(),
+					"arguments must be plain identifiers",
+				)),
+			},
+			FnArg::Receiver(receiver) => Err(Error::new(
+				receiver.span(),
+				"precompile functions cannot take `self`",
+			)),
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	for signature in &signatures {
+		match parameter_count(&signature.value()) {
+			Some(count) if count == args.len() => {}
+			Some(count) => {
+				return Err(Error::new(
+					signature.span(),
+					format!(
+						"the signature has {} parameters but the function takes {}",
+						count,
+						args.len()
+					),
+				))
+			}
+			None => return Err(Error::new(signature.span(), "invalid Solidity signature")),
+		}
+	}
+
+	Ok(Some(Function {
+		name: method.sig.ident.clone(),
+		signatures,
+		modifier: modifier.unwrap_or(Modifier::NonPayable),
+		allow_delegatecall,
+		args,
+	}))
+}
+
+/// Number of top level parameters of a signature such as `transfer(address,uint256)`.
+fn parameter_count(signature: &str) -> Option<usize> {
+	let open = signature.find('(')?;
+	if open == 0 || !signature.ends_with(')') {
+		return None;
+	}
+	let params = &signature[open + 1..signature.len() - 1];
+	if params.is_empty() {
+		return Some(0);
+	}
+
+	let mut depth = 0usize;
+	let mut count = 1;
+	for c in params.chars() {
+		match c {
+			'(' => depth += 1,
+			')' => depth = depth.checked_sub(1)?,
+			',' if depth == 0 => count += 1,
+			c if c.is_whitespace() => return None,
+			_ => {}
+		}
+	}
+	if depth == 0 {
+		Some(count)
+	} else {
+		None
+	}
+}
+
+fn selector(signature: &str) -> u32 {
+	let hash = Keccak256::digest(signature.as_bytes());
+	u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
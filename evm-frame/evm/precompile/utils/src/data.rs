@@ -0,0 +1,429 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{revert, EvmResult};
+use alloc::vec::Vec;
+use sp_core::{H160, H256, U256};
+
+/// Values read from and written to ABI encoded data.
+pub trait EvmData: Sized {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self>;
+
+	fn write(writer: &mut EvmDataWriter, value: Self);
+
+	/// Whether the value is encoded in place, rather than behind an offset.
+	fn has_static_size() -> bool;
+}
+
+/// Reader of ABI encoded data.
+#[derive(Clone, Copy, Debug)]
+pub struct EvmDataReader<'a> {
+	input: &'a [u8],
+	cursor: usize,
+}
+
+impl<'a> EvmDataReader<'a> {
+	pub fn new(input: &'a [u8]) -> Self {
+		Self { input, cursor: 0 }
+	}
+
+	/// Reader of the arguments of a call, after its selector.
+	pub fn new_skip_selector(input: &'a [u8]) -> EvmResult<Self> {
+		input
+			.get(4..)
+			.map(Self::new)
+			.ok_or_else(|| revert("tried to parse selector out of bounds"))
+	}
+
+	/// Selector of a call.
+	pub fn read_selector(input: &[u8]) -> EvmResult<u32> {
+		input
+			.get(..4)
+			.map(|selector| {
+				u32::from_be_bytes([selector[0], selector[1], selector[2], selector[3]])
+			})
+			.ok_or_else(|| revert("tried to parse selector out of bounds"))
+	}
+
+	pub fn read<T: EvmData>(&mut self) -> EvmResult<T> {
+		T::read(self)
+	}
+
+	/// Read raw bytes, without any padding.
+	pub fn read_raw_bytes(&mut self, len: usize) -> EvmResult<&'a [u8]> {
+		let end = self
+			.cursor
+			.checked_add(len)
+			.ok_or_else(|| revert("data length overflow"))?;
+		let data = self
+			.input
+			.get(self.cursor..end)
+			.ok_or_else(|| revert("tried to parse data out of bounds"))?;
+		self.cursor = end;
+		Ok(data)
+	}
+
+	/// Follow the offset at the cursor, relative to the start of this reader, to a dynamic
+	/// value.
+	pub fn read_pointer(&mut self) -> EvmResult<Self> {
+		let offset = self.read_usize()?;
+		let input = self
+			.input
+			.get(offset..)
+			.ok_or_else(|| revert("offset points out of bounds"))?;
+		Ok(Self::new(input))
+	}
+
+	fn read_word(&mut self) -> EvmResult<&'a [u8]> {
+		self.read_raw_bytes(32)
+	}
+
+	fn read_usize(&mut self) -> EvmResult<usize> {
+		let value = self.read::<U256>()?;
+		if value > U256::from(u32::MAX) {
+			return Err(revert("offset or length too large"));
+		}
+		Ok(value.low_u32() as usize)
+	}
+
+	/// Reader of what follows the cursor.
+	fn remaining(&self) -> Self {
+		Self::new(&self.input[self.cursor..])
+	}
+}
+
+/// Writer of ABI encoded data.
+#[derive(Clone, Debug, Default)]
+pub struct EvmDataWriter {
+	data: Vec<u8>,
+	offset_data: Vec<OffsetDatum>,
+	selector: Option<u32>,
+}
+
+/// Dynamic value written after the static part of the data, behind an offset.
+#[derive(Clone, Debug)]
+struct OffsetDatum {
+	/// Position of the offset in the static part.
+	offset_position: usize,
+	data: Vec<u8>,
+	/// Distance between the start of the data and the start of the container of the value,
+	/// which is not the same for the items of an array, as their offsets skip the length.
+	offset_shift: usize,
+}
+
+impl EvmDataWriter {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Writer of the input of a call to `selector`.
+	pub fn new_with_selector(selector: u32) -> Self {
+		Self {
+			selector: Some(selector),
+			..Default::default()
+		}
+	}
+
+	pub fn build(mut self) -> Vec<u8> {
+		Self::bake_offsets(&mut self.data, self.offset_data);
+		match self.selector {
+			Some(selector) => {
+				let mut output = selector.to_be_bytes().to_vec();
+				output.append(&mut self.data);
+				output
+			}
+			None => self.data,
+		}
+	}
+
+	pub fn write<T: EvmData>(mut self, value: T) -> Self {
+		T::write(&mut self, value);
+		self
+	}
+
+	/// Write raw bytes, without any padding.
+	pub fn write_raw_bytes(mut self, value: &[u8]) -> Self {
+		self.data.extend_from_slice(value);
+		self
+	}
+
+	/// Write a placeholder offset, set to the position of `data` once appended by `build`.
+	fn write_pointer(&mut self, data: Vec<u8>) {
+		let offset_position = self.data.len();
+		H256::write(self, H256::repeat_byte(0xff));
+		self.offset_data.push(OffsetDatum {
+			offset_position,
+			data,
+			offset_shift: 0,
+		});
+	}
+
+	fn bake_offsets(output: &mut Vec<u8>, offsets: Vec<OffsetDatum>) {
+		for mut datum in offsets {
+			let position = datum.offset_position;
+			let offset = output.len() - datum.offset_shift;
+			U256::from(offset).to_big_endian(&mut output[position..position + 32]);
+			output.append(&mut datum.data);
+		}
+	}
+}
+
+impl EvmData for () {
+	fn read(_reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(())
+	}
+
+	fn write(_writer: &mut EvmDataWriter, _value: Self) {}
+
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+impl EvmData for H256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(H256::from_slice(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		writer.data.extend_from_slice(value.as_bytes());
+	}
+
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+impl EvmData for H160 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let word = reader.read_word()?;
+		if word[..12].iter().any(|byte| *byte != 0) {
+			return Err(revert("invalid address"));
+		}
+		Ok(H160::from_slice(&word[12..]))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		H256::write(writer, value.into());
+	}
+
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+impl EvmData for U256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(U256::from_big_endian(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut word = [0u8; 32];
+		value.to_big_endian(&mut word);
+		writer.data.extend_from_slice(&word);
+	}
+
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+impl EvmData for bool {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		match reader.read::<U256>()? {
+			value if value.is_zero() => Ok(false),
+			value if value == U256::one() => Ok(true),
+			_ => Err(revert("invalid bool")),
+		}
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		U256::write(writer, (value as u8).into());
+	}
+
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+/// Integers are big endian, padded to a word with their sign.
+macro_rules! impl_evmdata_for_integers {
+	($($ty:ty),*) => {$(
+		impl EvmData for $ty {
+			fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+				const SIZE: usize = core::mem::size_of::<$ty>();
+				let (padding, bytes) = reader.read_word()?.split_at(32 - SIZE);
+				let mut value = [0u8; SIZE];
+				value.copy_from_slice(bytes);
+				let value = <$ty>::from_be_bytes(value);
+				#[allow(unused_comparisons)]
+				let fill = if value < 0 { 0xff } else { 0 };
+				if padding.iter().any(|byte| *byte != fill) {
+					return Err(revert("value too large for type"));
+				}
+				Ok(value)
+			}
+
+			fn write(writer: &mut EvmDataWriter, value: Self) {
+				#[allow(unused_comparisons)]
+				let mut word = if value < 0 { [0xff; 32] } else { [0u8; 32] };
+				word[32 - core::mem::size_of::<$ty>()..].copy_from_slice(&value.to_be_bytes());
+				writer.data.extend_from_slice(&word);
+			}
+
+			fn has_static_size() -> bool {
+				true
+			}
+		}
+	)*};
+}
+
+impl_evmdata_for_integers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Dynamic `bytes`, also used for `string` as they share their encoding.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+
+	pub fn as_str(&self) -> Result<&str, core::str::Utf8Error> {
+		core::str::from_utf8(&self.0)
+	}
+}
+
+impl From<&[u8]> for Bytes {
+	fn from(value: &[u8]) -> Self {
+		Self(value.to_vec())
+	}
+}
+
+impl From<&str> for Bytes {
+	fn from(value: &str) -> Self {
+		value.as_bytes().into()
+	}
+}
+
+impl From<Vec<u8>> for Bytes {
+	fn from(value: Vec<u8>) -> Self {
+		Self(value)
+	}
+}
+
+impl From<Bytes> for Vec<u8> {
+	fn from(value: Bytes) -> Self {
+		value.0
+	}
+}
+
+impl EvmData for Bytes {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len = inner.read_usize()?;
+		Ok(inner.read_raw_bytes(len)?.into())
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let len = value.0.len();
+		let mut data = value.0;
+		data.resize((len + 31) / 32 * 32, 0);
+		writer.write_pointer(
+			EvmDataWriter::new()
+				.write(U256::from(len))
+				.write_raw_bytes(&data)
+				.build(),
+		);
+	}
+
+	fn has_static_size() -> bool {
+		false
+	}
+}
+
+impl<T: EvmData> EvmData for Vec<T> {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len = inner.read_usize()?;
+		// Offsets of the items are relative to the first item, after the length.
+		let mut items = inner.remaining();
+		let mut array = Vec::new();
+		for _ in 0..len {
+			array.push(items.read()?);
+		}
+		Ok(array)
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut inner = EvmDataWriter::new().write(U256::from(value.len()));
+		for item in value {
+			let shift = inner.data.len();
+			let item_writer = EvmDataWriter::new().write(item);
+			inner.data.extend_from_slice(&item_writer.data);
+			for mut datum in item_writer.offset_data {
+				datum.offset_position += shift;
+				datum.offset_shift += 32;
+				inner.offset_data.push(datum);
+			}
+		}
+		writer.write_pointer(inner.build());
+	}
+
+	fn has_static_size() -> bool {
+		false
+	}
+}
+
+/// Tuples are encoded in place when all their members are, and behind an offset otherwise.
+macro_rules! impl_evmdata_for_tuples {
+	($(($($ident:ident: $index:tt),+)),+) => {$(
+		impl<$($ident: EvmData),+> EvmData for ($($ident,)+) {
+			fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+				if Self::has_static_size() {
+					Ok(($($ident::read(reader)?,)+))
+				} else {
+					let reader = &mut reader.read_pointer()?;
+					Ok(($($ident::read(reader)?,)+))
+				}
+			}
+
+			fn write(writer: &mut EvmDataWriter, value: Self) {
+				if Self::has_static_size() {
+					$($ident::write(writer, value.$index);)+
+				} else {
+					let mut inner = EvmDataWriter::new();
+					$(inner = inner.write(value.$index);)+
+					writer.write_pointer(inner.build());
+				}
+			}
+
+			fn has_static_size() -> bool {
+				$($ident::has_static_size())&&+
+			}
+		}
+	)+};
+}
+
+impl_evmdata_for_tuples!(
+	(A: 0),
+	(A: 0, B: 1),
+	(A: 0, B: 1, C: 2),
+	(A: 0, B: 1, C: 2, D: 3),
+	(A: 0, B: 1, C: 2, D: 3, E: 4),
+	(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5)
+);
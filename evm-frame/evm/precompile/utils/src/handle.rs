@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{logs::log_costs, revert, EvmDataReader, EvmResult, RuntimeHelper};
+use fp_evm::PrecompileHandle;
+use frame_support::weights::Weight;
+use pallet_evm::GasWeightMapping;
+
+/// What a function of a precompile allows of its callers, as its Solidity modifier would.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FunctionModifier {
+	/// Callable in a static context, without value.
+	View,
+	/// Modifies state, without value.
+	NonPayable,
+	/// Modifies state, with or without value.
+	Payable,
+}
+
+/// Helpers of the precompile handle, charging runtime costs as gas.
+pub trait PrecompileHandleExt: PrecompileHandle {
+	/// Record the cost of `count` storage reads.
+	fn record_db_read<Runtime: pallet_evm::Config>(&mut self, count: u64) -> EvmResult;
+
+	/// Record the cost of `count` storage writes.
+	fn record_db_write<Runtime: pallet_evm::Config>(&mut self, count: u64) -> EvmResult;
+
+	/// Record the cost of `weight`, through `GasWeightMapping`.
+	fn record_weight<Runtime: pallet_evm::Config>(&mut self, weight: Weight) -> EvmResult;
+
+	/// Record the cost of a log, before building it.
+	fn record_log_costs_manual(&mut self, topics: usize, data_len: usize) -> EvmResult;
+
+	/// Selector of the called function.
+	fn read_selector(&self) -> EvmResult<u32>;
+
+	/// Reject calls that `modifier` does not allow.
+	fn check_function_modifier(&self, modifier: FunctionModifier) -> EvmResult;
+
+	/// Reject calls through `DELEGATECALL` or `CALLCODE`, in which the precompile runs on
+	/// behalf of the calling contract rather than of its caller.
+	fn check_no_delegatecall(&self) -> EvmResult;
+}
+
+impl<T: PrecompileHandle> PrecompileHandleExt for T {
+	fn record_db_read<Runtime: pallet_evm::Config>(&mut self, count: u64) -> EvmResult {
+		self.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost().saturating_mul(count))?;
+		Ok(())
+	}
+
+	fn record_db_write<Runtime: pallet_evm::Config>(&mut self, count: u64) -> EvmResult {
+		self.record_cost(RuntimeHelper::<Runtime>::db_write_gas_cost().saturating_mul(count))?;
+		Ok(())
+	}
+
+	fn record_weight<Runtime: pallet_evm::Config>(&mut self, weight: Weight) -> EvmResult {
+		self.record_cost(Runtime::GasWeightMapping::weight_to_gas(weight))?;
+		Ok(())
+	}
+
+	fn record_log_costs_manual(&mut self, topics: usize, data_len: usize) -> EvmResult {
+		self.record_cost(log_costs(topics, data_len)?)?;
+		Ok(())
+	}
+
+	fn read_selector(&self) -> EvmResult<u32> {
+		EvmDataReader::read_selector(self.input())
+	}
+
+	fn check_function_modifier(&self, modifier: FunctionModifier) -> EvmResult {
+		if modifier != FunctionModifier::Payable && !self.context().apparent_value.is_zero() {
+			return Err(revert("function is not payable"));
+		}
+		if modifier != FunctionModifier::View && self.is_static() {
+			return Err(revert("cannot modify state in static context"));
+		}
+		Ok(())
+	}
+
+	fn check_no_delegatecall(&self) -> EvmResult {
+		if self.code_address() != self.context().address {
+			return Err(revert("cannot be called with DELEGATECALL or CALLCODE"));
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to write EVM precompiles against the Solidity ABI.
+//!
+//! - [`EvmDataReader`] and [`EvmDataWriter`] decode and encode ABI values, see [`EvmData`].
+//! - [`precompile`] dispatches calls to the functions of a precompile by selector.
+//! - [`PrecompileHandleExt`] charges storage accesses through `GasWeightMapping`, and
+//!   [`RuntimeHelper`] dispatches runtime calls from a precompile.
+//! - [`revert`] reverts with an ABI encoded `Error(string)` reason.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// The code generated by `precompile` refers to this crate by name, as in its tests.
+#[cfg(test)]
+extern crate self as precompile_utils;
+
+mod data;
+mod handle;
+mod logs;
+mod substrate;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+#[cfg(test)]
+mod tests;
+
+pub use data::{Bytes, EvmData, EvmDataReader, EvmDataWriter};
+pub use handle::{FunctionModifier, PrecompileHandleExt};
+pub use logs::{log0, log1, log2, log3, log4, LogExt};
+pub use precompile_utils_macro::precompile;
+pub use substrate::RuntimeHelper;
+
+#[doc(hidden)]
+pub use fp_evm;
+
+use alloc::vec::Vec;
+use fp_evm::{ExitRevert, ExitSucceed, PrecompileFailure, PrecompileOutput};
+
+/// Result of the functions of a precompile.
+pub type EvmResult<T = ()> = Result<T, PrecompileFailure>;
+
+/// Selector of `Error(string)`, the reason of Solidity reverts.
+pub const ERROR_SELECTOR: u32 = 0x08c379a0;
+
+/// Revert with a reason, encoded as Solidity does.
+pub fn revert(message: impl AsRef<[u8]>) -> PrecompileFailure {
+	PrecompileFailure::Revert {
+		exit_status: ExitRevert::Reverted,
+		output: EvmDataWriter::new_with_selector(ERROR_SELECTOR)
+			.write(Bytes::from(message.as_ref()))
+			.build(),
+	}
+}
+
+/// Successful output of a precompile.
+pub fn succeed(output: Vec<u8>) -> PrecompileOutput {
+	PrecompileOutput {
+		exit_status: ExitSucceed::Returned,
+		output,
+	}
+}
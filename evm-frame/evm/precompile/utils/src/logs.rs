@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{revert, EvmResult};
+use alloc::{vec, vec::Vec};
+use fp_evm::{Log, PrecompileHandle};
+use sp_core::{H160, H256};
+
+/// Gas of a log, as charged by the `LOG` opcodes.
+pub(crate) fn log_costs(topics: usize, data_len: usize) -> EvmResult<u64> {
+	const G_LOG: u64 = 375;
+	const G_LOGDATA: u64 = 8;
+	const G_LOGTOPIC: u64 = 375;
+
+	(data_len as u64)
+		.checked_mul(G_LOGDATA)
+		.and_then(|cost| cost.checked_add(G_LOG + G_LOGTOPIC * topics as u64))
+		.ok_or_else(|| revert("log too large"))
+}
+
+pub fn log0(address: impl Into<H160>, data: impl Into<Vec<u8>>) -> Log {
+	Log {
+		address: address.into(),
+		topics: vec![],
+		data: data.into(),
+	}
+}
+
+pub fn log1(address: impl Into<H160>, topic0: impl Into<H256>, data: impl Into<Vec<u8>>) -> Log {
+	Log {
+		address: address.into(),
+		topics: vec![topic0.into()],
+		data: data.into(),
+	}
+}
+
+pub fn log2(
+	address: impl Into<H160>,
+	topic0: impl Into<H256>,
+	topic1: impl Into<H256>,
+	data: impl Into<Vec<u8>>,
+) -> Log {
+	Log {
+		address: address.into(),
+		topics: vec![topic0.into(), topic1.into()],
+		data: data.into(),
+	}
+}
+
+pub fn log3(
+	address: impl Into<H160>,
+	topic0: impl Into<H256>,
+	topic1: impl Into<H256>,
+	topic2: impl Into<H256>,
+	data: impl Into<Vec<u8>>,
+) -> Log {
+	Log {
+		address: address.into(),
+		topics: vec![topic0.into(), topic1.into(), topic2.into()],
+		data: data.into(),
+	}
+}
+
+pub fn log4(
+	address: impl Into<H160>,
+	topic0: impl Into<H256>,
+	topic1: impl Into<H256>,
+	topic2: impl Into<H256>,
+	topic3: impl Into<H256>,
+	data: impl Into<Vec<u8>>,
+) -> Log {
+	Log {
+		address: address.into(),
+		topics: vec![topic0.into(), topic1.into(), topic2.into(), topic3.into()],
+		data: data.into(),
+	}
+}
+
+/// Emission of logs built by [`log0`] to [`log4`].
+pub trait LogExt {
+	/// Charge the gas of the log and emit it.
+	fn record(self, handle: &mut impl PrecompileHandle) -> EvmResult;
+
+	fn compute_cost(&self) -> EvmResult<u64>;
+}
+
+impl LogExt for Log {
+	fn record(self, handle: &mut impl PrecompileHandle) -> EvmResult {
+		handle.record_cost(self.compute_cost()?)?;
+		handle.log(self.address, self.topics, self.data)?;
+		Ok(())
+	}
+
+	fn compute_cost(&self) -> EvmResult<u64> {
+		log_costs(self.topics.len(), self.data.len())
+	}
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{revert, EvmResult};
+use alloc::format;
+use core::marker::PhantomData;
+use fp_evm::{ExitError, PrecompileFailure, PrecompileHandle};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::Get,
+};
+use pallet_evm::GasWeightMapping;
+
+/// Access to the runtime from a precompile.
+pub struct RuntimeHelper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime: pallet_evm::Config> RuntimeHelper<Runtime> {
+	/// Dispatch `call` from `origin`, charging its weight as gas.
+	///
+	/// The call is rejected before dispatch if the gas left cannot pay its declared weight, so
+	/// that a precompile cannot run a call it would not be able to pay for.
+	pub fn try_dispatch<Call>(
+		handle: &mut impl PrecompileHandle,
+		origin: Runtime::RuntimeOrigin,
+		call: Call,
+	) -> EvmResult<PostDispatchInfo>
+	where
+		Runtime::RuntimeCall:
+			From<Call> + Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo,
+	{
+		let call = Runtime::RuntimeCall::from(call);
+		let dispatch_info = call.get_dispatch_info();
+
+		let required_gas = Runtime::GasWeightMapping::weight_to_gas(dispatch_info.weight);
+		if required_gas > handle.remaining_gas() {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::OutOfGas,
+			});
+		}
+
+		let post_info = call.dispatch(origin).map_err(|e| {
+			revert(format!(
+				"dispatch execution failed: {}",
+				<&'static str>::from(e.error)
+			))
+		})?;
+
+		let used_weight = post_info.calc_actual_weight(&dispatch_info);
+		handle.record_cost(Runtime::GasWeightMapping::weight_to_gas(used_weight))?;
+		Ok(post_info)
+	}
+
+	/// Gas of a storage read.
+	pub fn db_read_gas_cost() -> u64 {
+		Runtime::GasWeightMapping::weight_to_gas(Runtime::DbWeight::get().reads(1))
+	}
+
+	/// Gas of a storage write.
+	pub fn db_write_gas_cost() -> u64 {
+		Runtime::GasWeightMapping::weight_to_gas(Runtime::DbWeight::get().writes(1))
+	}
+}
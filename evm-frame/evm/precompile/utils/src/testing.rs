@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mock precompile handle and helpers, for the unit tests of precompiles.
+
+use crate::{Bytes, EvmData, EvmDataReader, EvmDataWriter, ERROR_SELECTOR};
+use alloc::{boxed::Box, vec::Vec};
+use fp_evm::{
+	Context, ExitError, ExitReason, Log, PrecompileFailure, PrecompileHandle, PrecompileResult,
+	Transfer,
+};
+use sp_core::{hashing::keccak_256, H160, H256, U256};
+
+/// Callers of the precompiles in tests.
+pub fn alice() -> H160 {
+	H160::from_low_u64_be(1)
+}
+
+pub fn bob() -> H160 {
+	H160::from_low_u64_be(2)
+}
+
+pub fn charlie() -> H160 {
+	H160::from_low_u64_be(3)
+}
+
+/// Selector of a Solidity function signature such as `transfer(address,uint256)`.
+pub fn selector(signature: &str) -> u32 {
+	let hash = keccak_256(signature.as_bytes());
+	u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// Input calling the function of `signature`, to which its arguments are then written.
+pub fn input(signature: &str) -> EvmDataWriter {
+	EvmDataWriter::new_with_selector(selector(signature))
+}
+
+/// Output of a successful call, panicking if the call failed.
+pub fn output<T: EvmData>(result: PrecompileResult) -> T {
+	match result {
+		Ok(output) => EvmDataReader::new(&output.output).read().unwrap(),
+		Err(e) => panic!("precompile failed: {:?}", e),
+	}
+}
+
+/// Whether a call reverted with `reason`, as encoded by [`crate::revert`].
+pub fn reverted_with(result: PrecompileResult, reason: &str) -> bool {
+	let expected = EvmDataWriter::new_with_selector(ERROR_SELECTOR)
+		.write(Bytes::from(reason))
+		.build();
+	matches!(result, Err(PrecompileFailure::Revert { output, .. }) if output == expected)
+}
+
+/// Subcall made by a precompile through [`MockHandle::call`].
+pub struct Subcall {
+	pub address: H160,
+	pub transfer: Option<Transfer>,
+	pub input: Vec<u8>,
+	pub target_gas: Option<u64>,
+	pub is_static: bool,
+	pub context: Context,
+}
+
+/// Outcome of a [`Subcall`], as produced by the subcall handler of a [`MockHandle`].
+pub struct SubcallOutput {
+	pub reason: ExitReason,
+	pub output: Vec<u8>,
+	/// Gas used by the subcall.
+	pub cost: u64,
+	/// Logs emitted by the subcall, kept only if it succeeds.
+	pub logs: Vec<Log>,
+}
+
+/// Precompile handle recording the gas and the logs of a call.
+pub struct MockHandle {
+	pub input: Vec<u8>,
+	pub context: Context,
+	pub code_address: H160,
+	pub is_static: bool,
+	/// Unlimited gas when `None`.
+	pub gas_limit: Option<u64>,
+	pub used_gas: u64,
+	pub logs: Vec<Log>,
+	/// Answers the subcalls, which panic when `None`.
+	pub subcall_handle: Option<Box<dyn FnMut(Subcall) -> SubcallOutput>>,
+}
+
+impl MockHandle {
+	/// Call of `caller` to the precompile at `code_address`, without value.
+	pub fn new(code_address: H160, caller: H160, input: Vec<u8>) -> Self {
+		Self {
+			input,
+			context: Context {
+				address: code_address,
+				caller,
+				apparent_value: U256::zero(),
+			},
+			code_address,
+			is_static: false,
+			gas_limit: None,
+			used_gas: 0,
+			logs: Vec::new(),
+			subcall_handle: None,
+		}
+	}
+
+	/// Answer the subcalls of the precompile with `subcall_handle`.
+	pub fn with_subcall_handle(
+		mut self,
+		subcall_handle: impl FnMut(Subcall) -> SubcallOutput + 'static,
+	) -> Self {
+		self.subcall_handle = Some(Box::new(subcall_handle));
+		self
+	}
+}
+
+impl PrecompileHandle for MockHandle {
+	fn call(
+		&mut self,
+		address: H160,
+		transfer: Option<Transfer>,
+		input: Vec<u8>,
+		target_gas: Option<u64>,
+		is_static: bool,
+		context: &Context,
+	) -> (ExitReason, Vec<u8>) {
+		let subcall_handle = self
+			.subcall_handle
+			.as_mut()
+			.expect("subcalls need a subcall handle, see `MockHandle::with_subcall_handle`");
+		let SubcallOutput {
+			reason,
+			output,
+			cost,
+			logs,
+		} = subcall_handle(Subcall {
+			address,
+			transfer,
+			input,
+			target_gas,
+			is_static,
+			context: context.clone(),
+		});
+
+		// Like the EVM, a subcall running out of its gas consumes all of it.
+		if let Some(target_gas) = target_gas {
+			if cost > target_gas {
+				let _ = self.record_cost(target_gas);
+				return (ExitReason::Error(ExitError::OutOfGas), Vec::new());
+			}
+		}
+		if self.record_cost(cost).is_err() {
+			return (ExitReason::Error(ExitError::OutOfGas), Vec::new());
+		}
+		if matches!(reason, ExitReason::Succeed(_)) {
+			self.logs.extend(logs);
+		}
+		(reason, output)
+	}
+
+	fn record_cost(&mut self, cost: u64) -> Result<(), ExitError> {
+		let used_gas = self.used_gas.checked_add(cost).ok_or(ExitError::OutOfGas)?;
+		if matches!(self.gas_limit, Some(limit) if used_gas > limit) {
+			return Err(ExitError::OutOfGas);
+		}
+		self.used_gas = used_gas;
+		Ok(())
+	}
+
+	fn record_external_cost(
+		&mut self,
+		_: Option<u64>,
+		_: Option<u64>,
+		_: Option<u64>,
+	) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn refund_external_cost(&mut self, _: Option<u64>, _: Option<u64>) {}
+
+	fn remaining_gas(&self) -> u64 {
+		self.gas_limit
+			.map_or(u64::MAX, |limit| limit.saturating_sub(self.used_gas))
+	}
+
+	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
+		self.logs.push(Log {
+			address,
+			topics,
+			data,
+		});
+		Ok(())
+	}
+
+	fn code_address(&self) -> H160 {
+		self.code_address
+	}
+
+	fn input(&self) -> &[u8] {
+		&self.input
+	}
+
+	fn context(&self) -> &Context {
+		&self.context
+	}
+
+	fn is_static(&self) -> bool {
+		self.is_static
+	}
+
+	fn gas_limit(&self) -> Option<u64> {
+		self.gas_limit
+	}
+}
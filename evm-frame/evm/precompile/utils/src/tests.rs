@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::testing::{input, output, reverted_with, MockHandle};
+use fp_evm::{Precompile, PrecompileHandle};
+use hex_literal::hex;
+use sp_core::{H160, H256, U256};
+
+fn word(value: u64) -> Vec<u8> {
+	H256::from_low_u64_be(value).as_bytes().to_vec()
+}
+
+fn padded(value: &[u8]) -> Vec<u8> {
+	let mut data = value.to_vec();
+	data.resize((value.len() + 31) / 32 * 32, 0);
+	data
+}
+
+fn read<T: EvmData>(input: &[u8]) -> EvmResult<T> {
+	EvmDataReader::new(input).read::<T>()
+}
+
+#[test]
+fn static_values_round_trip() {
+	let address = H160::repeat_byte(0x11);
+	let input = EvmDataWriter::new()
+		.write(address)
+		.write(U256::from(42))
+		.write(true)
+		.write(7u32)
+		.write(-2i64)
+		.build();
+	assert_eq!(input.len(), 5 * 32);
+	assert_eq!(&input[32..64], &word(42)[..]);
+	assert_eq!(&input[128..], &[&[0xff; 31][..], &[0xfe]].concat()[..]);
+
+	let mut reader = EvmDataReader::new(&input);
+	assert_eq!(reader.read::<H160>().unwrap(), address);
+	assert_eq!(reader.read::<U256>().unwrap(), U256::from(42));
+	assert!(reader.read::<bool>().unwrap());
+	assert_eq!(reader.read::<u32>().unwrap(), 7);
+	assert_eq!(reader.read::<i64>().unwrap(), -2);
+	assert!(reader.read::<U256>().is_err());
+}
+
+#[test]
+fn malformed_static_values_are_rejected() {
+	let mut dirty_address = word(1);
+	dirty_address[0] = 1;
+	assert!(read::<H160>(&dirty_address).is_err());
+	assert!(read::<bool>(&word(2)).is_err());
+	assert!(read::<u8>(&word(256)).is_err());
+	assert_eq!(read::<u8>(&word(255)).unwrap(), 255);
+	assert!(read::<i8>(&word(128)).is_err());
+	assert_eq!(read::<i8>(&[0xff; 32]).unwrap(), -1);
+	assert!(read::<U256>(&[0; 31]).is_err());
+}
+
+#[test]
+fn bytes_encoding_matches_solidity() {
+	let encoded = EvmDataWriter::new().write(Bytes::from("abc")).build();
+	assert_eq!(encoded, [word(0x20), word(3), padded(b"abc")].concat());
+	assert_eq!(read::<Bytes>(&encoded).unwrap(), Bytes::from("abc"));
+
+	let empty = EvmDataWriter::new().write(Bytes::default()).build();
+	assert_eq!(empty, [word(0x20), word(0)].concat());
+	assert_eq!(read::<Bytes>(&empty).unwrap(), Bytes::default());
+}
+
+#[test]
+fn bytes_out_of_bounds_are_rejected() {
+	// Declares 64 bytes but holds 32.
+	let input = [word(0x20), word(64), padded(b"abc")].concat();
+	assert!(read::<Bytes>(&input).is_err());
+	assert!(read::<Bytes>(&word(0x40)).is_err());
+	assert!(read::<Bytes>(&[0xff; 32]).is_err());
+}
+
+#[test]
+fn arrays_encoding_matches_solidity() {
+	let encoded = EvmDataWriter::new()
+		.write(vec![U256::from(1), U256::from(2)])
+		.build();
+	assert_eq!(encoded, [word(0x20), word(2), word(1), word(2)].concat());
+	assert_eq!(
+		read::<Vec<U256>>(&encoded).unwrap(),
+		vec![U256::from(1), U256::from(2)]
+	);
+
+	// Offsets of dynamic items are relative to the first item.
+	let items = vec![Bytes::from("a"), Bytes::from("bc")];
+	let encoded = EvmDataWriter::new().write(items.clone()).build();
+	assert_eq!(
+		encoded,
+		[
+			word(0x20),
+			word(2),
+			word(0x40),
+			word(0x80),
+			word(1),
+			padded(b"a"),
+			word(2),
+			padded(b"bc"),
+		]
+		.concat()
+	);
+	assert_eq!(read::<Vec<Bytes>>(&encoded).unwrap(), items);
+}
+
+#[test]
+fn tuples_encoding_matches_solidity() {
+	let address = H160::repeat_byte(0x22);
+	let encoded = EvmDataWriter::new().write((address, 5u32)).build();
+	assert_eq!(
+		encoded,
+		[H256::from(address).as_bytes().to_vec(), word(5)].concat()
+	);
+	assert_eq!(read::<(H160, u32)>(&encoded).unwrap(), (address, 5));
+
+	let encoded = EvmDataWriter::new()
+		.write((U256::one(), Bytes::from("abc")))
+		.build();
+	assert_eq!(
+		encoded,
+		[word(0x20), word(1), word(0x40), word(3), padded(b"abc")].concat()
+	);
+	assert_eq!(
+		read::<(U256, Bytes)>(&encoded).unwrap(),
+		(U256::one(), Bytes::from("abc"))
+	);
+}
+
+#[test]
+fn nested_values_round_trip() {
+	let calls = vec![
+		(H160::repeat_byte(1), Bytes::from("first call")),
+		(H160::repeat_byte(2), Bytes::from(&[0u8; 40][..])),
+	];
+	let encoded = EvmDataWriter::new()
+		.write(calls.clone())
+		.write(U256::from(3))
+		.build();
+	let mut reader = EvmDataReader::new(&encoded);
+	assert_eq!(reader.read::<Vec<(H160, Bytes)>>().unwrap(), calls);
+	assert_eq!(reader.read::<U256>().unwrap(), U256::from(3));
+}
+
+#[test]
+fn selector_is_written_and_skipped() {
+	let input = EvmDataWriter::new_with_selector(0xa9059cbb)
+		.write(U256::from(9))
+		.build();
+	assert_eq!(&input[..4], &hex!("a9059cbb"));
+	assert_eq!(EvmDataReader::read_selector(&input).unwrap(), 0xa9059cbb);
+	let mut reader = EvmDataReader::new_skip_selector(&input).unwrap();
+	assert_eq!(reader.read::<U256>().unwrap(), U256::from(9));
+
+	assert!(EvmDataReader::read_selector(&[0xa9, 0x05]).is_err());
+	assert!(EvmDataReader::new_skip_selector(&[0xa9]).is_err());
+}
+
+#[test]
+fn revert_encodes_error_string() {
+	match revert("too low") {
+		fp_evm::PrecompileFailure::Revert { output, .. } => assert_eq!(
+			output,
+			[
+				hex!("08c379a0").to_vec(),
+				word(0x20),
+				word(7),
+				padded(b"too low")
+			]
+			.concat()
+		),
+		failure => panic!("unexpected failure {:?}", failure),
+	}
+}
+
+#[test]
+fn log_costs_match_opcodes() {
+	let log = log3(
+		H160::zero(),
+		H256::zero(),
+		H256::zero(),
+		H256::zero(),
+		word(1),
+	);
+	assert_eq!(log.compute_cost().unwrap(), 375 + 3 * 375 + 8 * 32);
+	assert_eq!(log0(H160::zero(), Vec::new()).compute_cost().unwrap(), 375);
+}
+
+struct TestPrecompile;
+
+#[precompile]
+impl TestPrecompile {
+	#[precompile::public("value()")]
+	#[precompile::view]
+	fn value(_handle: &mut impl PrecompileHandle) -> EvmResult<U256> {
+		Ok(U256::from(7))
+	}
+
+	#[precompile::public("set(uint256)")]
+	#[precompile::public("setValue(uint256)")]
+	fn set(_handle: &mut impl PrecompileHandle, value: U256) -> EvmResult<U256> {
+		Ok(value)
+	}
+
+	#[precompile::public("deposit()")]
+	#[precompile::payable]
+	fn deposit(handle: &mut impl PrecompileHandle) -> EvmResult<U256> {
+		Ok(handle.context().apparent_value)
+	}
+
+	#[precompile::public("caller()")]
+	#[precompile::view]
+	#[precompile::allow_delegatecall]
+	fn caller(handle: &mut impl PrecompileHandle) -> EvmResult<H160> {
+		Ok(handle.context().caller)
+	}
+}
+
+fn mock_handle(input: EvmDataWriter) -> MockHandle {
+	MockHandle::new(
+		H160::from_low_u64_be(1),
+		H160::from_low_u64_be(2),
+		input.build(),
+	)
+}
+
+#[test]
+fn dispatch_matches_selectors() {
+	assert_eq!(
+		output::<U256>(TestPrecompile::execute(&mut mock_handle(input("value()")))),
+		U256::from(7)
+	);
+	for signature in ["set(uint256)", "setValue(uint256)"] {
+		let mut handle = mock_handle(input(signature).write(U256::from(3)));
+		assert_eq!(
+			output::<U256>(TestPrecompile::execute(&mut handle)),
+			U256::from(3)
+		);
+	}
+
+	assert!(reverted_with(
+		TestPrecompile::execute(&mut mock_handle(input("unknown()"))),
+		"unknown selector"
+	));
+	// Missing argument.
+	assert!(TestPrecompile::execute(&mut mock_handle(input("set(uint256)"))).is_err());
+	// Truncated selector.
+	let mut handle = mock_handle(EvmDataWriter::new());
+	handle.input = vec![0x12, 0x34];
+	assert!(TestPrecompile::execute(&mut handle).is_err());
+}
+
+#[test]
+fn dispatch_checks_function_modifiers() {
+	let mut view = mock_handle(input("value()"));
+	view.is_static = true;
+	assert_eq!(
+		output::<U256>(TestPrecompile::execute(&mut view)),
+		U256::from(7)
+	);
+
+	let mut mutating = mock_handle(input("set(uint256)").write(U256::one()));
+	mutating.is_static = true;
+	assert!(reverted_with(
+		TestPrecompile::execute(&mut mutating),
+		"cannot modify state in static context"
+	));
+
+	for signature in ["value()", "set(uint256)"] {
+		let mut handle = mock_handle(input(signature).write(U256::one()));
+		handle.context.apparent_value = U256::from(5);
+		assert!(reverted_with(
+			TestPrecompile::execute(&mut handle),
+			"function is not payable"
+		));
+	}
+	let mut payable = mock_handle(input("deposit()"));
+	payable.context.apparent_value = U256::from(5);
+	assert_eq!(
+		output::<U256>(TestPrecompile::execute(&mut payable)),
+		U256::from(5)
+	);
+}
+
+#[test]
+fn dispatch_rejects_delegatecall() {
+	// The precompile code runs on behalf of the contract at `context.address`.
+	let mut handle = mock_handle(input("value()"));
+	handle.context.address = H160::from_low_u64_be(99);
+	assert!(reverted_with(
+		TestPrecompile::execute(&mut handle),
+		"cannot be called with DELEGATECALL or CALLCODE"
+	));
+
+	handle.input = input("caller()").build();
+	assert_eq!(
+		output::<H160>(TestPrecompile::execute(&mut handle)),
+		H160::from_low_u64_be(2)
+	);
+}
@@ -49,6 +49,7 @@
 //! private key, one should be able to convert any Ethereum transaction into a transaction compatible with this pallet.
 //!
 //! The gas configurations are configurable. Right now, a pre-defined London hard fork configuration option is provided.
+//! Later hard forks, up to Cancun, are enabled with the `fork` function of the pallet configuration.
 
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -63,6 +64,8 @@ mod mock;
 pub mod runner;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "tracing")]
+pub mod tracing;
 
 use frame_support::{
 	dispatch::{DispatchResultWithPostInfo, Pays, PostDispatchInfo},
@@ -86,9 +89,9 @@ pub use evm::{
 #[cfg(feature = "std")]
 use fp_evm::GenesisAccount;
 pub use fp_evm::{
-	Account, CallInfo, CreateInfo, ExecutionInfo, FeeCalculator, InvalidEvmTransactionError,
-	LinearCostPrecompile, Log, Precompile, PrecompileFailure, PrecompileHandle, PrecompileOutput,
-	PrecompileResult, PrecompileSet, Vicinity,
+	Account, CallInfo, CallOrCreateInfo, CreateInfo, EvmFork, ExecutionInfo, FeeCalculator,
+	InvalidEvmTransactionError, LinearCostPrecompile, Log, Precompile, PrecompileFailure,
+	PrecompileHandle, PrecompileOutput, PrecompileResult, PrecompileSet, StateOverride, Vicinity,
 };
 
 pub use self::{
@@ -151,9 +154,15 @@ pub mod pallet {
 		/// Find author for the current block.
 		type FindAuthor: FindAuthor<H160>;
 
-		/// EVM config used in the module.
+		/// EVM config used in the module, the config of the [`fork`](Config::fork) by default.
 		fn config() -> &'static EvmConfig {
-			&LONDON_CONFIG
+			Self::fork().config()
+		}
+
+		/// Hard fork whose EVM config is used. Runtimes move to a later fork with a runtime
+		/// upgrade.
+		fn fork() -> EvmFork {
+			EvmFork::London
 		}
 	}
 
@@ -464,6 +473,8 @@ pub mod pallet {
 		Reentrancy,
 		/// EIP-3607,
 		TransactionMustComeFromEOA,
+		/// Init code exceeds the EIP-3860 limit.
+		InitcodeTooLarge,
 	}
 
 	impl<T> From<InvalidEvmTransactionError> for Error<T> {
@@ -477,6 +488,7 @@ pub mod pallet {
 				InvalidEvmTransactionError::TxNonceTooLow => Error::<T>::InvalidNonce,
 				InvalidEvmTransactionError::TxNonceTooHigh => Error::<T>::InvalidNonce,
 				InvalidEvmTransactionError::InvalidPaymentInput => Error::<T>::GasPriceTooLow,
+				InvalidEvmTransactionError::InitcodeTooLarge => Error::<T>::InitcodeTooLarge,
 				_ => Error::<T>::Undefined,
 			}
 		}
@@ -687,8 +699,6 @@ impl<T: Config> GasWeightMapping for FixedGasWeightMapping<T> {
 	}
 }
 
-static LONDON_CONFIG: EvmConfig = EvmConfig::london();
-
 impl<T: Config> Pallet<T> {
 	/// Check whether an account is empty.
 	pub fn is_account_empty(address: &H160) -> bool {
@@ -18,7 +18,7 @@
 pub mod stack;
 
 use crate::Config;
-use fp_evm::{CallInfo, CreateInfo};
+use fp_evm::{CallInfo, CreateInfo, StateOverride};
 use sp_core::{H160, H256, U256};
 use sp_std::vec::Vec;
 
@@ -88,4 +88,28 @@ pub trait Runner<T: Config> {
 		validate: bool,
 		config: &evm::Config,
 	) -> Result<CreateInfo, RunnerError<Self::Error>>;
+
+	/// Executes a non-transactional call or, when `target` is `None`, a create, returning
+	/// its result along with the addresses and storage slots it accessed, in the format of
+	/// an EIP-2930 access list. The source, target and precompile addresses are only
+	/// listed when some of their storage slots were accessed.
+	fn create_access_list(
+		source: H160,
+		target: Option<H160>,
+		input: Vec<u8>,
+		value: U256,
+		gas_limit: u64,
+		max_fee_per_gas: Option<U256>,
+		max_priority_fee_per_gas: Option<U256>,
+		nonce: Option<U256>,
+		access_list: Vec<(H160, Vec<H256>)>,
+		config: &evm::Config,
+	) -> Result<(CallInfo, Vec<(H160, Vec<H256>)>), RunnerError<Self::Error>>;
+
+	/// Runs `f` with the state of each account overridden as given by `state_overrides`.
+	/// All the storage changes made, including the overrides, are reverted once `f` returns.
+	fn with_state_overrides<R, F: FnOnce() -> R>(
+		state_overrides: Vec<(H160, StateOverride)>,
+		f: F,
+	) -> R;
 }
@@ -27,10 +27,10 @@ use evm::{
 	executor::stack::{Accessed, StackExecutor, StackState as StackStateT, StackSubstateMetadata},
 	ExitError, ExitReason, Transfer,
 };
-use fp_evm::{CallInfo, CreateInfo, ExecutionInfo, Log, PrecompileSet, Vicinity};
+use fp_evm::{CallInfo, CreateInfo, ExecutionInfo, Log, PrecompileSet, StateOverride, Vicinity};
 use frame_support::traits::{Currency, ExistenceRequirement, Get};
 use sp_core::{H160, H256, U256};
-use sp_runtime::traits::UniqueSaturatedInto;
+use sp_runtime::traits::{Bounded, UniqueSaturatedInto};
 use sp_std::{
 	boxed::Box,
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
@@ -476,12 +476,121 @@ where
 			},
 		)
 	}
+
+	fn create_access_list(
+		source: H160,
+		target: Option<H160>,
+		input: Vec<u8>,
+		value: U256,
+		gas_limit: u64,
+		max_fee_per_gas: Option<U256>,
+		max_priority_fee_per_gas: Option<U256>,
+		nonce: Option<U256>,
+		access_list: Vec<(H160, Vec<H256>)>,
+		config: &evm::Config,
+	) -> Result<(CallInfo, Vec<(H160, Vec<H256>)>), RunnerError<Self::Error>> {
+		Self::validate(
+			source,
+			target,
+			input.clone(),
+			value,
+			gas_limit,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			nonce,
+			access_list.clone(),
+			false,
+			config,
+		)?;
+		let precompiles = T::PrecompilesValue::get();
+		let info = Self::execute(
+			source,
+			value,
+			gas_limit,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			config,
+			&precompiles,
+			false,
+			|executor| {
+				let (reason, retv, target) = match target {
+					Some(target) => {
+						let (reason, retv) = executor.transact_call(
+							source,
+							target,
+							value,
+							input,
+							gas_limit,
+							access_list,
+						);
+						(reason, retv, target)
+					}
+					None => {
+						let address =
+							executor.create_address(evm::CreateScheme::Legacy { caller: source });
+						let (reason, retv) =
+							executor.transact_create(source, value, input, gas_limit, access_list);
+						(reason, retv, address)
+					}
+				};
+				let accessed = executor.state().metadata().accessed().clone();
+				(reason, (retv, target, accessed))
+			},
+		)?;
+
+		let (retv, target, accessed) = info.value;
+		// A warm coinbase gains nothing from being listed.
+		let coinbase = T::config()
+			.warm_coinbase_address
+			.then(Pallet::<T>::find_author);
+		let mut access_list: BTreeMap<H160, Vec<H256>> = BTreeMap::new();
+		if let Some(accessed) = accessed {
+			for (address, index) in accessed.accessed_storage {
+				access_list.entry(address).or_default().push(index);
+			}
+			for address in accessed.accessed_addresses {
+				if address != source
+					&& address != target
+					&& Some(address) != coinbase
+					&& !precompiles.is_precompile(address)
+				{
+					access_list.entry(address).or_default();
+				}
+			}
+		}
+
+		Ok((
+			ExecutionInfo {
+				exit_reason: info.exit_reason,
+				value: retv,
+				used_gas: info.used_gas,
+				logs: info.logs,
+			},
+			access_list.into_iter().collect(),
+		))
+	}
+
+	fn with_state_overrides<R, F: FnOnce() -> R>(
+		state_overrides: Vec<(H160, StateOverride)>,
+		f: F,
+	) -> R {
+		sp_io::storage::start_transaction();
+		for (address, state_override) in state_overrides {
+			SubstrateStackState::<T>::apply_state_override(address, state_override);
+		}
+		let result = f();
+		sp_io::storage::rollback_transaction();
+
+		result
+	}
 }
 
 struct SubstrateStackSubstate<'config> {
 	metadata: StackSubstateMetadata<'config>,
 	deletes: BTreeSet<H160>,
 	logs: Vec<Log>,
+	/// Transient storage written in this substate (EIP-1153), discarded with the transaction.
+	transient_storage: BTreeMap<(H160, H256), U256>,
 	parent: Option<Box<SubstrateStackSubstate<'config>>>,
 }
 
@@ -500,6 +609,7 @@ impl<'config> SubstrateStackSubstate<'config> {
 			parent: None,
 			deletes: BTreeSet::new(),
 			logs: Vec::new(),
+			transient_storage: BTreeMap::new(),
 		};
 		mem::swap(&mut entering, self);
 
@@ -515,6 +625,7 @@ impl<'config> SubstrateStackSubstate<'config> {
 		self.metadata.swallow_commit(exited.metadata)?;
 		self.logs.append(&mut exited.logs);
 		self.deletes.append(&mut exited.deletes);
+		self.transient_storage.append(&mut exited.transient_storage);
 
 		sp_io::storage::commit_transaction();
 		Ok(())
@@ -554,6 +665,22 @@ impl<'config> SubstrateStackSubstate<'config> {
 		self.deletes.insert(address);
 	}
 
+	pub fn known_transient_storage(&self, address: H160, index: H256) -> Option<U256> {
+		if let Some(value) = self.transient_storage.get(&(address, index)) {
+			return Some(*value);
+		}
+
+		if let Some(parent) = self.parent.as_ref() {
+			return parent.known_transient_storage(address, index);
+		}
+
+		None
+	}
+
+	pub fn set_transient_storage(&mut self, address: H160, index: H256, value: U256) {
+		self.transient_storage.insert((address, index), value);
+	}
+
 	pub fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
 		self.logs.push(Log {
 			address,
@@ -592,12 +719,66 @@ impl<'vicinity, 'config, T: Config> SubstrateStackState<'vicinity, 'config, T> {
 				metadata,
 				deletes: BTreeSet::new(),
 				logs: Vec::new(),
+				transient_storage: BTreeMap::new(),
 				parent: None,
 			},
 			_marker: PhantomData,
 			original_storage: BTreeMap::new(),
 		}
 	}
+
+	/// Overrides the state of an account in storage.
+	fn apply_state_override(address: H160, state_override: StateOverride)
+	where
+		BalanceOf<T>: TryFrom<U256>,
+	{
+		let account_id = T::AddressMapping::into_account_id(address);
+
+		if let Some(balance) = state_override.balance {
+			let balance = balance
+				.try_into()
+				.unwrap_or_else(|_| BalanceOf::<T>::max_value());
+			let _ = T::Currency::make_free_balance_be(&account_id, balance);
+		}
+
+		if let Some(nonce) = state_override.nonce {
+			let nonce: u128 = nonce.try_into().unwrap_or(u128::MAX);
+			frame_system::Account::<T>::mutate(&account_id, |account| {
+				account.nonce = nonce.unique_saturated_into();
+			});
+		}
+
+		if let Some(code) = state_override.code {
+			if code.is_empty() {
+				if <AccountCodes<T>>::contains_key(address) {
+					let _ = frame_system::Pallet::<T>::dec_sufficients(&account_id);
+				}
+				<AccountCodes<T>>::remove(address);
+			} else {
+				Pallet::<T>::create_account(address, code);
+			}
+		}
+
+		if let Some(state) = state_override.state {
+			#[allow(deprecated)]
+			let _ = <AccountStorages<T>>::remove_prefix(address, None);
+			for (index, value) in state {
+				if value != H256::default() {
+					<AccountStorages<T>>::insert(address, index, value);
+				}
+			}
+		}
+
+		if let Some(state_diff) = state_override.state_diff {
+			for (index, value) in state_diff {
+				if value == H256::default() {
+					<AccountStorages<T>>::remove(address, index);
+				} else {
+					<AccountStorages<T>>::insert(address, index, value);
+				}
+			}
+		}
+	}
 }
 
 impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity, 'config, T> {
@@ -678,6 +859,10 @@ impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity,
 		let (base_fee, _) = T::FeeCalculator::min_gas_price();
 		base_fee
 	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		None
+	}
 }
 
 impl<'vicinity, 'config, T: Config> StackStateT<'config>
@@ -760,6 +945,24 @@ where
 		let _ = <AccountStorages<T>>::remove_prefix(address, None);
 	}
 
+	fn tload(&mut self, address: H160, index: H256) -> Result<U256, ExitError> {
+		Ok(self
+			.substate
+			.known_transient_storage(address, index)
+			.unwrap_or_default())
+	}
+
+	fn tstore(&mut self, address: H160, index: H256, value: U256) -> Result<(), ExitError> {
+		// Transient storage is not writable in static calls (EIP-1153).
+		if self.substate.metadata.is_static() {
+			return Err(ExitError::Other(
+				"transient storage write in static context".into(),
+			));
+		}
+		self.substate.set_transient_storage(address, index, value);
+		Ok(())
+	}
+
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
 		self.substate.log(address, topics, data)
 	}
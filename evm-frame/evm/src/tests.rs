@@ -611,3 +611,60 @@ fn eip3607_transaction_from_precompile_should_fail() {
 		}
 	});
 }
+
+fn call_code_with_config(code: Vec<u8>, config: &EvmConfig) -> CallInfo {
+	let contract = H160::from_str("1000000000000000000000000000000000000002").unwrap();
+	<crate::AccountCodes<Test>>::insert(contract, code);
+	<Test as Config>::Runner::call(
+		H160::default(),
+		contract,
+		Vec::new(),
+		U256::zero(),
+		1000000,
+		None,
+		None,
+		None,
+		Vec::new(),
+		false, // non-transactional
+		true,  // must be validated
+		config,
+	)
+	.expect("Non transactional call succeeds")
+}
+
+#[test]
+fn cancun_opcodes_are_dispatched_from_cancun() {
+	// TSTORE 42 at slot 1, TLOAD it to memory with PUSH0 as offset, MCOPY it to the
+	// second word and return that word.
+	let code = hex::decode("602a60015d60015c5f5260205f60205e60206020f3").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let info = call_code_with_config(code.clone(), EvmFork::Cancun.config());
+		assert_eq!(info.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(info.value, H256::from_low_u64_be(42).as_bytes().to_vec());
+
+		for fork in [EvmFork::London, EvmFork::Shanghai] {
+			let info = call_code_with_config(code.clone(), fork.config());
+			assert!(!info.exit_reason.is_succeed());
+		}
+	});
+}
+
+#[test]
+fn create_initcode_is_limited_from_shanghai() {
+	// CREATE a contract from MAX_INITCODE_SIZE + 1 bytes of memory and return its address.
+	let code = hex::decode("6200c0015f5ff05f5260205ff3").unwrap();
+	let created =
+		|info: CallInfo| info.exit_reason.is_succeed() && info.value.iter().any(|byte| *byte != 0);
+
+	new_test_ext().execute_with(|| {
+		assert!(created(call_code_with_config(
+			code.clone(),
+			EvmFork::London.config()
+		)));
+		assert!(!created(call_code_with_config(
+			code.clone(),
+			EvmFork::Shanghai.config()
+		)));
+	});
+}
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collects execution traces from the events emitted by the EVM interpreter.
+//!
+//! Requires the `tracing` feature, which turns on event emission in `evm`,
+//! `evm-runtime` and `evm-gasometer`. Traces are only collected for code
+//! executed within [`Tracer::trace`].
+
+use evm::{tracing::Event as EvmEvent, CreateScheme};
+use evm_gasometer::tracing::{Event as GasometerEvent, Snapshot};
+use evm_runtime::tracing::Event as RuntimeEvent;
+use sp_core::{H160, H256, U256};
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap, rc::Rc, vec::Vec};
+
+pub use fp_evm::tracing::{CallTrace, CallType, StructLog, TraceType, TransactionTrace};
+
+/// Listener building a [`TransactionTrace`] out of interpreter events.
+pub struct Tracer {
+	trace_type: TraceType,
+	/// Number of frames currently executing.
+	depth: u32,
+	/// Step being executed, pushed to `struct_logs` once its result is known.
+	step: Option<StructLog>,
+	struct_logs: Vec<StructLog>,
+	/// Storage slots touched so far, by contract.
+	storage: BTreeMap<H160, BTreeMap<H256, H256>>,
+	/// Gas limit of the transaction, as reported by the executor.
+	gas_limit: U256,
+	/// Frames currently executing, innermost last.
+	frames: Vec<CallTrace>,
+	root: Option<CallTrace>,
+	return_value: Vec<u8>,
+	failed: bool,
+}
+
+impl Tracer {
+	pub fn new(trace_type: TraceType) -> Self {
+		Self {
+			trace_type,
+			depth: 0,
+			step: None,
+			struct_logs: Vec::new(),
+			storage: BTreeMap::new(),
+			gas_limit: U256::zero(),
+			frames: Vec::new(),
+			root: None,
+			return_value: Vec::new(),
+			failed: false,
+		}
+	}
+
+	/// Runs `f` with this tracer listening to the interpreter events.
+	pub fn trace<R, F: FnOnce() -> R>(self, f: F) -> (Self, R) {
+		let shared = Rc::new(RefCell::new(self));
+		let result = {
+			let mut evm_listener = ListenerProxy(Rc::clone(&shared));
+			let mut runtime_listener = ListenerProxy(Rc::clone(&shared));
+			let mut gasometer_listener = ListenerProxy(Rc::clone(&shared));
+
+			evm::tracing::using(&mut evm_listener, || {
+				evm_runtime::tracing::using(&mut runtime_listener, || {
+					evm_gasometer::tracing::using(&mut gasometer_listener, f)
+				})
+			})
+		};
+		let tracer = match Rc::try_unwrap(shared) {
+			Ok(tracer) => tracer.into_inner(),
+			Err(_) => unreachable!("all listener proxies are dropped at this point; qed"),
+		};
+
+		(tracer, result)
+	}
+
+	/// Builds the trace, `used_gas` being the gas used by the whole transaction.
+	pub fn finish(self, used_gas: U256) -> TransactionTrace {
+		match self.trace_type {
+			TraceType::Raw { .. } => TransactionTrace::Raw {
+				gas: used_gas,
+				failed: self.failed,
+				return_value: self.return_value,
+				struct_logs: self.struct_logs,
+			},
+			TraceType::CallTree => {
+				let mut root = self.root.unwrap_or_else(|| CallTrace {
+					call_type: CallType::Call,
+					from: H160::zero(),
+					to: H160::zero(),
+					value: U256::zero(),
+					gas: self.gas_limit,
+					gas_used: U256::zero(),
+					input: Vec::new(),
+					output: Vec::new(),
+					exit_reason: None,
+					calls: Vec::new(),
+				});
+				if !self.gas_limit.is_zero() {
+					root.gas = self.gas_limit;
+				}
+				root.gas_used = used_gas;
+				TransactionTrace::CallTree(root)
+			}
+		}
+	}
+
+	fn is_raw(&self) -> bool {
+		matches!(self.trace_type, TraceType::Raw { .. })
+	}
+
+	fn enter(&mut self, frame: CallTrace) {
+		self.depth += 1;
+		if !self.is_raw() {
+			self.frames.push(frame);
+		}
+	}
+
+	fn evm_event(&mut self, event: EvmEvent) {
+		match event {
+			EvmEvent::TransactCall { gas_limit, .. }
+			| EvmEvent::TransactCreate { gas_limit, .. }
+			| EvmEvent::TransactCreate2 { gas_limit, .. } => {
+				self.gas_limit = gas_limit;
+			}
+			EvmEvent::Call {
+				code_address,
+				transfer,
+				input,
+				target_gas,
+				is_static,
+				context,
+			} => {
+				let call_type = match (code_address == context.address, is_static) {
+					(true, true) => CallType::StaticCall,
+					(true, false) => CallType::Call,
+					(false, _) if transfer.is_none() => CallType::DelegateCall,
+					(false, _) => CallType::CallCode,
+				};
+				self.enter(CallTrace {
+					call_type,
+					from: context.caller,
+					to: code_address,
+					value: transfer
+						.as_ref()
+						.map(|transfer| transfer.value)
+						.unwrap_or(context.apparent_value),
+					gas: target_gas.map(U256::from).unwrap_or_default(),
+					gas_used: U256::zero(),
+					input: input.to_vec(),
+					output: Vec::new(),
+					exit_reason: None,
+					calls: Vec::new(),
+				});
+			}
+			EvmEvent::Create {
+				caller,
+				address,
+				scheme,
+				value,
+				init_code,
+				target_gas,
+			} => {
+				let call_type = match scheme {
+					CreateScheme::Create2 { .. } => CallType::Create2,
+					_ => CallType::Create,
+				};
+				self.enter(CallTrace {
+					call_type,
+					from: caller,
+					to: address,
+					value,
+					gas: target_gas.map(U256::from).unwrap_or_default(),
+					gas_used: U256::zero(),
+					input: init_code.to_vec(),
+					output: Vec::new(),
+					exit_reason: None,
+					calls: Vec::new(),
+				});
+			}
+			EvmEvent::Suicide {
+				address,
+				target,
+				balance,
+			} => {
+				if let Some(parent) = self.frames.last_mut() {
+					parent.calls.push(CallTrace {
+						call_type: CallType::SelfDestruct,
+						from: address,
+						to: target,
+						value: balance,
+						gas: U256::zero(),
+						gas_used: U256::zero(),
+						input: Vec::new(),
+						output: Vec::new(),
+						exit_reason: None,
+						calls: Vec::new(),
+					});
+				}
+			}
+			EvmEvent::Exit {
+				reason,
+				return_value,
+			} => {
+				self.depth = self.depth.saturating_sub(1);
+				if self.depth == 0 {
+					self.return_value = return_value.to_vec();
+					self.failed = !reason.is_succeed();
+				}
+				if let Some(mut frame) = self.frames.pop() {
+					frame.output = return_value.to_vec();
+					frame.exit_reason = Some(reason.clone());
+					match self.frames.last_mut() {
+						Some(parent) => parent.calls.push(frame),
+						None => self.root = Some(frame),
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn runtime_event(&mut self, event: RuntimeEvent) {
+		let (disable_storage, disable_memory, disable_stack) = match self.trace_type {
+			TraceType::Raw {
+				disable_storage,
+				disable_memory,
+				disable_stack,
+			} => (disable_storage, disable_memory, disable_stack),
+			TraceType::CallTree => return,
+		};
+
+		match event {
+			RuntimeEvent::Step {
+				opcode,
+				position,
+				stack,
+				memory,
+				..
+			} => {
+				self.step = Some(StructLog {
+					pc: position.as_ref().map(|pc| *pc as u64).unwrap_or_default(),
+					op: opcode.0,
+					gas: 0,
+					gas_cost: 0,
+					depth: self.depth,
+					stack: (!disable_stack).then(|| stack.data().clone()),
+					memory: (!disable_memory).then(|| memory.data().clone()),
+					storage: None,
+				});
+			}
+			RuntimeEvent::StepResult { .. } => {
+				if let Some(step) = self.step.take() {
+					self.struct_logs.push(step);
+				}
+			}
+			RuntimeEvent::SLoad {
+				address,
+				index,
+				value,
+			}
+			| RuntimeEvent::SStore {
+				address,
+				index,
+				value,
+			} => {
+				if disable_storage {
+					return;
+				}
+				let storage = self.storage.entry(address).or_default();
+				storage.insert(index, value);
+				if let Some(step) = self.step.as_mut() {
+					step.storage = Some(storage.iter().map(|(k, v)| (*k, *v)).collect());
+				}
+			}
+		}
+	}
+
+	fn gasometer_event(&mut self, event: GasometerEvent) {
+		let (cost, snapshot) = match event {
+			GasometerEvent::RecordCost { cost, snapshot } => (cost, snapshot),
+			GasometerEvent::RecordDynamicCost {
+				gas_cost,
+				memory_gas,
+				snapshot,
+				..
+			} => {
+				let memory_cost = snapshot
+					.map(|snapshot| memory_gas.saturating_sub(snapshot.memory_gas))
+					.unwrap_or_default();
+				(gas_cost.saturating_add(memory_cost), snapshot)
+			}
+			_ => return,
+		};
+		let snapshot = match snapshot {
+			Some(snapshot) => snapshot,
+			None => return,
+		};
+
+		if let Some(step) = self.step.as_mut() {
+			// The snapshot is taken before the cost is recorded, so the first
+			// one of a step tells how much gas was left when it started.
+			if step.gas_cost == 0 {
+				step.gas = remaining_gas(&snapshot);
+			}
+			step.gas_cost = step.gas_cost.saturating_add(cost);
+		}
+		if let Some(frame) = self.frames.last_mut() {
+			frame.gas_used = U256::from(
+				snapshot
+					.used_gas
+					.saturating_add(snapshot.memory_gas)
+					.saturating_add(cost),
+			);
+		}
+	}
+}
+
+fn remaining_gas(snapshot: &Snapshot) -> u64 {
+	snapshot
+		.gas_limit
+		.saturating_sub(snapshot.used_gas.saturating_add(snapshot.memory_gas))
+}
+
+/// The interpreter crates each expect their own exclusive listener, this
+/// forwards their events to a tracer shared between them.
+struct ListenerProxy(Rc<RefCell<Tracer>>);
+
+impl evm::tracing::EventListener for ListenerProxy {
+	fn event(&mut self, event: EvmEvent) {
+		self.0.borrow_mut().evm_event(event);
+	}
+}
+
+impl evm_runtime::tracing::EventListener for ListenerProxy {
+	fn event(&mut self, event: RuntimeEvent) {
+		self.0.borrow_mut().runtime_event(event);
+	}
+}
+
+impl evm_gasometer::tracing::EventListener for ListenerProxy {
+	fn event(&mut self, event: GasometerEvent) {
+		self.0.borrow_mut().gasometer_event(event);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use super::*;
+	use crate::{mock::*, AccountCodes, Config, ExitReason, ExitRevert, ExitSucceed, Runner};
+
+	fn contract(index: u64, code: &str) -> H160 {
+		let address = H160::from_low_u64_be(0x1000 + index);
+		<AccountCodes<Test>>::insert(address, hex::decode(code).unwrap());
+		address
+	}
+
+	fn trace_call(to: H160, trace_type: TraceType) -> TransactionTrace {
+		let caller = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let (tracer, info) = Tracer::new(trace_type).trace(|| {
+			<Test as Config>::Runner::call(
+				caller,
+				to,
+				Vec::new(),
+				U256::zero(),
+				1000000,
+				None,
+				None,
+				None,
+				Vec::new(),
+				false, // non-transactional
+				true,  // must be validated
+				<Test as Config>::config(),
+			)
+			.expect("Non transactional call succeeds")
+		});
+		tracer.finish(info.used_gas)
+	}
+
+	#[test]
+	fn call_tree_nests_subcalls() {
+		new_test_ext().execute_with(|| {
+			let callee = contract(3, "00");
+			// CALL the callee with no value nor data, then STOP.
+			let caller = contract(
+				2,
+				&format!("6000600060006000600073{}5af100", hex::encode(callee)),
+			);
+
+			let root = match trace_call(caller, TraceType::CallTree) {
+				TransactionTrace::CallTree(root) => root,
+				trace => panic!("unexpected trace {:?}", trace),
+			};
+			assert_eq!(root.call_type, CallType::Call);
+			assert_eq!(root.to, caller);
+			assert_eq!(root.gas, U256::from(1000000));
+			assert_eq!(
+				root.exit_reason,
+				Some(ExitReason::Succeed(ExitSucceed::Stopped))
+			);
+			assert_eq!(root.calls.len(), 1);
+
+			let subcall = &root.calls[0];
+			assert_eq!(subcall.call_type, CallType::Call);
+			assert_eq!((subcall.from, subcall.to), (caller, callee));
+			assert_eq!(
+				subcall.exit_reason,
+				Some(ExitReason::Succeed(ExitSucceed::Stopped))
+			);
+			assert!(subcall.calls.is_empty());
+		});
+	}
+
+	#[test]
+	fn call_tree_reports_reverts() {
+		new_test_ext().execute_with(|| {
+			// REVERT with no data.
+			let reverting = contract(2, "60006000fd");
+
+			match trace_call(reverting, TraceType::CallTree) {
+				TransactionTrace::CallTree(root) => assert_eq!(
+					root.exit_reason,
+					Some(ExitReason::Revert(ExitRevert::Reverted))
+				),
+				trace => panic!("unexpected trace {:?}", trace),
+			}
+		});
+	}
+
+	#[test]
+	fn raw_trace_logs_each_step() {
+		new_test_ext().execute_with(|| {
+			// SSTORE 42 at slot 0, then STOP.
+			let store = contract(2, "602a60005500");
+
+			let trace = trace_call(
+				store,
+				TraceType::Raw {
+					disable_storage: false,
+					disable_memory: true,
+					disable_stack: true,
+				},
+			);
+			let struct_logs = match trace {
+				TransactionTrace::Raw {
+					failed,
+					return_value,
+					struct_logs,
+					..
+				} => {
+					assert!(!failed);
+					assert!(return_value.is_empty());
+					struct_logs
+				}
+				trace => panic!("unexpected trace {:?}", trace),
+			};
+
+			let steps: Vec<_> = struct_logs.iter().map(|log| (log.pc, log.op)).collect();
+			assert_eq!(steps, vec![(0, 0x60), (2, 0x60), (4, 0x55), (5, 0x00)]);
+			assert!(struct_logs.iter().all(|log| log.depth == 1));
+			assert!(struct_logs
+				.iter()
+				.all(|log| log.stack.is_none() && log.memory.is_none()));
+			// Gas decreases by the cost of each step.
+			for pair in struct_logs.windows(2) {
+				assert_eq!(pair[1].gas, pair[0].gas - pair[0].gas_cost);
+			}
+			assert_eq!(
+				struct_logs[2].storage,
+				Some(vec![(H256::zero(), H256::from_low_u64_be(42))])
+			);
+			assert_eq!(struct_logs[3].storage, None);
+		});
+	}
+}
@@ -37,6 +37,9 @@ pub enum TransactionValidationError {
 	GasLimitTooLow,
 	GasLimitTooHigh,
 	MaxFeePerGasTooLow,
+	GasPriceTooLow,
+	InvalidFeeInput,
+	InitcodeTooLarge,
 }
 
 pub trait ValidatedTransaction {
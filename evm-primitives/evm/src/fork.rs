@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Maximum size of the init code of a contract creation (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * 24576;
+
+static LONDON_CONFIG: evm::Config = evm::Config::london();
+static SHANGHAI_CONFIG: evm::Config = evm::Config::shanghai();
+static CANCUN_CONFIG: evm::Config = evm::Config {
+	eip_1153_enabled: true,
+	eip_5656_enabled: true,
+	..evm::Config::shanghai()
+};
+
+/// Hard fork whose `evm::Config` a runtime applies.
+///
+/// Shanghai warms the coinbase address (EIP-3651), adds `PUSH0` (EIP-3855) and limits
+/// and meters init code, of creation transactions as well as of `CREATE` and `CREATE2`
+/// (EIP-3860). Cancun adds transient storage with `TLOAD` and `TSTORE` (EIP-1153) and
+/// `MCOPY` (EIP-5656).
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum EvmFork {
+	London,
+	Shanghai,
+	Cancun,
+}
+
+impl EvmFork {
+	/// EVM config of the fork.
+	pub fn config(&self) -> &'static evm::Config {
+		match self {
+			EvmFork::London => &LONDON_CONFIG,
+			EvmFork::Shanghai => &SHANGHAI_CONFIG,
+			EvmFork::Cancun => &CANCUN_CONFIG,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn configs_enable_the_fork_opcodes() {
+		let london = EvmFork::London.config();
+		assert!(!london.has_push0 && !london.warm_coinbase_address);
+		assert_eq!(london.max_initcode_size, None);
+
+		let shanghai = EvmFork::Shanghai.config();
+		assert!(shanghai.has_push0 && shanghai.warm_coinbase_address);
+		assert_eq!(shanghai.max_initcode_size, Some(MAX_INITCODE_SIZE));
+		assert!(!shanghai.eip_1153_enabled && !shanghai.eip_5656_enabled);
+
+		let cancun = EvmFork::Cancun.config();
+		assert!(cancun.has_push0 && cancun.eip_1153_enabled && cancun.eip_5656_enabled);
+		assert_eq!(cancun.max_initcode_size, Some(MAX_INITCODE_SIZE));
+	}
+}
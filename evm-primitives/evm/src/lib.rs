@@ -17,14 +17,16 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod fork;
 mod precompile;
+pub mod tracing;
 mod validation;
 
 use frame_support::weights::Weight;
 use scale_codec::{Decode, Encode};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_core::{H160, U256};
+use sp_core::{H160, H256, U256};
 use sp_std::vec::Vec;
 
 pub use evm::{
@@ -33,6 +35,7 @@ pub use evm::{
 };
 
 pub use self::{
+	fork::{EvmFork, MAX_INITCODE_SIZE},
 	precompile::{
 		Context, ExitError, ExitRevert, ExitSucceed, LinearCostPrecompile, Precompile,
 		PrecompileFailure, PrecompileHandle, PrecompileOutput, PrecompileResult, PrecompileSet,
@@ -87,6 +90,22 @@ pub struct GenesisAccount {
 	pub code: Vec<u8>,
 }
 
+/// Overrides of an account state, applied before executing a non-transactional call.
+#[derive(Clone, Eq, PartialEq, Default, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct StateOverride {
+	/// Balance to set.
+	pub balance: Option<U256>,
+	/// Nonce to set.
+	pub nonce: Option<U256>,
+	/// Code to set.
+	pub code: Option<Vec<u8>>,
+	/// Storage slots replacing the whole account storage.
+	pub state: Option<Vec<(H256, H256)>>,
+	/// Storage slots to set, leaving the other ones untouched.
+	pub state_diff: Option<Vec<(H256, H256)>>,
+}
+
 /// Trait that outputs the current transaction gas price.
 pub trait FeeCalculator {
 	/// Return the minimal required gas price.
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types describing the execution trace of an EVM transaction, as collected
+//! inside the runtime and handed back to the client.
+
+use scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{H160, H256, U256};
+use sp_std::vec::Vec;
+
+use crate::ExitReason;
+
+/// Kind of trace to collect while executing a transaction.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum TraceType {
+	/// Opcode level trace, one entry per executed step.
+	Raw {
+		disable_storage: bool,
+		disable_memory: bool,
+		disable_stack: bool,
+	},
+	/// Tree of the calls and creates performed by the transaction.
+	CallTree,
+}
+
+/// A single executed opcode.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: u64,
+	/// Raw opcode.
+	pub op: u8,
+	/// Gas remaining before the opcode is executed.
+	pub gas: u64,
+	/// Gas charged for the opcode.
+	pub gas_cost: u64,
+	/// Call depth, starting at 1 for the top level frame.
+	pub depth: u32,
+	/// Stack content before the opcode is executed.
+	pub stack: Option<Vec<H256>>,
+	/// Memory content before the opcode is executed.
+	pub memory: Option<Vec<u8>>,
+	/// Storage slots of the current contract touched so far, only
+	/// reported on `SLOAD` and `SSTORE`.
+	pub storage: Option<Vec<(H256, H256)>>,
+}
+
+/// Kind of frame in a call tree.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CallType {
+	Call,
+	CallCode,
+	DelegateCall,
+	StaticCall,
+	Create,
+	Create2,
+	SelfDestruct,
+}
+
+/// A frame of a call tree, along with its sub-calls.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CallTrace {
+	pub call_type: CallType,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: U256,
+	pub gas_used: U256,
+	pub input: Vec<u8>,
+	pub output: Vec<u8>,
+	/// `None` while the frame is still executing, or for self-destructs.
+	pub exit_reason: Option<ExitReason>,
+	pub calls: Vec<CallTrace>,
+}
+
+/// Outcome of tracing a transaction.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum TransactionTrace {
+	/// Result of a `TraceType::Raw` trace.
+	Raw {
+		gas: U256,
+		failed: bool,
+		return_value: Vec<u8>,
+		struct_logs: Vec<StructLog>,
+	},
+	/// Result of a `TraceType::CallTree` trace, rooted at the top level frame.
+	CallTree(CallTrace),
+}
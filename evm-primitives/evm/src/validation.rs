@@ -62,6 +62,7 @@ pub enum InvalidEvmTransactionError {
 	TxNonceTooHigh,
 	InvalidPaymentInput,
 	InvalidChainId,
+	InitcodeTooLarge,
 }
 
 impl<'config, E: From<InvalidEvmTransactionError>> CheckEvmTransaction<'config, E> {
@@ -178,6 +179,15 @@ impl<'config, E: From<InvalidEvmTransactionError>> CheckEvmTransaction<'config,
 
 	pub fn validate_common(&self) -> Result<&Self, E> {
 		if self.config.is_transactional {
+			// Init code is limited from Shanghai (EIP-3860), its cost being part of the
+			// transaction cost.
+			if let Some(max_initcode_size) = self.config.evm_config.max_initcode_size {
+				if self.transaction.to.is_none() && self.transaction.input.len() > max_initcode_size
+				{
+					return Err(InvalidEvmTransactionError::InitcodeTooLarge.into());
+				}
+			}
+
 			// We must ensure a transaction can pay the cost of its data bytes.
 			// If it can't it should not be included in a block.
 			let mut gasometer = evm::gasometer::Gasometer::new(
@@ -213,6 +223,7 @@ impl<'config, E: From<InvalidEvmTransactionError>> CheckEvmTransaction<'config,
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::EvmFork;
 
 	#[derive(Debug, PartialEq)]
 	pub enum TestError {
@@ -225,10 +236,9 @@ mod tests {
 		TxNonceTooHigh,
 		InvalidPaymentInput,
 		InvalidChainId,
+		InitcodeTooLarge,
 	}
 
-	static LONDON_CONFIG: evm::Config = evm::Config::london();
-
 	impl From<InvalidEvmTransactionError> for TestError {
 		fn from(e: InvalidEvmTransactionError) -> Self {
 			match e {
@@ -241,6 +251,7 @@ mod tests {
 				InvalidEvmTransactionError::TxNonceTooHigh => TestError::TxNonceTooHigh,
 				InvalidEvmTransactionError::InvalidPaymentInput => TestError::InvalidPaymentInput,
 				InvalidEvmTransactionError::InvalidChainId => TestError::InvalidChainId,
+				InvalidEvmTransactionError::InitcodeTooLarge => TestError::InitcodeTooLarge,
 			}
 		}
 	}
@@ -257,6 +268,9 @@ mod tests {
 		pub max_fee_per_gas: Option<U256>,
 		pub max_priority_fee_per_gas: Option<U256>,
 		pub value: U256,
+		pub fork: EvmFork,
+		pub to: Option<H160>,
+		pub input: Vec<u8>,
 	}
 
 	impl Default for TestCase {
@@ -273,6 +287,9 @@ mod tests {
 				max_fee_per_gas: Some(U256::from(1_000_000_000u128)),
 				max_priority_fee_per_gas: Some(U256::from(1_000_000_000u128)),
 				value: U256::from(1u8),
+				fork: EvmFork::London,
+				to: Some(H160::default()),
+				input: vec![],
 			}
 		}
 	}
@@ -290,10 +307,13 @@ mod tests {
 			max_fee_per_gas,
 			max_priority_fee_per_gas,
 			value,
+			fork,
+			to,
+			input,
 		} = input;
 		CheckEvmTransaction::<TestError>::new(
 			CheckEvmTransactionConfig {
-				evm_config: &LONDON_CONFIG,
+				evm_config: fork.config(),
 				block_gas_limit: blockchain_gas_limit,
 				base_fee: blockchain_base_fee,
 				chain_id: blockchain_chain_id,
@@ -301,8 +321,8 @@ mod tests {
 			},
 			CheckEvmTransactionInput {
 				chain_id,
-				to: Some(H160::default()),
-				input: vec![],
+				to,
+				input,
 				nonce,
 				gas_limit,
 				gas_price,
@@ -407,6 +427,19 @@ mod tests {
 		test_env(input)
 	}
 
+	fn transaction_create<'config>(
+		fork: EvmFork,
+		initcode_len: usize,
+		gas_limit: u64,
+	) -> CheckEvmTransaction<'config, TestError> {
+		let mut input = TestCase::default();
+		input.fork = fork;
+		input.to = None;
+		input.input = vec![0u8; initcode_len];
+		input.gas_limit = U256::from(gas_limit);
+		test_env(input)
+	}
+
 	// Tests
 	#[test]
 	// Default (valid) transaction succeeds in pool and in block.
@@ -744,4 +777,35 @@ mod tests {
 		let res = test.with_base_fee();
 		assert!(res.is_ok());
 	}
+
+	#[test]
+	// Init code is limited from Shanghai (EIP-3860).
+	fn validate_initcode_size_from_shanghai() {
+		let initcode_len = crate::MAX_INITCODE_SIZE + 1;
+		let gas_limit = 10_000_000u64;
+		let test = transaction_create(EvmFork::London, initcode_len, gas_limit);
+		assert!(test.validate_common().is_ok());
+		let test = transaction_create(EvmFork::Shanghai, initcode_len, gas_limit);
+		let res = test.validate_common();
+		assert!(res.is_err());
+		assert_eq!(res.unwrap_err(), TestError::InitcodeTooLarge);
+		let test = transaction_create(EvmFork::Shanghai, crate::MAX_INITCODE_SIZE, gas_limit);
+		assert!(test.validate_common().is_ok());
+	}
+
+	#[test]
+	// Init code words are charged from Shanghai (EIP-3860).
+	fn validate_initcode_cost_from_shanghai() {
+		// 53_000 base cost and 4 gas per zero byte of init code.
+		let initcode_len = 64;
+		let gas_limit = 53_000 + 4 * initcode_len as u64;
+		let test = transaction_create(EvmFork::London, initcode_len, gas_limit);
+		assert!(test.validate_common().is_ok());
+		let test = transaction_create(EvmFork::Shanghai, initcode_len, gas_limit);
+		let res = test.validate_common();
+		assert!(res.is_err());
+		assert_eq!(res.unwrap_err(), TestError::GasLimitTooLow);
+		let test = transaction_create(EvmFork::Shanghai, initcode_len, gas_limit + 4);
+		assert!(test.validate_common().is_ok());
+	}
 }
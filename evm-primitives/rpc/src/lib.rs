@@ -24,7 +24,10 @@ use scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 // Substrate
 use sp_core::{H160, H256, U256};
-use sp_runtime::{traits::Block as BlockT, Permill, RuntimeDebug};
+use sp_runtime::{
+	traits::{BlakeTwo256, Block as BlockT},
+	Permill, RuntimeDebug,
+};
 use sp_std::vec::Vec;
 
 #[derive(Clone, Eq, PartialEq, Default, RuntimeDebug, Encode, Decode, TypeInfo)]
@@ -40,7 +43,7 @@ pub struct TransactionStatus {
 
 sp_api::decl_runtime_apis! {
 	/// API necessary for Ethereum-compatibility layer.
-	#[api_version(4)]
+	#[api_version(6)]
 	pub trait EthereumRuntimeRPCApi {
 		/// Returns runtime defined pallet_evm::ChainId.
 		fn chain_id() -> u64;
@@ -78,6 +81,7 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 		) -> Result<fp_evm::CallInfo, sp_runtime::DispatchError>;
+		#[changed_in(6)]
 		fn call(
 			from: H160,
 			to: H160,
@@ -90,6 +94,19 @@ sp_api::decl_runtime_apis! {
 			estimate: bool,
 			access_list: Option<Vec<(H160, Vec<H256>)>>,
 		) -> Result<fp_evm::CallInfo, sp_runtime::DispatchError>;
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			state_overrides: Option<Vec<(H160, fp_evm::StateOverride)>>,
+		) -> Result<fp_evm::CallInfo, sp_runtime::DispatchError>;
 		/// Returns a frame_ethereum::create response.
 		#[changed_in(2)]
 		fn create(
@@ -112,6 +129,18 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 		) -> Result<fp_evm::CreateInfo, sp_runtime::DispatchError>;
+		#[changed_in(6)]
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<fp_evm::CreateInfo, sp_runtime::DispatchError>;
 		fn create(
 			from: H160,
 			data: Vec<u8>,
@@ -122,6 +151,7 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			state_overrides: Option<Vec<(H160, fp_evm::StateOverride)>>,
 		) -> Result<fp_evm::CreateInfo, sp_runtime::DispatchError>;
 		/// Return the current block. Legacy.
 		#[changed_in(2)]
@@ -168,6 +198,19 @@ sp_api::decl_runtime_apis! {
 		/// Used to determine if gas limit multiplier for non-transactional calls (eth_call/estimateGas)
 		/// is supported.
 		fn gas_limit_multiplier_support();
+		/// Executes a call or, when `to` is `None`, a create, returning its result along with
+		/// the access list of the addresses and storage slots it touched.
+		fn create_access_list(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<(fp_evm::CallInfo, Vec<(H160, Vec<H256>)>), sp_runtime::DispatchError>;
 	}
 
 	#[api_version(2)]
@@ -176,6 +219,69 @@ sp_api::decl_runtime_apis! {
 		#[changed_in(2)]
 		fn convert_transaction(transaction: ethereum::TransactionV0) -> <Block as BlockT>::Extrinsic;
 	}
+
+	/// API used by the `debug_*` RPC namespace to trace EVM execution.
+	pub trait DebugRuntimeApi {
+		/// Applies `extrinsics` in order on top of an initialized block, tracing the
+		/// Ethereum transaction whose hash is `transaction_hash`. Extrinsics following
+		/// it are not applied.
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_hash: H256,
+			trace_type: fp_evm::tracing::TraceType,
+		) -> Result<fp_evm::tracing::TransactionTrace, sp_runtime::DispatchError>;
+		/// Applies `extrinsics` in order on top of an initialized block, returning the
+		/// call tree of each Ethereum transaction along with its hash.
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Result<Vec<(H256, fp_evm::tracing::CallTrace)>, sp_runtime::DispatchError>;
+		/// Traces a call or, when `to` is `None`, a create, as done by `call`/`create`
+		/// in `EthereumRuntimeRPCApi`.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			trace_type: fp_evm::tracing::TraceType,
+		) -> Result<fp_evm::tracing::TransactionTrace, sp_runtime::DispatchError>;
+	}
+
+	/// API exposing the storage keys the EIP-1186 proofs of an EVM account are built from.
+	pub trait EthereumProofRuntimeApi {
+		/// Returns the storage keys holding the basic info (nonce, balance) and the code
+		/// of an account.
+		fn account_proof_keys(address: H160) -> Vec<Vec<u8>>;
+		/// Returns the storage key of an account storage slot.
+		fn storage_proof_key(address: H160, index: H256) -> Vec<u8>;
+	}
+}
+
+/// A storage proof which could not be verified.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct InvalidProof;
+
+/// Verifies a proof returned by `eth_getProof`.
+///
+/// Proofs are Substrate trie read proofs rather than Ethereum Merkle-Patricia ones:
+/// `account_proof` proves the keys given by `EthereumProofRuntimeApi::account_proof_keys`
+/// and each `storage_proof` the key given by `EthereumProofRuntimeApi::storage_proof_key`,
+/// all against the block state root, returned as `storage_hash`.
+///
+/// Returns the SCALE encoded value stored under `key`, or `None` if the proof shows the key
+/// is not set.
+pub fn verify_storage_proof(
+	state_root: H256,
+	proof: Vec<Vec<u8>>,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, InvalidProof> {
+	let db = sp_trie::StorageProof::new(proof).into_memory_db::<BlakeTwo256>();
+	sp_trie::read_trie_value::<sp_trie::LayoutV1<BlakeTwo256>, _>(&db, &state_root, key, None, None)
+		.map_err(|_| InvalidProof)
 }
 
 pub trait ConvertTransaction<E> {
@@ -192,3 +298,54 @@ impl<E> ConvertTransaction<E> for NoTransactionConverter {
 		unreachable!()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use sp_trie::{MemoryDB, TrieDBMutBuilderV1, TrieMut};
+
+	use super::*;
+
+	/// Builds a state trie over `entries`, returning its root and all of its nodes as proof.
+	fn state(entries: &[(&[u8], &[u8])]) -> (H256, Vec<Vec<u8>>) {
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = H256::zero();
+		{
+			let mut trie = TrieDBMutBuilderV1::<BlakeTwo256>::new(&mut db, &mut root).build();
+			for (key, value) in entries {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		let proof = db.drain().into_values().map(|(node, _)| node).collect();
+		(root, proof)
+	}
+
+	#[test]
+	fn verify_storage_proof_reads_proven_values() {
+		let (root, proof) = state(&[(b"account", b"basic"), (b"slot", b"value")]);
+
+		assert_eq!(
+			verify_storage_proof(root, proof.clone(), b"slot"),
+			Ok(Some(b"value".to_vec()))
+		);
+		assert_eq!(verify_storage_proof(root, proof, b"missing"), Ok(None));
+	}
+
+	#[test]
+	fn verify_storage_proof_rejects_tampered_proofs() {
+		let (root, proof) = state(&[(b"account", b"basic"), (b"slot", b"value")]);
+
+		let mut tampered = proof.clone();
+		for node in tampered.iter_mut() {
+			let last = node.len() - 1;
+			node[last] ^= 1;
+		}
+		assert_eq!(
+			verify_storage_proof(root, tampered, b"slot"),
+			Err(InvalidProof)
+		);
+		assert_eq!(
+			verify_storage_proof(H256::repeat_byte(1), proof, b"slot"),
+			Err(InvalidProof)
+		);
+	}
+}